@@ -0,0 +1,406 @@
+use crate::commands::{parse_timestamp, CachedMetadata};
+use crate::currency::{self, DEFAULT_ITEM_CURRENCY};
+use crate::library::{self, Identity, LibraryItem};
+use crate::steam_local::steam_local_playtime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const UNKNOWN: &str = "unknown";
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct AnalyticsRequest {
+  pub member_id: Option<String>,
+  /// Local Steam install/account; when given, Steam playtime also decides
+  /// whether a backlog game has ever been played.
+  pub steam_root: Option<String>,
+  pub steam_account: Option<String>,
+  /// Converts prices into this currency at each purchase's acquisition-date
+  /// rate. Items without a usable rate keep their own currency.
+  pub currency: Option<String>,
+}
+
+/// One bar/slice of a spend chart. Amounts are never summed across
+/// currencies, so a group paid in two currencies yields two points.
+#[derive(Serialize, Clone, Default)]
+pub struct SpendPoint {
+  pub key: String,
+  pub label: String,
+  pub currency: String,
+  pub items: usize,
+  pub total_spend: f64,
+  /// Spend divided by HLTB main-story hours, over items that have both.
+  pub cost_per_hour: Option<f64>,
+  pub unplayed_items: usize,
+  pub unplayed_spend: f64,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct CohortPoint {
+  /// Acquisition year, or "unknown".
+  pub cohort: String,
+  pub purchased: usize,
+  pub playing: usize,
+  pub beaten: usize,
+  pub abandoned: usize,
+  pub completion_rate: f64,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct BacklogPoint {
+  /// "YYYY-MM"
+  pub month: String,
+  pub acquired: usize,
+  pub finished: usize,
+  /// Games acquired up to this month that are still unfinished today.
+  pub backlog_size: usize,
+}
+
+#[derive(Serialize, Default)]
+pub struct SpendingAnalytics {
+  pub totals: Vec<SpendPoint>,
+  pub by_member: Vec<SpendPoint>,
+  pub by_account: Vec<SpendPoint>,
+  pub by_platform: Vec<SpendPoint>,
+  pub by_year: Vec<SpendPoint>,
+  pub cohorts: Vec<CohortPoint>,
+  pub backlog_trend: Vec<BacklogPoint>,
+  /// Items without an acquisition date are left out of `backlog_trend`.
+  pub undated_items: usize,
+}
+
+#[derive(Default)]
+struct Accumulator {
+  label: String,
+  items: usize,
+  spend: f64,
+  priced_spend: f64,
+  priced_hours: f64,
+  unplayed_items: usize,
+  unplayed_spend: f64,
+}
+
+struct Row<'a> {
+  item: &'a LibraryItem,
+  price: Option<f64>,
+  currency: String,
+  hours: Option<f64>,
+  unplayed: bool,
+}
+
+fn round2(v: f64) -> f64 {
+  (v * 100.0).round() / 100.0
+}
+
+fn acquired_month(item: &LibraryItem) -> Option<String> {
+  let ts = parse_timestamp(item.acquired_at.as_deref()?)?;
+  chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.format("%Y-%m").to_string())
+}
+
+fn is_finished(status: &str) -> bool {
+  matches!(status, "Beaten" | "Abandoned")
+}
+
+/// A game counts as played once its status moved past the backlog or Steam
+/// reports any playtime for it.
+fn is_unplayed(item: &LibraryItem, identity: &Identity, minutes: &HashMap<u32, u32>) -> bool {
+  matches!(item.status.as_str(), "Backlog" | "Owned")
+    && identity.appid.and_then(|a| minutes.get(&a)).is_none_or(|m| *m == 0)
+}
+
+fn group<'a, F>(rows: &[Row<'a>], key_of: F) -> Vec<SpendPoint>
+where
+  F: Fn(&Row<'a>) -> (String, String),
+{
+  let mut groups: BTreeMap<(String, String), Accumulator> = BTreeMap::new();
+  for row in rows {
+    let (key, label) = key_of(row);
+    let acc = groups.entry((key, row.currency.clone())).or_default();
+    acc.label = label;
+    acc.items += 1;
+    let price = row.price.unwrap_or(0.0);
+    acc.spend += price;
+    if let (Some(p), Some(h)) = (row.price, row.hours) {
+      acc.priced_spend += p;
+      acc.priced_hours += h;
+    }
+    if row.unplayed {
+      acc.unplayed_items += 1;
+      acc.unplayed_spend += price;
+    }
+  }
+  groups
+    .into_iter()
+    .map(|((key, currency), acc)| SpendPoint {
+      key,
+      label: acc.label,
+      currency,
+      items: acc.items,
+      total_spend: round2(acc.spend),
+      cost_per_hour: (acc.priced_hours > 0.0).then(|| round2(acc.priced_spend / acc.priced_hours)),
+      unplayed_items: acc.unplayed_items,
+      unplayed_spend: round2(acc.unplayed_spend),
+    })
+    .collect()
+}
+
+fn cohorts(rows: &[Row]) -> Vec<CohortPoint> {
+  let mut out: BTreeMap<String, CohortPoint> = BTreeMap::new();
+  for row in rows {
+    let cohort = acquired_month(row.item)
+      .map(|m| m[..4].to_string())
+      .unwrap_or_else(|| UNKNOWN.into());
+    let point = out.entry(cohort.clone()).or_insert_with(|| CohortPoint {
+      cohort,
+      ..Default::default()
+    });
+    point.purchased += 1;
+    match row.item.status.as_str() {
+      "Playing" => point.playing += 1,
+      "Beaten" => point.beaten += 1,
+      "Abandoned" => point.abandoned += 1,
+      _ => {}
+    }
+  }
+  out
+    .into_values()
+    .map(|mut p| {
+      p.completion_rate = round2(p.beaten as f64 / p.purchased.max(1) as f64);
+      p
+    })
+    .collect()
+}
+
+/// Monthly backlog size from acquisition dates. There is no status history,
+/// so finished games count as finished in the month they were acquired.
+fn backlog_trend(rows: &[Row]) -> (Vec<BacklogPoint>, usize) {
+  let mut months: BTreeMap<String, BacklogPoint> = BTreeMap::new();
+  let mut undated = 0;
+  for row in rows {
+    let Some(month) = acquired_month(row.item) else {
+      undated += 1;
+      continue;
+    };
+    let point = months.entry(month.clone()).or_insert_with(|| BacklogPoint {
+      month,
+      ..Default::default()
+    });
+    point.acquired += 1;
+    if is_finished(&row.item.status) {
+      point.finished += 1;
+    }
+  }
+  let mut running = 0;
+  let trend = months
+    .into_values()
+    .map(|mut p| {
+      running += p.acquired - p.finished;
+      p.backlog_size = running;
+      p
+    })
+    .collect();
+  (trend, undated)
+}
+
+/// Spend and value breakdowns for charts. Wishlist entries are not purchases
+/// and are ignored; prices stay in the currency they were recorded in unless
+/// `currency` asks for a conversion.
+#[tauri::command]
+pub fn library_spending_analytics(request: Option<AnalyticsRequest>) -> Result<SpendingAnalytics, String> {
+  let req = request.unwrap_or_default();
+  let conn = library::open_db()?;
+  let identities: HashMap<String, Identity> = library::load_identities(&conn)?
+    .into_iter()
+    .map(|i| (i.id.clone(), i))
+    .collect();
+  let members: HashMap<String, String> = library::load_members(&conn)?
+    .into_iter()
+    .map(|m| (m.id, m.name))
+    .collect();
+  let accounts: HashMap<String, String> = library::load_accounts(&conn)?
+    .into_iter()
+    .map(|a| (a.id, a.label))
+    .collect();
+  let items = library::load_items(&conn)?;
+  let cache = CachedMetadata::load();
+  let minutes: HashMap<u32, u32> = if req.steam_root.is_some() || req.steam_account.is_some() {
+    steam_local_playtime(req.steam_root.clone(), req.steam_account.clone())
+      .map(|rows| {
+        rows
+          .into_iter()
+          .filter_map(|r| Some((r.appid, r.playtime_minutes?)))
+          .collect()
+      })
+      .unwrap_or_default()
+  } else {
+    HashMap::new()
+  };
+
+  let rows: Vec<Row> = items
+    .iter()
+    .filter(|i| i.status != "Wishlist")
+    .filter(|i| req.member_id.is_none() || i.member_id == req.member_id)
+    .filter_map(|item| {
+      let identity = identities.get(&item.identity_id)?;
+      let hours = item
+        .ttb_median_main_h
+        .or(identity.ttb_median_main_h)
+        .or_else(|| cache.hltb_hours(&identity.title).map(f64::from))
+        .filter(|h| *h > 0.0);
+      let converted = req
+        .currency
+        .as_deref()
+        .and_then(|target| currency::convert_item(&conn, item, target).ok().flatten());
+      let (price, currency) = match converted {
+        Some(c) => (Some(c.converted), c.to),
+        None => (
+          item.price_try,
+          item
+            .currency_code
+            .as_deref()
+            .map(|c| c.trim().to_uppercase())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| DEFAULT_ITEM_CURRENCY.into()),
+        ),
+      };
+      Some(Row {
+        item,
+        price: price.filter(|p| *p >= 0.0),
+        currency,
+        hours,
+        unplayed: is_unplayed(item, identity, &minutes),
+      })
+    })
+    .collect();
+
+  let named = |id: Option<&String>, names: &HashMap<String, String>| match id {
+    Some(id) => (id.clone(), names.get(id).unwrap_or(id).clone()),
+    None => (UNKNOWN.to_string(), UNKNOWN.to_string()),
+  };
+  let (backlog_trend, undated_items) = backlog_trend(&rows);
+  Ok(SpendingAnalytics {
+    totals: group(&rows, |_| ("all".into(), "All".into())),
+    by_member: group(&rows, |r| named(r.item.member_id.as_ref(), &members)),
+    by_account: group(&rows, |r| named(r.item.account_id.as_ref(), &accounts)),
+    by_platform: group(&rows, |r| {
+      let platform = identities
+        .get(&r.item.identity_id)
+        .and_then(|i| i.platform.clone())
+        .unwrap_or_else(|| UNKNOWN.into());
+      (platform.to_lowercase(), platform)
+    }),
+    by_year: group(&rows, |r| {
+      let year = acquired_month(r.item)
+        .map(|m| m[..4].to_string())
+        .unwrap_or_else(|| UNKNOWN.into());
+      (year.clone(), year)
+    }),
+    cohorts: cohorts(&rows),
+    backlog_trend,
+    undated_items,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn item(status: &str, acquired: Option<&str>) -> LibraryItem {
+    LibraryItem {
+      status: status.into(),
+      acquired_at: acquired.map(|d| format!("{}T12:00:00Z", d)),
+      ..Default::default()
+    }
+  }
+
+  fn row<'a>(item: &'a LibraryItem, price: Option<f64>, currency: &str, hours: Option<f64>) -> Row<'a> {
+    Row {
+      item,
+      price,
+      currency: currency.into(),
+      hours,
+      unplayed: matches!(item.status.as_str(), "Backlog" | "Owned"),
+    }
+  }
+
+  #[test]
+  fn groups_spend_per_currency() {
+    let a = item("Backlog", None);
+    let b = item("Beaten", None);
+    let c = item("Playing", None);
+    let rows = vec![
+      row(&a, Some(100.0), "TRY", Some(10.0)),
+      row(&b, Some(50.0), "TRY", None),
+      row(&c, Some(20.0), "EUR", Some(4.0)),
+    ];
+    let points = group(&rows, |_| ("all".into(), "All".into()));
+    assert_eq!(points.len(), 2);
+    let eur = points.iter().find(|p| p.currency == "EUR").unwrap();
+    assert_eq!((eur.items, eur.total_spend, eur.cost_per_hour), (1, 20.0, Some(5.0)));
+    assert_eq!((eur.unplayed_items, eur.unplayed_spend), (0, 0.0));
+    let try_ = points.iter().find(|p| p.currency == "TRY").unwrap();
+    assert_eq!((try_.items, try_.total_spend, try_.cost_per_hour), (2, 150.0, Some(10.0)));
+    assert_eq!((try_.unplayed_items, try_.unplayed_spend), (1, 100.0));
+  }
+
+  #[test]
+  fn cohort_completion_rate_counts_beaten_only() {
+    let items = [
+      item("Beaten", Some("2022-03-01")),
+      item("Abandoned", Some("2022-05-01")),
+      item("Playing", Some("2022-07-01")),
+      item("Backlog", Some("2022-09-01")),
+      item("Beaten", Some("2023-01-10")),
+      item("Backlog", None),
+    ];
+    let rows: Vec<Row> = items.iter().map(|i| row(i, None, "TRY", None)).collect();
+    let points = cohorts(&rows);
+    let cohort: Vec<(&str, usize, f64)> = points
+      .iter()
+      .map(|p| (p.cohort.as_str(), p.purchased, p.completion_rate))
+      .collect();
+    assert_eq!(cohort, vec![("2022", 4, 0.25), ("2023", 1, 1.0), (UNKNOWN, 1, 0.0)]);
+    assert_eq!((points[0].playing, points[0].abandoned), (1, 1));
+  }
+
+  #[test]
+  fn backlog_trend_keeps_a_running_size() {
+    let items = [
+      item("Backlog", Some("2024-01-05")),
+      item("Beaten", Some("2024-01-20")),
+      item("Owned", Some("2024-03-02")),
+      item("Abandoned", Some("2024-03-15")),
+      item("Playing", Some("2024-04-01")),
+      item("Backlog", None),
+    ];
+    let rows: Vec<Row> = items.iter().map(|i| row(i, None, "TRY", None)).collect();
+    let (trend, undated) = backlog_trend(&rows);
+    let sizes: Vec<(&str, usize, usize, usize)> = trend
+      .iter()
+      .map(|p| (p.month.as_str(), p.acquired, p.finished, p.backlog_size))
+      .collect();
+    assert_eq!(
+      sizes,
+      vec![("2024-01", 2, 1, 1), ("2024-03", 2, 1, 2), ("2024-04", 1, 0, 3)]
+    );
+    assert_eq!(undated, 1);
+  }
+
+  #[test]
+  fn unplayed_needs_backlog_status_and_no_playtime() {
+    let identity = Identity {
+      appid: Some(620),
+      ..Default::default()
+    };
+    let no_appid = Identity::default();
+    let mut minutes = HashMap::new();
+    assert!(is_unplayed(&item("Backlog", None), &identity, &minutes));
+    assert!(is_unplayed(&item("Owned", None), &no_appid, &minutes));
+    assert!(!is_unplayed(&item("Playing", None), &identity, &minutes));
+    minutes.insert(620, 0);
+    assert!(is_unplayed(&item("Backlog", None), &identity, &minutes));
+    minutes.insert(620, 45);
+    assert!(!is_unplayed(&item("Backlog", None), &identity, &minutes));
+    assert!(is_unplayed(&item("Backlog", None), &no_appid, &minutes));
+  }
+}
@@ -0,0 +1,332 @@
+use crate::commands::{data_root, now_unix};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::{
+  fs::{self, File},
+  io::{self, Write},
+  path::{Path, PathBuf},
+  sync::Mutex,
+  thread,
+  time::Duration,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+const BACKUP_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "backup-";
+// Files captured in every archive, relative to `data_root()`.
+const BACKUP_FILES: [&str; 3] = ["library.db", "hltb_cache.json", "opencritic_cache.json"];
+const DEFAULT_KEEP: usize = 14;
+const DEFAULT_MAX_AGE_DAYS: i64 = 90;
+const DEFAULT_INTERVAL_HOURS: i64 = 24;
+const SCHEDULER_TICK: Duration = Duration::from_secs(60 * 60);
+
+// Backups, restores and prunes touch the same files; never interleave them.
+static BACKUP_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Clone)]
+pub struct BackupInfo {
+  pub file_name: String,
+  pub path: String,
+  pub created_at: i64,
+  pub reason: String,
+  pub size_bytes: u64,
+}
+
+#[derive(Serialize)]
+pub struct PruneResult {
+  pub removed: Vec<String>,
+  pub kept: usize,
+}
+
+fn env_num<T: std::str::FromStr>(key: &str, default: T) -> T {
+  std::env::var(key)
+    .ok()
+    .and_then(|v| v.trim().parse().ok())
+    .unwrap_or(default)
+}
+
+fn backup_dir(root: &Path) -> PathBuf {
+  let dir = root.join(BACKUP_DIR);
+  let _ = fs::create_dir_all(&dir);
+  dir
+}
+
+/// `backup-<unix>-<reason>.zip`; the timestamp in the name keeps ordering
+/// stable even when file mtimes are not.
+fn parse_name(name: &str) -> Option<(i64, String)> {
+  let rest = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(".zip")?;
+  let (ts, reason) = rest.split_once('-').unwrap_or((rest, "manual"));
+  Some((ts.parse().ok()?, reason.to_string()))
+}
+
+fn sanitize_reason(reason: &str) -> String {
+  let cleaned: String = reason
+    .trim()
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+    .collect();
+  if cleaned.is_empty() {
+    "manual".into()
+  } else {
+    cleaned
+  }
+}
+
+/// Consistent copy of a live SQLite file, including anything still in its WAL.
+fn snapshot_db(src: &Path, dest: &Path) -> Result<(), String> {
+  let _ = fs::remove_file(dest);
+  let conn = Connection::open(src).map_err(|e| e.to_string())?;
+  conn
+    .execute("VACUUM INTO ?1", [dest.display().to_string()])
+    .map_err(|e| format!("snapshot {}: {}", src.display(), e))?;
+  Ok(())
+}
+
+fn write_archive(root: &Path, target: &Path) -> Result<(), String> {
+  let file = File::create(target).map_err(|e| format!("{}: {}", target.display(), e))?;
+  let mut zip = ZipWriter::new(file);
+  let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+  for name in BACKUP_FILES {
+    let src = root.join(name);
+    if !src.is_file() {
+      continue;
+    }
+    let bytes = if name.ends_with(".db") {
+      let tmp = backup_dir(root).join(format!(".{}.snapshot", name));
+      snapshot_db(&src, &tmp)?;
+      let bytes = fs::read(&tmp).map_err(|e| e.to_string());
+      let _ = fs::remove_file(&tmp);
+      bytes?
+    } else {
+      fs::read(&src).map_err(|e| format!("{}: {}", src.display(), e))?
+    };
+    zip.start_file(name, options).map_err(|e| e.to_string())?;
+    zip.write_all(&bytes).map_err(|e| e.to_string())?;
+  }
+  zip.finish().map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+fn info_for(path: &Path) -> Option<BackupInfo> {
+  let file_name = path.file_name()?.to_str()?.to_string();
+  let (created_at, reason) = parse_name(&file_name)?;
+  Some(BackupInfo {
+    size_bytes: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+    path: path.display().to_string(),
+    file_name,
+    created_at,
+    reason,
+  })
+}
+
+fn create_locked(root: &Path, reason: &str) -> Result<BackupInfo, String> {
+  // Timestamps double as the ordering key, so two backups in the same second
+  // (e.g. pre-restore right after a manual one) get distinct ones.
+  let newest = list_locked(root).first().map(|b| b.created_at).unwrap_or(0);
+  let ts = now_unix().max(newest + 1);
+  let target = backup_dir(root).join(format!("{}{}-{}.zip", BACKUP_PREFIX, ts, sanitize_reason(reason)));
+  let partial = target.with_extension("zip.partial");
+  if let Err(e) = write_archive(root, &partial) {
+    let _ = fs::remove_file(&partial);
+    return Err(e);
+  }
+  fs::rename(&partial, &target).map_err(|e| e.to_string())?;
+  info_for(&target).ok_or_else(|| "Backup was written but could not be read back".to_string())
+}
+
+/// Archives the library database and metadata caches. Called by the scheduler
+/// and before library migrations and bulk imports.
+pub(crate) fn create_backup(reason: &str) -> Result<BackupInfo, String> {
+  let _guard = BACKUP_LOCK.lock().map_err(|e| e.to_string())?;
+  create_locked(&data_root(), reason)
+}
+
+fn list_locked(root: &Path) -> Vec<BackupInfo> {
+  let mut out: Vec<BackupInfo> = fs::read_dir(backup_dir(root))
+    .map(|entries| entries.flatten().filter_map(|e| info_for(&e.path())).collect())
+    .unwrap_or_default();
+  out.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+  out
+}
+
+/// Keeps the newest `keep` backups and drops anything older than
+/// `max_age_days`, but never removes the most recent one.
+fn prune_locked(root: &Path, keep: usize, max_age_days: i64) -> PruneResult {
+  let cutoff = now_unix() - max_age_days * 24 * 3600;
+  let mut removed = Vec::new();
+  let mut kept = 0;
+  for (i, b) in list_locked(root).into_iter().enumerate() {
+    let expired = i >= keep.max(1) || (i > 0 && b.created_at < cutoff);
+    if expired && fs::remove_file(&b.path).is_ok() {
+      removed.push(b.file_name);
+    } else {
+      kept += 1;
+    }
+  }
+  PruneResult { removed, kept }
+}
+
+fn extract(root: &Path, archive_path: &Path) -> Result<Vec<String>, String> {
+  let file = File::open(archive_path).map_err(|e| format!("{}: {}", archive_path.display(), e))?;
+  let mut zip = ZipArchive::new(file).map_err(|e| e.to_string())?;
+  let mut restored = Vec::new();
+  for name in BACKUP_FILES {
+    let Ok(mut entry) = zip.by_name(name) else { continue };
+    let target = root.join(name);
+    let partial = root.join(format!("{}.restoring", name));
+    let mut out = File::create(&partial).map_err(|e| e.to_string())?;
+    io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+    drop(out);
+    if name.ends_with(".db") {
+      // Stale WAL/SHM files would be replayed over the restored database.
+      let _ = fs::remove_file(root.join(format!("{}-wal", name)));
+      let _ = fs::remove_file(root.join(format!("{}-shm", name)));
+    }
+    fs::rename(&partial, &target).map_err(|e| e.to_string())?;
+    restored.push(name.to_string());
+  }
+  Ok(restored)
+}
+
+/// Takes a scheduled backup whenever the newest one is older than
+/// `BACKUP_INTERVAL_HOURS`, then applies the retention policy.
+pub fn start_scheduler() {
+  thread::spawn(|| loop {
+    let interval = env_num("BACKUP_INTERVAL_HOURS", DEFAULT_INTERVAL_HOURS).max(1) * 3600;
+    if let Ok(_guard) = BACKUP_LOCK.lock() {
+      let root = data_root();
+      let due = list_locked(&root)
+        .first()
+        .is_none_or(|b| now_unix() - b.created_at >= interval);
+      if due && create_locked(&root, "scheduled").is_ok() {
+        prune_locked(
+          &root,
+          env_num("BACKUP_KEEP", DEFAULT_KEEP),
+          env_num("BACKUP_MAX_AGE_DAYS", DEFAULT_MAX_AGE_DAYS),
+        );
+      }
+    }
+    thread::sleep(SCHEDULER_TICK);
+  });
+}
+
+#[tauri::command]
+pub fn backup_create(reason: Option<String>) -> Result<BackupInfo, String> {
+  create_backup(reason.as_deref().unwrap_or("manual"))
+}
+
+#[tauri::command]
+pub fn backup_list() -> Result<Vec<BackupInfo>, String> {
+  let _guard = BACKUP_LOCK.lock().map_err(|e| e.to_string())?;
+  Ok(list_locked(&data_root()))
+}
+
+/// Restores a backup over the current data. The current state is backed up
+/// first so a restore can itself be undone.
+#[tauri::command]
+pub fn backup_restore(file_name: String) -> Result<Vec<String>, String> {
+  let _guard = BACKUP_LOCK.lock().map_err(|e| e.to_string())?;
+  if parse_name(&file_name).is_none() || file_name.contains(['/', '\\']) {
+    return Err(format!("Not a backup archive: {}", file_name));
+  }
+  let root = data_root();
+  let path = backup_dir(&root).join(&file_name);
+  if !path.is_file() {
+    return Err(format!("Backup not found: {}", file_name));
+  }
+  create_locked(&root, "pre_restore")?;
+  extract(&root, &path)
+}
+
+#[tauri::command]
+pub fn backup_prune(keep: Option<usize>, max_age_days: Option<i64>) -> Result<PruneResult, String> {
+  let _guard = BACKUP_LOCK.lock().map_err(|e| e.to_string())?;
+  Ok(prune_locked(
+    &data_root(),
+    keep.unwrap_or_else(|| env_num("BACKUP_KEEP", DEFAULT_KEEP)),
+    max_age_days.unwrap_or_else(|| env_num("BACKUP_MAX_AGE_DAYS", DEFAULT_MAX_AGE_DAYS)),
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn temp_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("backup_test_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+  }
+
+  fn titles(db: &Path) -> Vec<String> {
+    let conn = Connection::open(db).unwrap();
+    let mut stmt = conn.prepare("SELECT title FROM games ORDER BY title").unwrap();
+    let rows = stmt.query_map([], |r| r.get(0)).unwrap();
+    rows.collect::<Result<_, _>>().unwrap()
+  }
+
+  #[test]
+  fn restores_a_backup_over_changed_data() {
+    let root = temp_root("restore");
+    let db = root.join("library.db");
+    {
+      let conn = Connection::open(&db).unwrap();
+      conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+      conn
+        .execute_batch("CREATE TABLE games (title TEXT); INSERT INTO games VALUES ('Hades'), ('Celeste');")
+        .unwrap();
+      fs::write(root.join("hltb_cache.json"), r#"{"hades":22}"#).unwrap();
+
+      // Rows still in the WAL make it into the archive.
+      let backup = create_locked(&root, "Before Import!").unwrap();
+      assert_eq!(backup.reason, "before_import_");
+      assert!(backup.size_bytes > 0);
+
+      conn.execute("DELETE FROM games WHERE title = 'Celeste'", []).unwrap();
+      fs::write(root.join("hltb_cache.json"), "{}").unwrap();
+      let restored = extract(&root, Path::new(&backup.path)).unwrap();
+      assert_eq!(restored, vec!["library.db", "hltb_cache.json"]);
+    }
+
+    let titles = titles(&db);
+    let cache = fs::read_to_string(root.join("hltb_cache.json")).unwrap();
+    let leftovers: Vec<_> = fs::read_dir(&root)
+      .unwrap()
+      .flatten()
+      .map(|e| e.file_name().to_string_lossy().into_owned())
+      .filter(|n| n.ends_with(".restoring") || n.ends_with("-wal"))
+      .collect();
+    let _ = fs::remove_dir_all(&root);
+    assert_eq!(titles, vec!["Celeste", "Hades"]);
+    assert_eq!(cache, r#"{"hades":22}"#);
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+  }
+
+  #[test]
+  fn backups_in_the_same_second_stay_ordered_and_prune_keeps_the_newest() {
+    let root = temp_root("prune");
+    fs::write(root.join("opencritic_cache.json"), "{}").unwrap();
+    let first = create_locked(&root, "manual").unwrap();
+    let second = create_locked(&root, "pre_restore").unwrap();
+    let third = create_locked(&root, "").unwrap();
+    let listed: Vec<String> = list_locked(&root).into_iter().map(|b| b.file_name).collect();
+    let pruned = prune_locked(&root, 2, DEFAULT_MAX_AGE_DAYS);
+    let kept: Vec<String> = list_locked(&root).into_iter().map(|b| b.file_name).collect();
+    let _ = fs::remove_dir_all(&root);
+
+    assert!(first.created_at < second.created_at && second.created_at < third.created_at);
+    assert_eq!(third.reason, "manual");
+    assert_eq!(listed, vec![third.file_name.clone(), second.file_name.clone(), first.file_name.clone()]);
+    assert_eq!((pruned.removed, pruned.kept), (vec![first.file_name], 2));
+    assert_eq!(kept, vec![third.file_name, second.file_name]);
+  }
+
+  #[test]
+  fn parses_backup_names() {
+    assert_eq!(parse_name("backup-1700000000-pre_migration.zip"), Some((1700000000, "pre_migration".into())));
+    assert_eq!(parse_name("backup-1700000000.zip"), Some((1700000000, "manual".into())));
+    assert_eq!(parse_name("backup-1700000000-manual.zip.partial"), None);
+    assert_eq!(parse_name("notes.zip"), None);
+  }
+}
@@ -0,0 +1,158 @@
+use crate::commands::now_unix;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{collections::HashMap, sync::Mutex};
+
+const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+const DEFAULT_COOLDOWN_SECS: i64 = 5 * 60;
+
+pub const PROVIDERS: [&str; 4] = ["hltb", "steam-store", "steam-api", "opencritic"];
+
+#[derive(Default)]
+struct Breaker {
+  consecutive_failures: u32,
+  open_until: Option<i64>,
+  last_error: Option<String>,
+}
+
+static BREAKERS: Lazy<Mutex<HashMap<String, Breaker>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn failure_threshold() -> u32 {
+  std::env::var("BREAKER_FAILURE_THRESHOLD")
+    .ok()
+    .and_then(|v| v.trim().parse::<u32>().ok())
+    .filter(|n| *n > 0)
+    .unwrap_or(DEFAULT_FAILURE_THRESHOLD)
+}
+
+fn cooldown_secs() -> i64 {
+  std::env::var("BREAKER_COOLDOWN_SECS")
+    .ok()
+    .and_then(|v| v.trim().parse::<i64>().ok())
+    .filter(|n| *n > 0)
+    .unwrap_or(DEFAULT_COOLDOWN_SECS)
+}
+
+fn with_breakers<R>(f: impl FnOnce(&mut HashMap<String, Breaker>) -> R) -> R {
+  let mut guard = match BREAKERS.lock() {
+    Ok(g) => g,
+    Err(poisoned) => poisoned.into_inner(),
+  };
+  f(&mut guard)
+}
+
+/// Error returned while a provider's breaker is open. Its `Display` form is
+/// what commands hand back to the UI, prefixed so it can be told apart from
+/// ordinary request failures.
+pub struct ProviderUnavailable {
+  pub provider: String,
+  pub until: i64,
+}
+
+impl std::fmt::Display for ProviderUnavailable {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "ProviderUnavailable: {} temporarily unavailable until {}",
+      self.provider, self.until
+    )
+  }
+}
+
+/// Fails fast while the breaker is open. Once the cooldown has elapsed the
+/// breaker is half-open and lets requests through; the next result decides
+/// whether it closes or re-opens.
+pub fn check(provider: &str) -> Result<(), String> {
+  let now = now_unix();
+  with_breakers(|map| match map.get(provider).and_then(|b| b.open_until) {
+    Some(until) if until > now => Err(
+      ProviderUnavailable {
+        provider: provider.to_string(),
+        until,
+      }
+      .to_string(),
+    ),
+    _ => Ok(()),
+  })
+}
+
+pub fn record_success(provider: &str) {
+  with_breakers(|map| {
+    let b = map.entry(provider.to_string()).or_default();
+    b.consecutive_failures = 0;
+    b.open_until = None;
+  });
+}
+
+pub fn record_failure(provider: &str, error: &str) {
+  let now = now_unix();
+  let threshold = failure_threshold();
+  let cooldown = cooldown_secs();
+  with_breakers(|map| {
+    let b = map.entry(provider.to_string()).or_default();
+    let half_open = matches!(b.open_until, Some(until) if until <= now);
+    b.consecutive_failures = b.consecutive_failures.saturating_add(1);
+    b.last_error = Some(error.to_string());
+    if half_open || b.consecutive_failures >= threshold {
+      b.open_until = Some(now + cooldown);
+    }
+  });
+}
+
+/// Records the outcome of a provider request and passes it through.
+pub fn observe<T>(provider: &str, res: Result<T, String>) -> Result<T, String> {
+  match &res {
+    Ok(_) => record_success(provider),
+    Err(e) => record_failure(provider, e),
+  }
+  res
+}
+
+#[derive(Serialize)]
+pub struct ProviderStatus {
+  pub provider: String,
+  /// "closed" | "open" | "half-open"
+  pub state: String,
+  pub consecutive_failures: u32,
+  pub open_until: Option<i64>,
+  pub last_error: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_provider_status() -> Vec<ProviderStatus> {
+  let now = now_unix();
+  with_breakers(|map| {
+    PROVIDERS
+      .iter()
+      .map(|p| {
+        let (failures, open_until, last_error) = map
+          .get(*p)
+          .map(|b| (b.consecutive_failures, b.open_until, b.last_error.clone()))
+          .unwrap_or((0, None, None));
+        let state = match open_until {
+          Some(until) if until > now => "open",
+          Some(_) => "half-open",
+          None => "closed",
+        };
+        ProviderStatus {
+          provider: (*p).into(),
+          state: state.into(),
+          consecutive_failures: failures,
+          open_until,
+          last_error,
+        }
+      })
+      .collect()
+  })
+}
+
+#[tauri::command]
+pub fn reset_provider_breaker(provider: String) -> Result<(), String> {
+  if !PROVIDERS.contains(&provider.as_str()) {
+    return Err(format!("Unknown provider '{}'", provider));
+  }
+  with_breakers(|map| {
+    map.remove(&provider);
+  });
+  Ok(())
+}
@@ -1,51 +1,115 @@
+use crate::{breaker, datasets, offline, price_history, ratelimit};
 use regex::Regex;
 use reqwest::{header, StatusCode};
 use once_cell::sync::Lazy;
 use deunicode::deunicode;
 use strsim::jaro_winkler;
+use tauri::Emitter;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 use std::{
-  collections::HashMap,
+  collections::{HashMap, HashSet},
   fs,
   path::{Path, PathBuf},
-  sync::OnceLock,
+  sync::{Arc, Mutex, OnceLock},
   thread,
-  time::{Duration, SystemTime, UNIX_EPOCH},
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 const HLTB_CACHE_FILE: &str = "hltb_cache.json";
 const OPENCRITIC_CACHE_FILE: &str = "opencritic_cache.json";
+const STEAM_PRICE_CACHE_FILE: &str = "steam_price_cache.json";
 const HLTB_CACHE_TTL_SECS: i64 = 30 * 24 * 60 * 60;
 const OPENCRITIC_CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
 const OPENCRITIC_MAX_RETRIES: usize = 5;
 const OPENCRITIC_BACKOFF_FALLBACK_MS: u64 = 700;
 const OPENCRITIC_NEGATIVE_TTL_SECS: i64 = 24 * 60 * 60; // 24h for not-found
+const STEAM_PRICE_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
 const DEFAULT_STEAM_REGION: &str = "us";
-const USER_AGENT: &str = "GameTracker/1.0 (+https://tracker.local)";
-
-#[derive(Serialize)]
+const DEFAULT_STEAM_STORE_BASE: &str = "https://store.steampowered.com";
+pub(crate) const USER_AGENT: &str = "GameTracker/1.0 (+https://tracker.local)";
+const HLTB_REFRESHED_EVENT: &str = "hltb-refreshed";
+const OPENCRITIC_REFRESHED_EVENT: &str = "opencritic-refreshed";
+const DIAG_HLTB_TITLE: &str = "Portal 2";
+const DIAG_STEAM_APPID: u32 = 620; // Portal 2
+const DIAG_TIMEOUT_SECS: u64 = 15;
+// Bump whenever `normalize_key` output changes: caches and the Steam app list
+// index persist normalized keys and re-derive them when this moves.
+pub(crate) const NORMALIZE_VERSION: u32 = 2;
+const NORMALIZE_VERSION_FILE: &str = "normalize_version";
+
+#[derive(Serialize, Clone)]
 pub struct HLTBMeta {
   pub main_median_hours: Option<f32>,
+  pub main_extra_median_hours: Option<f32>,
+  pub completionist_median_hours: Option<f32>,
   pub source: String,
+  pub stale: bool,
+}
+
+/// HLTB medians per category, in hours. Only the API reports extras and
+/// completionist; the HTML fallback and local dataset give main story alone.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct HltbTimes {
+  pub main: Option<f32>,
+  pub extras: Option<f32>,
+  pub completionist: Option<f32>,
+}
+
+impl HltbTimes {
+  fn main_only(main: Option<f32>) -> Self {
+    HltbTimes { main, ..Default::default() }
+  }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 struct HltbCacheEntry {
   value: Option<f32>,
+  #[serde(default)]
+  extras: Option<f32>,
+  #[serde(default)]
+  completionist: Option<f32>,
   ts: i64,
 }
 
+impl HltbCacheEntry {
+  fn times(&self) -> HltbTimes {
+    HltbTimes {
+      main: self.value,
+      extras: self.extras,
+      completionist: self.completionist,
+    }
+  }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 struct Cached {
   score: Option<f32>,
   cached_at: i64,
 }
 
+#[derive(Serialize, Clone)]
+pub struct OpenCriticMeta {
+  pub score: Option<f32>,
+  pub source: String,
+  pub stale: bool,
+}
+
 #[derive(Serialize)]
 pub struct SteamPrice {
   pub price: f32,
   pub currency: String,
+  pub stale: bool,
+  /// Undiscounted price; only known for fresh store responses.
+  pub initial_price: Option<f32>,
+  pub discount_percent: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SteamPriceCacheEntry {
+  price: Option<f32>,
+  currency: Option<String>,
+  ts: i64,
 }
 
 #[derive(Deserialize)]
@@ -53,6 +117,10 @@ struct SteamPriceOverview {
   #[serde(rename = "final")]
   final_price: i32,
   currency: String,
+  #[serde(default)]
+  initial: i32,
+  #[serde(default)]
+  discount_percent: i32,
 }
 
 #[derive(Deserialize)]
@@ -66,18 +134,42 @@ struct SteamAppResult {
   data: Option<SteamAppData>,
 }
 
-fn now_unix() -> i64 {
+pub(crate) fn now_unix() -> i64 {
   SystemTime::now()
     .duration_since(UNIX_EPOCH)
     .unwrap_or_default()
     .as_secs() as i64
 }
 
-fn is_expired(ts: i64, ttl: i64) -> bool {
+/// Parses the date/time shapes our sources emit (RFC 3339, `YYYY-MM-DD HH:MM:SS`,
+/// bare `YYYY-MM-DD`, or unix seconds) into unix seconds, treating naive times as UTC.
+pub(crate) fn parse_timestamp(text: &str) -> Option<i64> {
+  let text = text.trim();
+  if text.is_empty() {
+    return None;
+  }
+  if let Ok(secs) = text.parse::<i64>() {
+    return Some(secs);
+  }
+  if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(text) {
+    return Some(dt.timestamp());
+  }
+  for fmt in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"] {
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(text, fmt) {
+      return Some(dt.and_utc().timestamp());
+    }
+  }
+  chrono::NaiveDate::parse_from_str(text.get(..10).unwrap_or(text), "%Y-%m-%d")
+    .ok()
+    .and_then(|d| d.and_hms_opt(0, 0, 0))
+    .map(|dt| dt.and_utc().timestamp())
+}
+
+pub(crate) fn is_expired(ts: i64, ttl: i64) -> bool {
   now_unix().saturating_sub(ts) > ttl
 }
 
-fn data_root() -> PathBuf {
+pub(crate) fn data_root() -> PathBuf {
   let mut dir = dirs::data_dir().unwrap_or_else(std::env::temp_dir);
   dir.push("GameTracker");
   let _ = fs::create_dir_all(&dir);
@@ -94,7 +186,7 @@ fn data_file(name: &str) -> PathBuf {
   cache_path(name)
 }
 
-fn read_cache_map<T>(name: &str) -> HashMap<String, T>
+pub(crate) fn read_cache_map<T>(name: &str) -> HashMap<String, T>
 where
   T: DeserializeOwned,
 {
@@ -106,7 +198,7 @@ where
   }
 }
 
-fn write_cache_map<T>(name: &str, map: &HashMap<String, T>)
+pub(crate) fn write_cache_map<T>(name: &str, map: &HashMap<String, T>)
 where
   T: Serialize,
 {
@@ -119,6 +211,27 @@ where
   }
 }
 
+// One lock per cache file, held across read-modify-write so concurrent
+// refreshes don't drop each other's entries.
+static CACHE_LOCKS: Lazy<Mutex<HashMap<String, Arc<Mutex<()>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub(crate) fn update_cache_map<T, F>(name: &str, update: F)
+where
+  T: DeserializeOwned + Serialize,
+  F: FnOnce(&mut HashMap<String, T>),
+{
+  let lock = CACHE_LOCKS
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .entry(name.to_string())
+    .or_default()
+    .clone();
+  let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+  let mut map = read_cache_map::<T>(name);
+  update(&mut map);
+  write_cache_map(name, &map);
+}
+
 fn read_cache(path: &Path) -> HashMap<String, Cached> {
   if let Ok(bytes) = fs::read(path) {
     serde_json::from_slice::<HashMap<String, Cached>>(&bytes).unwrap_or_default()
@@ -127,21 +240,39 @@ fn read_cache(path: &Path) -> HashMap<String, Cached> {
   }
 }
 
-fn write_cache(path: &Path, map: &HashMap<String, Cached>) {
-  if let Some(parent) = path.parent() {
-    let _ = fs::create_dir_all(parent);
+/// Re-keys the HLTB and OpenCritic caches after a `NORMALIZE_VERSION` bump.
+/// Old keys are normalized titles, so normalizing them again lands on the new
+/// key; when two collapse into one the fresher entry wins.
+pub(crate) fn rekey_title_caches() {
+  let path = data_file(NORMALIZE_VERSION_FILE);
+  let stored = fs::read_to_string(&path)
+    .ok()
+    .and_then(|v| v.trim().parse::<u32>().ok())
+    .unwrap_or(1);
+  if stored == NORMALIZE_VERSION {
+    return;
   }
-  if let Ok(json) = serde_json::to_vec_pretty(map) {
-    let _ = fs::write(path, json);
+  update_cache_map::<HltbCacheEntry, _>(HLTB_CACHE_FILE, |cache| rekey(cache, |e| e.ts));
+  update_cache_map::<Cached, _>(OPENCRITIC_CACHE_FILE, |cache| rekey(cache, |e| e.cached_at));
+  let _ = fs::write(path, NORMALIZE_VERSION.to_string());
+}
+
+fn rekey<T>(cache: &mut HashMap<String, T>, ts: fn(&T) -> i64) {
+  for (key, entry) in std::mem::take(cache) {
+    let new_key = normalize_key(&key);
+    let new_key = if new_key.is_empty() { key } else { new_key };
+    if cache.get(&new_key).is_none_or(|existing| ts(existing) < ts(&entry)) {
+      cache.insert(new_key, entry);
+    }
   }
 }
 
-fn normalize_key(title: &str) -> String {
+pub(crate) fn normalize_key(title: &str) -> String {
   let s = normalize_title(title);
   s.to_lowercase()
 }
 
-fn normalize_title(input: &str) -> String {
+pub(crate) fn normalize_title(input: &str) -> String {
   let trimmed = input.trim();
   if trimmed.is_empty() {
     return String::new();
@@ -171,7 +302,7 @@ fn edition_regex() -> &'static Regex {
   static RE: OnceLock<Regex> = OnceLock::new();
   RE.get_or_init(|| {
     Regex::new(
-      r"(?i)\\b(?:game of the year|goty|complete|definitive|ultimate|enhanced|deluxe|anniversary|royal|collection|remastered|remake)\\b(?:\\s+edition|\\s+collection)?",
+      r"(?i)\b(?:game of the year|goty|complete|definitive|ultimate|enhanced|deluxe|anniversary|royal|collection|remastered|remake)\b(?:\s+edition|\s+collection)?",
     )
     .unwrap()
   })
@@ -184,12 +315,12 @@ fn edition_paren_regex() -> &'static Regex {
 
 fn year_regex() -> &'static Regex {
   static RE: OnceLock<Regex> = OnceLock::new();
-  RE.get_or_init(|| Regex::new(r"\\b(19|20)\\d{2}\\b").unwrap())
+  RE.get_or_init(|| Regex::new(r"\b(19|20)\d{2}\b").unwrap())
 }
 
 fn whitespace_regex() -> &'static Regex {
   static RE: OnceLock<Regex> = OnceLock::new();
-  RE.get_or_init(|| Regex::new(r"\\s+").unwrap())
+  RE.get_or_init(|| Regex::new(r"\s+").unwrap())
 }
 
 fn retry_after_duration(headers: &header::HeaderMap) -> Duration {
@@ -212,6 +343,22 @@ fn fallback_backoff() -> Duration {
   Duration::from_millis(OPENCRITIC_BACKOFF_FALLBACK_MS + jitter)
 }
 
+static REFRESHING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Claims a background refresh slot for `provider:key`; false if one is already running.
+fn begin_refresh(provider: &str, key: &str) -> bool {
+  REFRESHING
+    .lock()
+    .map(|mut set| set.insert(format!("{}:{}", provider, key)))
+    .unwrap_or(false)
+}
+
+fn end_refresh(provider: &str, key: &str) {
+  if let Ok(mut set) = REFRESHING.lock() {
+    set.remove(&format!("{}:{}", provider, key));
+  }
+}
+
 fn clear_cache_file(name: &str) -> Result<(), String> {
   let path = cache_path(name);
   if path.exists() {
@@ -220,7 +367,12 @@ fn clear_cache_file(name: &str) -> Result<(), String> {
   Ok(())
 }
 
-async fn hltb_try_api(client: &reqwest::Client, title: &str) -> Result<Option<f32>, String> {
+async fn hltb_try_api(client: &reqwest::Client, title: &str) -> Result<Option<HltbTimes>, String> {
+  let body = hltb_api_request(client, title).await?;
+  hltb_parse_api(body)
+}
+
+async fn hltb_api_request(client: &reqwest::Client, title: &str) -> Result<Value, String> {
   let terms: Vec<&str> = title.split_whitespace().collect();
   let payload = serde_json::json!({
     "searchType": 1,
@@ -242,67 +394,96 @@ async fn hltb_try_api(client: &reqwest::Client, title: &str) -> Result<Option<f3
     }
   });
 
+  let url = "https://howlongtobeat.com/api/search";
+  ratelimit::acquire(url).await;
   let res = client
-    .post("https://howlongtobeat.com/api/search")
+    .post(url)
     .header("origin", "https://howlongtobeat.com")
     .header("referer", "https://howlongtobeat.com/")
     .header("content-type", "application/json")
     .json(&payload)
     .send()
     .await
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| offline::note_error(&e))?;
+  offline::note_online();
 
   if !res.status().is_success() {
     return Err(format!("HLTB HTTP {}", res.status()));
   }
 
+  res.json::<Value>().await.map_err(|e| e.to_string())
+}
+
+fn hltb_parse_api(body: Value) -> Result<Option<HltbTimes>, String> {
   #[derive(Deserialize)]
   struct Item {
     #[serde(rename = "gameplayMain")]
     gameplay_main: Option<f32>,
+    #[serde(rename = "gameplayMainExtra")]
+    gameplay_main_extra: Option<f32>,
+    #[serde(rename = "gameplayCompletionist")]
+    gameplay_completionist: Option<f32>,
   }
   #[derive(Deserialize)]
   struct ApiResp {
     data: Vec<Item>,
   }
 
-  let body: ApiResp = res.json().await.map_err(|e| e.to_string())?;
-  Ok(body.data.get(0).and_then(|i| i.gameplay_main))
+  // HLTB reports 0 for categories nobody has submitted yet.
+  let hours = |v: Option<f32>| v.filter(|h| *h > 0.0);
+  let body: ApiResp = serde_json::from_value(body).map_err(|e| e.to_string())?;
+  Ok(body.data.get(0).and_then(|i| {
+    i.gameplay_main.map(|main| HltbTimes {
+      main: Some(main),
+      extras: hours(i.gameplay_main_extra),
+      completionist: hours(i.gameplay_completionist),
+    })
+  }))
 }
 
 async fn hltb_try_html(client: &reqwest::Client, title: &str) -> Result<Option<f32>, String> {
+  let text = hltb_html_request(client, title).await?;
+  Ok(hltb_parse_html(&text).map(|(v, _)| v))
+}
+
+async fn hltb_html_request(client: &reqwest::Client, title: &str) -> Result<String, String> {
   let q = urlencoding::encode(title);
   let url = format!("https://howlongtobeat.com/?q={}", q);
+  ratelimit::acquire(&url).await;
   let res = client
     .get(url)
     .header("user-agent", USER_AGENT)
     .send()
     .await
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| offline::note_error(&e))?;
+  offline::note_online();
 
   if !res.status().is_success() {
     return Err(format!("HLTB HTML HTTP {}", res.status()));
   }
 
-  let text = res.text().await.map_err(|e| e.to_string())?;
+  res.text().await.map_err(|e| e.to_string())
+}
 
-  if let Some(caps) = html_main_regex().captures(&text) {
+/// Returns the parsed main-story hours and which pattern matched.
+fn hltb_parse_html(text: &str) -> Option<(f32, &'static str)> {
+  if let Some(caps) = html_main_regex().captures(text) {
     if let Some(mat) = caps.get(1) {
       if let Ok(v) = mat.as_str().parse::<f32>() {
-        return Ok(Some(v));
+        return Some((v, "gameplayMain"));
       }
     }
   }
 
-  if let Some(caps) = html_alt_regex().captures(&text) {
+  if let Some(caps) = html_alt_regex().captures(text) {
     if let Some(mat) = caps.get(1) {
       if let Ok(v) = mat.as_str().parse::<f32>() {
-        return Ok(Some(v));
+        return Some((v, "main-story-block"));
       }
     }
   }
 
-  Ok(None)
+  None
 }
 
 fn html_main_regex() -> &'static Regex {
@@ -320,64 +501,108 @@ fn html_alt_regex() -> &'static Regex {
   })
 }
 
-#[tauri::command]
-pub async fn hltb_search(title: String) -> Result<HLTBMeta, String> {
-  let trimmed = title.trim();
-  if trimmed.is_empty() {
-    return Ok(HLTBMeta {
-      main_median_hours: None,
-      source: "hltb".into(),
-    });
+fn hltb_meta(times: HltbTimes, source: &str, stale: bool) -> HLTBMeta {
+  HLTBMeta {
+    main_median_hours: times.main,
+    main_extra_median_hours: times.extras,
+    completionist_median_hours: times.completionist,
+    source: source.into(),
+    stale,
   }
+}
 
-  let key = normalize_key(trimmed);
-  let mut cache = read_cache_map::<HltbCacheEntry>(HLTB_CACHE_FILE);
-  if let Some(entry) = cache.get(&key) {
-    if !is_expired(entry.ts, HLTB_CACHE_TTL_SECS) {
-      return Ok(HLTBMeta {
-        main_median_hours: entry.value,
-        source: "hltb-cache".into(),
-      });
-    }
+/// Offline answer: any cached value (flagged stale once past its TTL), else the local dataset.
+fn hltb_offline(title: &str, key: &str, cache: &HashMap<String, HltbCacheEntry>) -> Result<HLTBMeta, String> {
+  if let Some(entry) = cache.get(key) {
+    return Ok(hltb_meta(entry.times(), "hltb-cache", is_expired(entry.ts, HLTB_CACHE_TTL_SECS)));
+  }
+  if let Some(hours) = datasets::local_hltb_hours(key) {
+    return Ok(hltb_meta(HltbTimes::main_only(Some(hours)), "hltb-local", false));
   }
+  Err(format!("Offline: no cached HLTB time for '{}'", title))
+}
+
+fn update_hltb_cache(key: &str, times: HltbTimes) {
+  update_cache_map(HLTB_CACHE_FILE, |cache| {
+    cache.insert(
+      key.to_string(),
+      HltbCacheEntry {
+        value: times.main,
+        extras: times.extras,
+        completionist: times.completionist,
+        ts: now_unix(),
+      },
+    );
+  });
+}
 
+async fn hltb_fetch(title: &str, key: &str) -> Result<HLTBMeta, String> {
+  breaker::check("hltb")?;
   let client = reqwest::Client::builder()
     .user_agent(USER_AGENT)
     .build()
     .map_err(|e| e.to_string())?;
 
-  match hltb_try_api(&client, trimmed).await {
-    Ok(Some(main)) => {
-      cache.insert(
-        key.clone(),
-        HltbCacheEntry {
-          value: Some(main),
-          ts: now_unix(),
-        },
-      );
-      write_cache_map(HLTB_CACHE_FILE, &cache);
-      return Ok(HLTBMeta {
-        main_median_hours: Some(main),
-        source: "hltb".into(),
-      });
+  match hltb_try_api(&client, title).await {
+    Ok(Some(times)) => {
+      breaker::record_success("hltb");
+      update_hltb_cache(key, times);
+      return Ok(hltb_meta(times, "hltb", false));
     }
-    Ok(None) | Err(_) => {}
+    Ok(None) => {}
+    // Still try the HTML page, but let a failing API count towards the breaker.
+    Err(e) => breaker::record_failure("hltb", &e),
   }
 
-  let fallback = hltb_try_html(&client, trimmed).await?;
-  cache.insert(
-    key,
-    HltbCacheEntry {
-      value: fallback,
-      ts: now_unix(),
-    },
-  );
-  write_cache_map(HLTB_CACHE_FILE, &cache);
+  let fallback = HltbTimes::main_only(breaker::observe("hltb", hltb_try_html(&client, title).await)?);
+  update_hltb_cache(key, fallback);
+  Ok(hltb_meta(fallback, "html", false))
+}
 
-  Ok(HLTBMeta {
-    main_median_hours: fallback,
-    source: "html".into(),
-  })
+#[derive(Serialize, Clone)]
+pub struct HltbRefreshed {
+  pub title: String,
+  pub meta: Option<HLTBMeta>,
+  pub error: Option<String>,
+}
+
+#[tauri::command]
+pub async fn hltb_search(app: tauri::AppHandle, title: String) -> Result<HLTBMeta, String> {
+  let trimmed = title.trim();
+  if trimmed.is_empty() {
+    return Ok(hltb_meta(HltbTimes::default(), "hltb", false));
+  }
+
+  let key = normalize_key(trimmed);
+  let cache = read_cache_map::<HltbCacheEntry>(HLTB_CACHE_FILE);
+  if let Some(entry) = cache.get(&key) {
+    if !is_expired(entry.ts, HLTB_CACHE_TTL_SECS) {
+      return Ok(hltb_meta(entry.times(), "hltb-cache", false));
+    }
+    // Stale-while-revalidate: answer with the old value now, refresh behind it.
+    if !offline::is_offline() && begin_refresh("hltb", &key) {
+      let title = trimmed.to_string();
+      tauri::async_runtime::spawn(async move {
+        let result = hltb_fetch(&title, &key).await;
+        end_refresh("hltb", &key);
+        let payload = match result {
+          Ok(meta) => HltbRefreshed { title, meta: Some(meta), error: None },
+          Err(e) => HltbRefreshed { title, meta: None, error: Some(e) },
+        };
+        let _ = app.emit(HLTB_REFRESHED_EVENT, payload);
+      });
+    }
+    return Ok(hltb_meta(entry.times(), "hltb-cache", true));
+  }
+  if offline::is_offline() {
+    return hltb_offline(trimmed, &key, &cache);
+  }
+
+  match hltb_fetch(trimmed, &key).await {
+    Ok(meta) => Ok(meta),
+    Err(_) if offline::is_offline() => hltb_offline(trimmed, &key, &cache),
+    Err(e) => Err(e),
+  }
 }
 
 #[tauri::command]
@@ -387,37 +612,112 @@ pub fn hltb_clear_cache() -> Result<(), String> {
 
 #[tauri::command]
 pub async fn get_steam_price_try(
+  app: tauri::AppHandle,
   appid: u32,
   region: Option<String>,
 ) -> Result<Option<SteamPrice>, String> {
-  let cc = region
-    .as_deref()
-    .unwrap_or(DEFAULT_STEAM_REGION)
-    .trim()
-    .to_lowercase();
-  let cc = if cc.is_empty() {
-    DEFAULT_STEAM_REGION.to_string()
-  } else {
-    cc
-  };
+  let cc = steam_region(region.as_deref());
 
-  let url = format!(
-    "https://store.steampowered.com/api/appdetails?appids={}&cc={}&filters=price_overview",
-    appid, cc
-  );
+  let cache_key = format!("{}:{}", appid, cc);
+  if offline::is_offline() {
+    return steam_price_offline(&cache_key);
+  }
 
   let client = reqwest::Client::builder()
     .user_agent(USER_AGENT)
     .build()
     .map_err(|e| e.to_string())?;
 
-  let res = client.get(url).send().await.map_err(|e| e.to_string())?;
+  breaker::check("steam-store")?;
+  let v = match breaker::observe("steam-store", steam_prices_request(&client, &[appid], &cc).await) {
+    Ok(v) => v,
+    Err(_) if offline::is_offline() => return steam_price_offline(&cache_key),
+    Err(e) => return Err(e),
+  };
+  let price = parse_steam_price(&v, appid)?;
+  cache_steam_prices(&cc, &[(appid, price.as_ref())]);
+  price_history::check_alerts(&app);
+
+  Ok(price)
+}
+
+pub(crate) fn steam_region(region: Option<&str>) -> String {
+  let cc = region.unwrap_or(DEFAULT_STEAM_REGION).trim().to_lowercase();
+  if cc.is_empty() {
+    DEFAULT_STEAM_REGION.to_string()
+  } else {
+    cc
+  }
+}
+
+/// Storefront base URL, overridable for mirrors and test servers.
+pub(crate) fn steam_store_base() -> String {
+  std::env::var("STEAM_STORE_BASE_URL")
+    .ok()
+    .map(|v| v.trim().trim_end_matches('/').to_string())
+    .filter(|v| !v.is_empty())
+    .unwrap_or_else(|| DEFAULT_STEAM_STORE_BASE.to_string())
+}
+
+pub(crate) fn cache_steam_prices(cc: &str, prices: &[(u32, Option<&SteamPrice>)]) {
+  let ts = now_unix();
+  update_cache_map(STEAM_PRICE_CACHE_FILE, |cache| {
+    for (appid, price) in prices {
+      cache.insert(
+        format!("{}:{}", appid, cc),
+        SteamPriceCacheEntry {
+          price: price.map(|p| p.price),
+          currency: price.map(|p| p.currency.clone()),
+          ts,
+        },
+      );
+    }
+  });
+  // The JSON cache keeps only the latest price; the history keeps them all.
+  let _ = price_history::record_prices(cc, prices);
+}
+
+fn steam_price_offline(cache_key: &str) -> Result<Option<SteamPrice>, String> {
+  let cache = read_cache_map::<SteamPriceCacheEntry>(STEAM_PRICE_CACHE_FILE);
+  let entry = cache
+    .get(cache_key)
+    .ok_or_else(|| format!("Offline: no cached Steam price for {}", cache_key))?;
+  let stale = is_expired(entry.ts, STEAM_PRICE_CACHE_TTL_SECS);
+  Ok(match (entry.price, entry.currency.clone()) {
+    (Some(price), Some(currency)) => Some(SteamPrice {
+      price,
+      currency,
+      stale,
+      initial_price: None,
+      discount_percent: None,
+    }),
+    _ => None,
+  })
+}
+
+/// appdetails accepts several comma-separated appids only together with the
+/// `price_overview` filter, which is all this needs.
+pub(crate) async fn steam_prices_request(client: &reqwest::Client, appids: &[u32], cc: &str) -> Result<Value, String> {
+  let ids: Vec<String> = appids.iter().map(|id| id.to_string()).collect();
+  let url = format!(
+    "{}/api/appdetails?appids={}&cc={}&filters=price_overview",
+    steam_store_base(),
+    ids.join(","),
+    cc
+  );
+
+  ratelimit::acquire(&url).await;
+  let res = client.get(url).send().await.map_err(|e| offline::note_error(&e))?;
+  offline::note_online();
   if !res.status().is_success() {
     return Err(format!("Steam HTTP {}", res.status()));
   }
   let txt = res.text().await.map_err(|e| e.to_string())?;
+  serde_json::from_str(&txt).map_err(|e| e.to_string())
+}
 
-  let v: Value = serde_json::from_str(&txt).map_err(|e| e.to_string())?;
+/// Picks the `price_overview` for `appid` out of an appdetails response.
+pub(crate) fn parse_steam_price(v: &Value, appid: u32) -> Result<Option<SteamPrice>, String> {
   let key = appid.to_string();
   if let Some(entry) = v.get(&key) {
     let parsed: SteamAppResult = serde_json::from_value(entry.clone()).map_err(|e| e.to_string())?;
@@ -426,7 +726,13 @@ pub async fn get_steam_price_try(
         if let Some(po) = data.price_overview {
           let price = po.final_price as f32 / 100.0;
           let currency = po.currency.to_uppercase();
-          return Ok(Some(SteamPrice { price, currency }));
+          return Ok(Some(SteamPrice {
+            price,
+            currency,
+            stale: false,
+            initial_price: Some(po.initial as f32 / 100.0).filter(|p| *p > 0.0),
+            discount_percent: Some(po.discount_percent),
+          }));
         }
       }
     }
@@ -448,11 +754,13 @@ fn rapid_get_json(url: &str, headers: &header::HeaderMap) -> Result<Value, Strin
   let mut attempt = 0;
   loop {
     attempt += 1;
+    ratelimit::acquire_blocking(url);
     let response = client
       .get(url)
       .headers(headers.clone())
       .send()
-      .map_err(|e| e.to_string())?;
+      .map_err(|e| offline::note_error(&e))?;
+    offline::note_online();
 
     if response.status() == StatusCode::TOO_MANY_REQUESTS {
       if attempt >= OPENCRITIC_MAX_RETRIES {
@@ -471,17 +779,92 @@ fn rapid_get_json(url: &str, headers: &header::HeaderMap) -> Result<Value, Strin
   }
 }
 
+#[derive(Serialize, Clone)]
+pub struct OpenCriticRefreshed {
+  pub title: String,
+  pub meta: Option<OpenCriticMeta>,
+  pub error: Option<String>,
+}
+
+fn oc_meta(score: Option<f32>, source: &str, stale: bool) -> OpenCriticMeta {
+  OpenCriticMeta {
+    score,
+    source: source.into(),
+    stale,
+  }
+}
+
+/// Offline answer: any cached score (flagged stale once past its TTL), else
+/// the local Metacritic dataset's metascore.
+fn opencritic_offline(title: &str, key: &str, cache: &HashMap<String, Cached>) -> Result<OpenCriticMeta, String> {
+  if let Some(entry) = cache.get(key) {
+    let ttl = if entry.score.is_some() { OPENCRITIC_CACHE_TTL_SECS } else { OPENCRITIC_NEGATIVE_TTL_SECS };
+    return Ok(oc_meta(entry.score, "opencritic-cache", is_expired(entry.cached_at, ttl)));
+  }
+  if let Some(score) = datasets::local_metascore(key) {
+    return Ok(oc_meta(Some(score), "metacritic-local", false));
+  }
+  Err(format!("Offline: no cached critic score for '{}'", title))
+}
+
+/// Cache-only view of HLTB, critic score and Steam price metadata for
+/// library-wide commands (export, analytics) that must not hit the network.
+#[derive(Default)]
+pub(crate) struct CachedMetadata {
+  hltb: HashMap<String, HltbCacheEntry>,
+  critic: HashMap<String, Cached>,
+  prices: HashMap<String, SteamPriceCacheEntry>,
+}
+
+impl CachedMetadata {
+  pub(crate) fn load() -> Self {
+    CachedMetadata {
+      hltb: read_cache_map(HLTB_CACHE_FILE),
+      critic: read_cache(&data_file(OPENCRITIC_CACHE_FILE)),
+      prices: read_cache_map(STEAM_PRICE_CACHE_FILE),
+    }
+  }
+
+  pub(crate) fn hltb_hours(&self, title: &str) -> Option<f32> {
+    self.hltb_times(title).main
+  }
+
+  pub(crate) fn hltb_times(&self, title: &str) -> HltbTimes {
+    let key = normalize_key(title);
+    match self.hltb.get(&key) {
+      Some(entry) => entry.times(),
+      None => HltbTimes::main_only(datasets::local_hltb_hours(&key)),
+    }
+  }
+
+  pub(crate) fn critic_score(&self, title: &str) -> Option<f32> {
+    let norm = normalize_title(title);
+    let key = if norm.is_empty() { title.trim().to_lowercase() } else { norm.to_lowercase() };
+    match self.critic.get(&key) {
+      Some(entry) => entry.score,
+      None => datasets::local_metascore(&key),
+    }
+  }
+
+  pub(crate) fn steam_price(&self, appid: u32, cc: &str) -> Option<SteamPrice> {
+    let entry = self.prices.get(&format!("{}:{}", appid, cc))?;
+    Some(SteamPrice {
+      price: entry.price?,
+      currency: entry.currency.clone()?,
+      stale: is_expired(entry.ts, STEAM_PRICE_CACHE_TTL_SECS),
+      initial_price: None,
+      discount_percent: None,
+    })
+  }
+}
+
 #[tauri::command]
-pub fn get_opencritic_score(title: String) -> Result<Option<f32>, String> {
+pub fn get_opencritic_score(app: tauri::AppHandle, title: String) -> Result<OpenCriticMeta, String> {
   let trimmed = title.trim();
   if trimmed.is_empty() {
-    return Ok(None);
+    return Ok(oc_meta(None, "opencritic", false));
   }
 
-  let api_key = std::env::var("OPENCRITIC_API_KEY")
-    .map_err(|_| "OPENCRITIC_API_KEY is not set".to_string())?;
-  let host = std::env::var("OPENCRITIC_HOST")
-    .unwrap_or_else(|_| "opencritic-api.p.rapidapi.com".to_string());
   let debug = std::env::var("DEBUG_OC").ok().as_deref() == Some("1");
 
   let normalized_title = {
@@ -495,28 +878,62 @@ pub fn get_opencritic_score(title: String) -> Result<Option<f32>, String> {
   let cache_key = normalized_title.to_lowercase();
 
   let cache_path = data_file(OPENCRITIC_CACHE_FILE);
-  let mut cache = read_cache(&cache_path);
+  let cache = read_cache(&cache_path);
   if let Some(entry) = cache.get(&cache_key) {
     let ttl = if entry.score.is_some() { OPENCRITIC_CACHE_TTL_SECS } else { OPENCRITIC_NEGATIVE_TTL_SECS };
     if !is_expired(entry.cached_at, ttl) {
       if debug { eprintln!("DEBUG_OC: CACHE_HIT {} -> {:?}", &cache_key, entry.score); }
-      return Ok(entry.score);
-    } else if debug { eprintln!("DEBUG_OC: CACHE_EXPIRED {}", &cache_key); }
+      return Ok(oc_meta(entry.score, "opencritic-cache", false));
+    }
+    if debug { eprintln!("DEBUG_OC: CACHE_EXPIRED {}", &cache_key); }
+    // Hand back the expired score right away; a worker thread refreshes it.
+    if !offline::is_offline() && begin_refresh("opencritic", &cache_key) {
+      let title = trimmed.to_string();
+      let key = cache_key.clone();
+      thread::spawn(move || {
+        let result = opencritic_fetch(&title, &normalized_title, &key, debug);
+        end_refresh("opencritic", &key);
+        let payload = match result {
+          Ok(meta) => OpenCriticRefreshed { title, meta: Some(meta), error: None },
+          Err(e) => OpenCriticRefreshed { title, meta: None, error: Some(e) },
+        };
+        let _ = app.emit(OPENCRITIC_REFRESHED_EVENT, payload);
+      });
+    }
+    return Ok(oc_meta(entry.score, "opencritic-cache", true));
+  }
+  if offline::is_offline() {
+    return opencritic_offline(trimmed, &cache_key, &cache);
   }
 
-  let mut headers = header::HeaderMap::new();
-  headers.insert(
-    header::HeaderName::from_static("x-rapidapi-key"),
-    header::HeaderValue::from_str(&api_key).map_err(|e| e.to_string())?,
-  );
-  headers.insert(
-    header::HeaderName::from_static("x-rapidapi-host"),
-    header::HeaderValue::from_str(&host).map_err(|e| e.to_string())?,
-  );
+  match opencritic_fetch(trimmed, &normalized_title, &cache_key, debug) {
+    Ok(meta) => Ok(meta),
+    Err(_) if offline::is_offline() => opencritic_offline(trimmed, &cache_key, &cache),
+    Err(e) => Err(e),
+  }
+}
 
-  let query = urlencoding::encode(&normalized_title);
+fn update_opencritic_cache(key: &str, score: Option<f32>) {
+  update_cache_map(OPENCRITIC_CACHE_FILE, |cache| {
+    cache.insert(key.to_string(), Cached { score, cached_at: now_unix() });
+  });
+}
+
+fn opencritic_fetch(
+  trimmed: &str,
+  normalized_title: &str,
+  cache_key: &str,
+  debug: bool,
+) -> Result<OpenCriticMeta, String> {
+  let api_key = std::env::var("OPENCRITIC_API_KEY")
+    .map_err(|_| "OPENCRITIC_API_KEY is not set".to_string())?;
+  let host = opencritic_host();
+  let headers = opencritic_headers(&api_key, &host)?;
+
+  let query = urlencoding::encode(normalized_title);
   let search_url = format!("https://{}/game/search?criteria={}", host, query);
-  let search_json = rapid_get_json(&search_url, &headers)?;
+  breaker::check("opencritic")?;
+  let search_json = breaker::observe("opencritic", rapid_get_json(&search_url, &headers))?;
   let results: Vec<Value> = match &search_json {
     Value::Array(arr) => arr.clone(),
     Value::Object(obj) => obj
@@ -528,13 +945,9 @@ pub fn get_opencritic_score(title: String) -> Result<Option<f32>, String> {
   };
 
   if results.is_empty() {
-    if debug { eprintln!("DEBUG_OC: EMPTY_SEARCH for '{}': {}", trimmed, &normalized_title); }
-    cache.insert(
-      cache_key,
-      Cached { score: None, cached_at: now_unix() }
-    );
-    write_cache(&cache_path, &cache);
-    return Ok(None);
+    if debug { eprintln!("DEBUG_OC: EMPTY_SEARCH for '{}': {}", trimmed, normalized_title); }
+    update_opencritic_cache(cache_key, None);
+    return Ok(oc_meta(None, "opencritic", false));
   }
 
   // Choose best result by fuzzy score
@@ -554,12 +967,8 @@ pub fn get_opencritic_score(title: String) -> Result<Option<f32>, String> {
   let threshold_ok = 0.85f64;
   if best_score < threshold_ok {
     if debug { eprintln!("DEBUG_OC: FUZZY_LOW score={:.3} for '{}'", best_score, trimmed); }
-    cache.insert(
-      cache_key,
-      Cached { score: None, cached_at: now_unix() }
-    );
-    write_cache(&cache_path, &cache);
-    return Ok(None);
+    update_opencritic_cache(cache_key, None);
+    return Ok(oc_meta(None, "opencritic", false));
   }
   let chosen = results[best_idx].clone();
 
@@ -569,34 +978,237 @@ pub fn get_opencritic_score(title: String) -> Result<Option<f32>, String> {
     .ok_or_else(|| "OpenCritic search result missing id".to_string())?;
 
   let details_url = format!("https://{}/game/{}", host, game_id);
-  let details_json = rapid_get_json(&details_url, &headers)?;
+  let details_json = breaker::observe("opencritic", rapid_get_json(&details_url, &headers))?;
   let score = details_json
     .get("topCriticScore")
     .and_then(|v| v.as_f64())
     .map(|v| v as f32);
 
   if let Some(value) = score {
-    cache.insert(
-      cache_key,
-      Cached {
-        score: Some(value),
-        cached_at: now_unix(),
-      },
-    );
-    write_cache(&cache_path, &cache);
-    Ok(Some(value))
+    update_opencritic_cache(cache_key, Some(value));
+    Ok(oc_meta(Some(value), "opencritic", false))
   } else {
-    cache.insert(
-      cache_key,
-      Cached { score: None, cached_at: now_unix() }
-    );
-    write_cache(&cache_path, &cache);
-    Ok(None)
+    update_opencritic_cache(cache_key, None);
+    Ok(oc_meta(None, "opencritic", false))
+  }
+}
+
+fn opencritic_host() -> String {
+  std::env::var("OPENCRITIC_HOST").unwrap_or_else(|_| "opencritic-api.p.rapidapi.com".to_string())
+}
+
+fn opencritic_headers(api_key: &str, host: &str) -> Result<header::HeaderMap, String> {
+  let mut headers = header::HeaderMap::new();
+  headers.insert(
+    header::HeaderName::from_static("x-rapidapi-key"),
+    header::HeaderValue::from_str(api_key).map_err(|e| e.to_string())?,
+  );
+  headers.insert(
+    header::HeaderName::from_static("x-rapidapi-host"),
+    header::HeaderValue::from_str(host).map_err(|e| e.to_string())?,
+  );
+  Ok(headers)
+}
+
+#[derive(Serialize)]
+pub struct ProviderDiagnostic {
+  pub provider: String,
+  pub configured: bool,
+  pub config_note: Option<String>,
+  /// "ok" | "empty" | "error" | "skipped"
+  pub status: String,
+  pub latency_ms: Option<u64>,
+  pub result_shape: Option<String>,
+  pub parsed: Option<String>,
+  pub error: Option<String>,
+}
+
+impl ProviderDiagnostic {
+  fn new(provider: &str, configured: bool, config_note: Option<String>) -> Self {
+    Self {
+      provider: provider.into(),
+      configured,
+      config_note,
+      status: "skipped".into(),
+      latency_ms: None,
+      result_shape: None,
+      parsed: None,
+      error: None,
+    }
+  }
+
+  fn fail(mut self, started: Instant, err: String) -> Self {
+    self.status = "error".into();
+    self.latency_ms = Some(started.elapsed().as_millis() as u64);
+    self.error = Some(err);
+    self
+  }
+}
+
+/// Summarises the structure of a JSON payload (keys and array lengths, two
+/// levels deep) so a changed upstream schema is visible at a glance.
+fn describe_shape(v: &Value, depth: usize) -> String {
+  match v {
+    Value::Null => "null".into(),
+    Value::Bool(_) => "bool".into(),
+    Value::Number(_) => "number".into(),
+    Value::String(_) => "string".into(),
+    Value::Array(arr) => match arr.first() {
+      Some(first) if depth > 0 => format!("array[{}]<{}>", arr.len(), describe_shape(first, depth - 1)),
+      _ => format!("array[{}]", arr.len()),
+    },
+    Value::Object(obj) => {
+      if depth == 0 {
+        return format!("object{{{} keys}}", obj.len());
+      }
+      let fields: Vec<String> = obj
+        .iter()
+        .take(12)
+        .map(|(k, v)| format!("{}: {}", k, describe_shape(v, depth - 1)))
+        .collect();
+      let more = if obj.len() > 12 { ", …" } else { "" };
+      format!("{{{}{}}}", fields.join(", "), more)
+    }
+  }
+}
+
+async fn diagnose_hltb_api(client: &reqwest::Client) -> ProviderDiagnostic {
+  let diag = ProviderDiagnostic::new("hltb-api", true, None);
+  let started = Instant::now();
+  let body = match hltb_api_request(client, DIAG_HLTB_TITLE).await {
+    Ok(body) => body,
+    Err(e) => return diag.fail(started, e),
+  };
+  let mut diag = diag;
+  diag.latency_ms = Some(started.elapsed().as_millis() as u64);
+  diag.result_shape = Some(describe_shape(&body, 2));
+  match hltb_parse_api(body) {
+    Ok(Some(times)) => {
+      diag.status = "ok".into();
+      diag.parsed = Some(format!(
+        "gameplayMain={:?} gameplayMainExtra={:?} gameplayCompletionist={:?}",
+        times.main, times.extras, times.completionist
+      ));
+    }
+    Ok(None) => diag.status = "empty".into(),
+    Err(e) => {
+      diag.status = "error".into();
+      diag.error = Some(format!("parse: {}", e));
+    }
+  }
+  diag
+}
+
+async fn diagnose_hltb_html(client: &reqwest::Client) -> ProviderDiagnostic {
+  let diag = ProviderDiagnostic::new("hltb-html", true, None);
+  let started = Instant::now();
+  let text = match hltb_html_request(client, DIAG_HLTB_TITLE).await {
+    Ok(text) => text,
+    Err(e) => return diag.fail(started, e),
+  };
+  let mut diag = diag;
+  diag.latency_ms = Some(started.elapsed().as_millis() as u64);
+  diag.result_shape = Some(format!("html[{} bytes]", text.len()));
+  match hltb_parse_html(&text) {
+    Some((hours, pattern)) => {
+      diag.status = "ok".into();
+      diag.parsed = Some(format!("{}={}", pattern, hours));
+    }
+    None => diag.status = "empty".into(),
+  }
+  diag
+}
+
+async fn diagnose_steam_store(client: &reqwest::Client) -> ProviderDiagnostic {
+  let diag = ProviderDiagnostic::new("steam-store", true, None);
+  let started = Instant::now();
+  let body = match steam_prices_request(client, &[DIAG_STEAM_APPID], DEFAULT_STEAM_REGION).await {
+    Ok(body) => body,
+    Err(e) => return diag.fail(started, e),
+  };
+  let mut diag = diag;
+  diag.latency_ms = Some(started.elapsed().as_millis() as u64);
+  diag.result_shape = Some(describe_shape(&body, 3));
+  match parse_steam_price(&body, DIAG_STEAM_APPID) {
+    Ok(Some(p)) => {
+      diag.status = "ok".into();
+      diag.parsed = Some(format!("{:.2} {}", p.price, p.currency));
+    }
+    Ok(None) => diag.status = "empty".into(),
+    Err(e) => {
+      diag.status = "error".into();
+      diag.error = Some(format!("parse: {}", e));
+    }
   }
+  diag
+}
+
+fn diagnose_opencritic() -> ProviderDiagnostic {
+  let host = opencritic_host();
+  let api_key = match std::env::var("OPENCRITIC_API_KEY") {
+    Ok(key) if !key.trim().is_empty() => key,
+    _ => {
+      return ProviderDiagnostic::new("opencritic", false, Some("OPENCRITIC_API_KEY is not set".into()));
+    }
+  };
+  let diag = ProviderDiagnostic::new("opencritic", true, Some(format!("host {}", host)));
+  let started = Instant::now();
+  let headers = match opencritic_headers(&api_key, &host) {
+    Ok(h) => h,
+    Err(e) => return diag.fail(started, e),
+  };
+  let url = format!(
+    "https://{}/game/search?criteria={}",
+    host,
+    urlencoding::encode(DIAG_HLTB_TITLE)
+  );
+  let body = match rapid_get_json(&url, &headers) {
+    Ok(body) => body,
+    Err(e) => return diag.fail(started, e),
+  };
+  let mut diag = diag;
+  diag.latency_ms = Some(started.elapsed().as_millis() as u64);
+  diag.result_shape = Some(describe_shape(&body, 2));
+  let first = match &body {
+    Value::Array(arr) => arr.first(),
+    Value::Object(obj) => obj.get("results").and_then(|v| v.as_array()).and_then(|a| a.first()),
+    _ => None,
+  };
+  match first.and_then(|item| Some((item.get("id")?.as_u64()?, item.get("name")?.as_str()?))) {
+    Some((id, name)) => {
+      diag.status = "ok".into();
+      diag.parsed = Some(format!("id={} name={}", id, name));
+    }
+    None => diag.status = "empty".into(),
+  }
+  diag
+}
+
+/// Probes every metadata provider with a known title and reports whether the
+/// response still parses, so upstream API changes show up immediately.
+#[tauri::command]
+pub async fn diagnose_providers() -> Result<Vec<ProviderDiagnostic>, String> {
+  let client = reqwest::Client::builder()
+    .user_agent(USER_AGENT)
+    .timeout(Duration::from_secs(DIAG_TIMEOUT_SECS))
+    .build()
+    .map_err(|e| e.to_string())?;
+
+  let mut out = vec![
+    diagnose_hltb_api(&client).await,
+    diagnose_hltb_html(&client).await,
+    diagnose_steam_store(&client).await,
+  ];
+  // rapid_get_json uses the blocking client, which must stay off the async runtime.
+  let oc = tauri::async_runtime::spawn_blocking(diagnose_opencritic)
+    .await
+    .map_err(|e| e.to_string())?;
+  out.push(oc);
+  Ok(out)
 }
 
 // simple token-set jaccard similarity on whitespace tokens
-fn jaccard_token_set(a: &str, b: &str) -> f64 {
+pub(crate) fn jaccard_token_set(a: &str, b: &str) -> f64 {
   use std::collections::HashSet;
   let ta: HashSet<_> = a.split_whitespace().collect();
   let tb: HashSet<_> = b.split_whitespace().collect();
@@ -0,0 +1,838 @@
+use crate::backup;
+use crate::commands::{normalize_key, parse_timestamp};
+use crate::library::{self, Identity, LibraryItem};
+use deunicode::deunicode;
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1254};
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+  collections::{HashMap, HashSet},
+  fs::File,
+  io::Read,
+  sync::OnceLock,
+};
+
+const SNIFF_BYTES: usize = 64 * 1024;
+const DEFAULT_PREVIEW_ROWS: usize = 50;
+// Keeps the preview payload bounded on badly broken files.
+const MAX_REPORTED_ERRORS: usize = 500;
+const DEFAULT_MEMBER: &str = "everyone";
+
+// Import field -> header aliases, compared after folding to lowercase ASCII
+// alphanumerics (so "Oyun Adı" matches "oyunadi"). Matches are exact: a
+// prefix would read "Store Link" as the account or "Hours Played" as HLTB time.
+const COLUMN_ALIASES: &[(&str, &[&str])] = &[
+  ("title", &["title", "name", "game", "gamename", "gametitle", "oyun", "oyunadi", "isim", "ad", "baslik"]),
+  ("platform", &["platform", "system", "console", "platforma", "sistem", "konsol"]),
+  ("status", &["status", "state", "progress", "durum"]),
+  ("memberId", &["member", "memberid", "owner", "player", "user", "uye", "kisi", "sahip"]),
+  ("accountId", &["account", "accountid", "store", "launcher", "hesap", "magaza"]),
+  ("priceTRY", &["price", "pricetry", "paid", "cost", "fiyat", "ucret", "tutar"]),
+  ("currencyCode", &["currency", "currencycode", "parabirimi", "doviz"]),
+  ("acquiredAt", &["acquiredat", "acquired", "purchased", "purchasedate", "dateadded", "added", "date", "tarih", "alimtarihi"]),
+  ("ocScore", &["ocscore", "opencritic", "opencriticscore"]),
+  ("mcScore", &["mcscore", "metacritic", "metacriticscore", "metascore", "score", "puan"]),
+  ("ttbMedianMainH", &["ttb", "hltb", "hours", "ttbmedianmainh", "mainstory", "sure", "saat"]),
+  ("services", &["services", "service", "subscription", "abonelik"]),
+  ("appid", &["appid", "steamappid"]),
+  ("url", &["url", "link", "storeurl", "steamurl", "storelink"]),
+];
+
+#[derive(Serialize, Clone)]
+pub struct CsvRowError {
+  /// 1-based line in the file, counting the header.
+  pub line: u64,
+  pub field: Option<String>,
+  pub message: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct CsvRow {
+  pub line: u64,
+  pub title: String,
+  pub platform: String,
+  pub status: String,
+  pub member_id: String,
+  pub account_id: Option<String>,
+  pub price: Option<f64>,
+  pub currency_code: Option<String>,
+  pub acquired_at: Option<String>,
+  pub oc_score: Option<f64>,
+  pub mc_score: Option<f64>,
+  pub ttb_hours: Option<f64>,
+  pub services: Option<Vec<String>>,
+  pub appid: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct CsvPreview {
+  pub encoding: String,
+  pub delimiter: String,
+  pub has_bom: bool,
+  pub headers: Vec<String>,
+  /// Import field -> header used for it.
+  pub mapping: HashMap<String, String>,
+  pub total_rows: usize,
+  pub valid_rows: usize,
+  pub rows: Vec<CsvRow>,
+  pub errors: Vec<CsvRowError>,
+  pub error_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct CsvImportResult {
+  pub identities_created: usize,
+  pub identities_matched: usize,
+  pub items_created: usize,
+  pub duplicates: usize,
+  pub invalid_rows: usize,
+  pub backup: Option<String>,
+}
+
+struct Sniff {
+  encoding: &'static Encoding,
+  has_bom: bool,
+  delimiter: u8,
+}
+
+fn steam_url_regex() -> &'static Regex {
+  static RE: OnceLock<Regex> = OnceLock::new();
+  RE.get_or_init(|| {
+    Regex::new(r"(?i)(?:store\.steampowered\.com|steamcommunity\.com)/app/(\d+)|steam://(?:run|rungameid|install|store)/(\d+)")
+      .unwrap()
+  })
+}
+
+fn url_regex() -> &'static Regex {
+  static RE: OnceLock<Regex> = OnceLock::new();
+  RE.get_or_init(|| Regex::new(r"https?://\S+").unwrap())
+}
+
+fn title_split_regex() -> &'static Regex {
+  static RE: OnceLock<Regex> = OnceLock::new();
+  RE.get_or_init(|| Regex::new(r"\s*[:|\u{2013}\u{2014}]\s*|\s+-\s+").unwrap())
+}
+
+fn fold_header(h: &str) -> String {
+  deunicode(h).to_lowercase().chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+}
+
+/// Picks the encoding from the BOM, else UTF-8 when the sample decodes
+/// cleanly, else Windows-1254 (the usual Turkish Excel export).
+fn sniff_encoding(sample: &[u8]) -> (&'static Encoding, bool) {
+  if let Some((enc, _)) = Encoding::for_bom(sample) {
+    return (enc, true);
+  }
+  // A multi-byte sequence may be cut at the sample boundary; only judge
+  // what came before the last few bytes.
+  let judged = match std::str::from_utf8(sample) {
+    Ok(_) => true,
+    Err(e) => e.error_len().is_none() && sample.len() - e.valid_up_to() < 4,
+  };
+  if judged {
+    (UTF_8, false)
+  } else {
+    (WINDOWS_1254, false)
+  }
+}
+
+/// Counts candidate delimiters outside quotes over the first lines and picks
+/// the most frequent; ties go to the comma.
+fn sniff_delimiter(text: &str) -> u8 {
+  let mut counts = [0usize; 4];
+  let candidates = [b',', b';', b'\t', b'|'];
+  let mut in_quote = false;
+  let mut lines = 0;
+  for ch in text.bytes() {
+    match ch {
+      b'"' => in_quote = !in_quote,
+      b'\n' if !in_quote => {
+        lines += 1;
+        if lines >= 20 {
+          break;
+        }
+      }
+      _ if !in_quote => {
+        if let Some(i) = candidates.iter().position(|c| *c == ch) {
+          counts[i] += 1;
+        }
+      }
+      _ => {}
+    }
+  }
+  let best = (0..candidates.len()).max_by_key(|i| (counts[*i], std::cmp::Reverse(*i))).unwrap_or(0);
+  if counts[best] == 0 {
+    b','
+  } else {
+    candidates[best]
+  }
+}
+
+fn sniff(path: &str) -> Result<Sniff, String> {
+  let mut file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+  let mut sample = vec![0u8; SNIFF_BYTES];
+  let mut read = 0;
+  while read < sample.len() {
+    let n = file.read(&mut sample[read..]).map_err(|e| e.to_string())?;
+    if n == 0 {
+      break;
+    }
+    read += n;
+  }
+  sample.truncate(read);
+  let (encoding, has_bom) = sniff_encoding(&sample);
+  let (text, _, _) = encoding.decode(&sample);
+  Ok(Sniff {
+    encoding,
+    has_bom,
+    delimiter: sniff_delimiter(&text),
+  })
+}
+
+fn open_reader(path: &str, sniff: &Sniff) -> Result<csv::Reader<impl Read>, String> {
+  let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+  let decoded = DecodeReaderBytesBuilder::new()
+    .encoding(Some(sniff.encoding))
+    .bom_override(true)
+    .strip_bom(true)
+    .build(file);
+  Ok(
+    csv::ReaderBuilder::new()
+      .delimiter(sniff.delimiter)
+      .flexible(true)
+      .trim(csv::Trim::All)
+      .from_reader(decoded),
+  )
+}
+
+pub(crate) fn auto_mapping(headers: &[String]) -> HashMap<String, String> {
+  let mut mapping = HashMap::new();
+  for (field, aliases) in COLUMN_ALIASES {
+    // A header named after the field itself wins, so an export carrying both
+    // "memberId" and the "member" display name reads back the id.
+    let hit = headers
+      .iter()
+      .find(|h| fold_header(h) == fold_header(field))
+      .or_else(|| headers.iter().find(|h| aliases.contains(&fold_header(h).as_str())));
+    if let Some(h) = hit {
+      mapping.insert(field.to_string(), h.clone());
+    }
+  }
+  mapping
+}
+
+/// Game title from a cell that may carry a store URL, mirroring `extractTitle`
+/// in `@tracker/core`. Plain titles are kept whole so subtitles survive.
+fn extract_title(raw: &str) -> String {
+  let trimmed = raw.trim();
+  let Some(m) = url_regex().find(trimmed) else {
+    return trimmed.to_string();
+  };
+  if m.start() > 0 {
+    let before = trimmed[..m.start()].trim_end_matches([' ', ':', '|', '-', '\u{2013}', '\u{2014}']).trim();
+    if !before.is_empty() {
+      return before.to_string();
+    }
+  }
+  let without_url = url_regex().replace_all(trimmed, "").trim().to_string();
+  title_split_regex()
+    .split(&without_url)
+    .next()
+    .map(str::trim)
+    .filter(|t| !t.is_empty())
+    .map(str::to_string)
+    .unwrap_or(without_url)
+}
+
+fn normalize_platform(s: &str) -> Option<&'static str> {
+  let t = deunicode(s).to_lowercase();
+  if t.is_empty() {
+    return None;
+  }
+  if ["pc", "steam", "epic", "gog", "windows", "bilgisayar"].iter().any(|k| t.contains(k)) {
+    Some("PC")
+  } else if t.contains("xbox") {
+    Some("Xbox")
+  } else if t.contains("ps") || t.contains("playstation") {
+    Some("PlayStation")
+  } else if t.contains("switch") || t.contains("nintendo") {
+    Some("Switch")
+  } else if t.contains("android") || t.contains("mobile") || t.contains("mobil") {
+    Some("Android")
+  } else {
+    None
+  }
+}
+
+/// English keywords follow `normalizeStatus`; Turkish ones cover our own sheets.
+/// Negated and planned phrases are checked first so "Not played", "Plan to
+/// play" and "Incomplete" don't hit the "play"/"complete" keywords.
+fn normalize_status(s: &str) -> Option<&'static str> {
+  let t = deunicode(s).to_lowercase();
+  let has = |keys: &[&str]| keys.iter().any(|k| t.contains(k));
+  if has(&[
+    "backlog",
+    "not played",
+    "unplayed",
+    "never played",
+    "not started",
+    "plan to",
+    "planned",
+    "incomplete",
+    "unfinished",
+    "not finished",
+    "sirada",
+    "bekliyor",
+    "oynanmadi",
+    "bitmedi",
+    "bitirilmedi",
+  ]) {
+    Some("Backlog")
+  } else if has(&["playing", "play", "oynaniyor", "oynuyor"]) {
+    Some("Playing")
+  } else if has(&["beat", "clear", "finished", "complete", "bitti", "bitirildi", "tamam"]) {
+    Some("Beaten")
+  } else if has(&["abandon", "drop", "birakildi", "yarim"]) {
+    Some("Abandoned")
+  } else if has(&["wish", "istek"]) {
+    Some("Wishlist")
+  } else if has(&["own", "library", "purchased", "sahip", "kutuphane"]) {
+    Some("Owned")
+  } else {
+    None
+  }
+}
+
+fn currency_from_symbol(s: &str) -> Option<&'static str> {
+  let u = s.to_uppercase();
+  if u.contains('₺') || u.contains("TL") || u.contains("TRY") {
+    Some("TRY")
+  } else if u.contains('€') || u.contains("EUR") {
+    Some("EUR")
+  } else if u.contains('$') || u.contains("USD") {
+    Some("USD")
+  } else if u.contains('£') || u.contains("GBP") {
+    Some("GBP")
+  } else {
+    None
+  }
+}
+
+/// Parses "1.234,56", "1,234.56", "1.234.567", "59,99 TL" and plain numbers.
+/// A separator only groups thousands when both kinds appear or it repeats;
+/// a lone one is the decimal point, so "0.999" and "12.500" stay small.
+fn parse_number(s: &str) -> Option<f64> {
+  let cleaned: String = s.chars().filter(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | '-')).collect();
+  if cleaned.is_empty() {
+    return None;
+  }
+  let last_dot = cleaned.rfind('.');
+  let last_comma = cleaned.rfind(',');
+  let decimal = match (last_dot, last_comma) {
+    (Some(d), Some(c)) => Some(if d > c { '.' } else { ',' }),
+    (Some(i), None) | (None, Some(i)) => {
+      let sep = cleaned.as_bytes()[i] as char;
+      (cleaned.matches(sep).count() == 1).then_some(sep)
+    }
+    (None, None) => None,
+  };
+  let normalized: String = cleaned
+    .chars()
+    .filter_map(|c| match c {
+      '.' | ',' if Some(c) == decimal => Some('.'),
+      '.' | ',' => None,
+      other => Some(other),
+    })
+    .collect();
+  normalized.parse().ok()
+}
+
+/// ISO dates pass through; "31.12.2023" and "31/12/2023" are read day-first.
+fn normalize_date(s: &str) -> Option<String> {
+  let ts = parse_timestamp(s).or_else(|| {
+    ["%d.%m.%Y", "%d/%m/%Y", "%d-%m-%Y"]
+      .iter()
+      .find_map(|fmt| chrono::NaiveDate::parse_from_str(s.trim(), fmt).ok())
+      .and_then(|d| d.and_hms_opt(0, 0, 0))
+      .map(|dt| dt.and_utc().timestamp())
+  })?;
+  chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.format("%Y-%m-%d").to_string())
+}
+
+fn steam_appid_in(text: &str) -> Option<u32> {
+  let caps = steam_url_regex().captures(text)?;
+  caps.get(1).or_else(|| caps.get(2))?.as_str().parse().ok()
+}
+
+fn parse_row(
+  line: u64,
+  record: &csv::StringRecord,
+  columns: &HashMap<String, usize>,
+  errors: &mut Vec<CsvRowError>,
+) -> Option<CsvRow> {
+  let cell = |field: &str| {
+    columns
+      .get(field)
+      .and_then(|i| record.get(*i))
+      .map(str::trim)
+      .filter(|v| !v.is_empty())
+  };
+  let before = errors.len();
+  let mut fail = |field: &str, message: String| {
+    errors.push(CsvRowError {
+      line,
+      field: Some(field.to_string()),
+      message,
+    });
+  };
+
+  let raw_title = cell("title").unwrap_or("");
+  let title = extract_title(raw_title);
+  if title.is_empty() {
+    fail("title", "Missing title".into());
+  }
+
+  let platform = match cell("platform") {
+    Some(p) => normalize_platform(p).unwrap_or_else(|| {
+      fail("platform", format!("Unknown platform '{}'", p));
+      "PC"
+    }),
+    None => "PC",
+  };
+  let status = match cell("status") {
+    Some(s) => normalize_status(s).unwrap_or_else(|| {
+      fail("status", format!("Unknown status '{}'", s));
+      "Backlog"
+    }),
+    None => "Backlog",
+  };
+
+  let raw_price = cell("priceTRY");
+  let price = raw_price.and_then(|p| {
+    let v = parse_number(p);
+    if v.is_none() {
+      fail("priceTRY", format!("Not a price: '{}'", p));
+    }
+    v
+  });
+  let currency_code = cell("currencyCode")
+    .map(|c| c.to_uppercase())
+    .or_else(|| raw_price.and_then(currency_from_symbol).map(str::to_string));
+
+  let mut number_field = |field: &str, label: &str| {
+    cell(field).and_then(|v| {
+      let n = parse_number(v);
+      if n.is_none() {
+        fail(field, format!("Not a {}: '{}'", label, v));
+      }
+      n
+    })
+  };
+  let oc_score = number_field("ocScore", "score");
+  let mc_score = number_field("mcScore", "score");
+  let ttb_hours = number_field("ttbMedianMainH", "number of hours");
+
+  let acquired_at = cell("acquiredAt").and_then(|d| {
+    let v = normalize_date(d);
+    if v.is_none() {
+      fail("acquiredAt", format!("Unrecognised date '{}'", d));
+    }
+    v
+  });
+
+  let appid = cell("appid")
+    .and_then(|a| a.parse::<u32>().ok().or_else(|| steam_appid_in(a)))
+    .or_else(|| record.iter().find_map(steam_appid_in));
+  let account_id = cell("accountId").map(str::to_string).or_else(|| {
+    let steam_link = record.iter().any(|v| steam_appid_in(v).is_some());
+    steam_link.then(|| "Steam".to_string())
+  });
+
+  if errors.len() > before {
+    return None;
+  }
+  Some(CsvRow {
+    line,
+    title,
+    platform: platform.to_string(),
+    status: status.to_string(),
+    member_id: cell("memberId").unwrap_or(DEFAULT_MEMBER).to_string(),
+    account_id,
+    price,
+    currency_code,
+    acquired_at,
+    oc_score,
+    mc_score,
+    ttb_hours,
+    services: cell("services").map(|s| {
+      s.split([',', ';'])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+    }),
+    appid,
+  })
+}
+
+/// Streams the file once, handing each valid row to `on_row`.
+fn scan(
+  path: &str,
+  mapping: Option<HashMap<String, String>>,
+  mut on_row: impl FnMut(CsvRow),
+) -> Result<CsvPreview, String> {
+  let sniff = sniff(path)?;
+  let mut reader = open_reader(path, &sniff)?;
+  let headers: Vec<String> = reader
+    .headers()
+    .map_err(|e| format!("{}: {}", path, e))?
+    .iter()
+    .map(|h| h.trim_start_matches('\u{feff}').to_string())
+    .collect();
+  let mapping = match mapping {
+    Some(m) if !m.is_empty() => m,
+    _ => auto_mapping(&headers),
+  };
+  let mut columns = HashMap::new();
+  for (field, header) in &mapping {
+    let idx = headers
+      .iter()
+      .position(|h| h == header)
+      .ok_or_else(|| format!("Column '{}' mapped to {} is not in the file", header, field))?;
+    columns.insert(field.clone(), idx);
+  }
+  if !columns.contains_key("title") {
+    return Err("No title column found; map one explicitly".into());
+  }
+
+  let mut preview = CsvPreview {
+    encoding: sniff.encoding.name().to_string(),
+    delimiter: (sniff.delimiter as char).to_string(),
+    has_bom: sniff.has_bom,
+    headers,
+    mapping,
+    total_rows: 0,
+    valid_rows: 0,
+    rows: Vec::new(),
+    errors: Vec::new(),
+    error_count: 0,
+  };
+  let mut row_errors = Vec::new();
+  let mut record = csv::StringRecord::new();
+  loop {
+    let line = reader.position().line();
+    match reader.read_record(&mut record) {
+      Ok(false) => break,
+      Ok(true) => {}
+      Err(e) => {
+        preview.total_rows += 1;
+        preview.error_count += 1;
+        if preview.errors.len() < MAX_REPORTED_ERRORS {
+          preview.errors.push(CsvRowError {
+            line,
+            field: None,
+            message: e.to_string(),
+          });
+        }
+        continue;
+      }
+    }
+    if record.iter().all(|v| v.trim().is_empty()) {
+      continue;
+    }
+    preview.total_rows += 1;
+    row_errors.clear();
+    match parse_row(line, &record, &columns, &mut row_errors) {
+      Some(row) => {
+        preview.valid_rows += 1;
+        on_row(row);
+      }
+      None => {
+        preview.error_count += 1;
+        let room = MAX_REPORTED_ERRORS.saturating_sub(preview.errors.len());
+        preview.errors.extend(row_errors.drain(..).take(room));
+      }
+    }
+  }
+  Ok(preview)
+}
+
+/// Sniffs and parses a CSV without touching the library. Returns the column
+/// mapping used, the first `limit` parsed rows and every row error.
+#[tauri::command]
+pub fn csv_import_preview(
+  path: String,
+  mapping: Option<HashMap<String, String>>,
+  limit: Option<usize>,
+) -> Result<CsvPreview, String> {
+  let limit = limit.unwrap_or(DEFAULT_PREVIEW_ROWS);
+  let mut rows = Vec::new();
+  let mut preview = scan(&path, mapping, |row| {
+    if rows.len() < limit {
+      rows.push(row);
+    }
+  })?;
+  preview.rows = rows;
+  Ok(preview)
+}
+
+/// Imports the valid rows of a CSV into the library store. Rows with errors
+/// are skipped; a title already owned by the same member is not duplicated.
+#[tauri::command]
+pub fn csv_import_commit(path: String, mapping: Option<HashMap<String, String>>) -> Result<CsvImportResult, String> {
+  let mut rows = Vec::new();
+  let preview = scan(&path, mapping, |row| rows.push(row))?;
+
+  let backup = backup::create_backup("pre_csv_import")?;
+  let mut conn = library::open_db()?;
+  let mut by_appid: HashMap<u32, String> = HashMap::new();
+  let mut by_key: HashMap<(String, String), String> = HashMap::new();
+  let mut identities: HashMap<String, Identity> = HashMap::new();
+  for identity in library::load_identities(&conn)? {
+    if let Some(appid) = identity.appid {
+      by_appid.entry(appid).or_insert_with(|| identity.id.clone());
+    }
+    let platform = identity.platform.clone().unwrap_or_else(|| "PC".into());
+    by_key
+      .entry((normalize_key(&identity.title), platform))
+      .or_insert_with(|| identity.id.clone());
+    identities.insert(identity.id.clone(), identity);
+  }
+  let mut owned: HashSet<(String, String)> = library::load_items(&conn)?
+    .into_iter()
+    .map(|i| (i.identity_id, i.member_id.unwrap_or_default()))
+    .collect();
+
+  let mut result = CsvImportResult {
+    identities_created: 0,
+    identities_matched: 0,
+    items_created: 0,
+    duplicates: 0,
+    invalid_rows: preview.error_count,
+    backup: Some(backup.file_name),
+  };
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  for row in rows {
+    let key = (normalize_key(&row.title), row.platform.clone());
+    let existing = row
+      .appid
+      .and_then(|a| by_appid.get(&a))
+      .or_else(|| by_key.get(&key))
+      .cloned();
+    let identity_id = match existing {
+      Some(id) => {
+        result.identities_matched += 1;
+        if let (Some(appid), Some(identity)) = (row.appid, identities.get_mut(&id)) {
+          if identity.appid.is_none() {
+            identity.appid = Some(appid);
+            library::put_identity(&tx, identity)?;
+            by_appid.insert(appid, id.clone());
+          }
+        }
+        id
+      }
+      None => {
+        let identity = Identity {
+          id: library::new_id("id"),
+          title: row.title.clone(),
+          platform: Some(row.platform.clone()),
+          appid: row.appid,
+          ..Default::default()
+        };
+        library::put_identity(&tx, &identity)?;
+        result.identities_created += 1;
+        if let Some(appid) = row.appid {
+          by_appid.insert(appid, identity.id.clone());
+        }
+        by_key.insert(key, identity.id.clone());
+        let id = identity.id.clone();
+        identities.insert(id.clone(), identity);
+        id
+      }
+    };
+
+    if !owned.insert((identity_id.clone(), row.member_id.clone())) {
+      result.duplicates += 1;
+      continue;
+    }
+    library::put_item(
+      &tx,
+      &LibraryItem {
+        id: library::new_id("item"),
+        identity_id,
+        account_id: row.account_id,
+        member_id: Some(row.member_id),
+        status: row.status,
+        price_try: row.price,
+        currency_code: row.currency_code,
+        acquired_at: row.acquired_at,
+        services: row.services,
+        oc_score: row.oc_score,
+        mc_score: row.mc_score,
+        ttb_median_main_h: row.ttb_hours,
+      },
+    )?;
+    result.items_created += 1;
+  }
+  tx.commit().map_err(|e| e.to_string())?;
+  Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  fn write_temp(bytes: &[u8]) -> String {
+    static SEQ: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+      "csv_import_test_{}_{}.csv",
+      std::process::id(),
+      SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&path, bytes).unwrap();
+    path.display().to_string()
+  }
+
+  fn scan_bytes(bytes: &[u8]) -> (CsvPreview, Vec<CsvRow>) {
+    let path = write_temp(bytes);
+    let mut rows = Vec::new();
+    let preview = scan(&path, None, |row| rows.push(row)).unwrap();
+    let _ = std::fs::remove_file(&path);
+    (preview, rows)
+  }
+
+  #[test]
+  fn parses_simple_csv() {
+    let (preview, rows) = scan_bytes(b"Title,Platform,Status\nHades,PC,Backlog\n");
+    assert_eq!(preview.delimiter, ",");
+    assert_eq!(preview.valid_rows, 1);
+    assert_eq!(rows[0].title, "Hades");
+    assert_eq!(rows[0].platform, "PC");
+    assert_eq!(rows[0].status, "Backlog");
+    assert_eq!(rows[0].member_id, DEFAULT_MEMBER);
+  }
+
+  #[test]
+  fn sniffs_delimiter() {
+    assert_eq!(sniff_delimiter("a;b;c\n1;2;3\n"), b';');
+    assert_eq!(sniff_delimiter("a\tb\n1\t2\n"), b'\t');
+    assert_eq!(sniff_delimiter("\"a;b\",c\n"), b',');
+    assert_eq!(sniff_delimiter("title\nHades\n"), b',');
+  }
+
+  #[test]
+  fn sniffs_bom_and_windows_1254() {
+    let (preview, rows) = scan_bytes("\u{feff}Title;Fiyat\nHades;59,99 TL\n".as_bytes());
+    assert!(preview.has_bom);
+    assert_eq!(preview.encoding, "UTF-8");
+    assert_eq!(preview.headers[0], "Title");
+    assert_eq!(rows[0].price, Some(59.99));
+    assert_eq!(rows[0].currency_code.as_deref(), Some("TRY"));
+
+    // "Oyun Adı;Durum\nŞehir;Bitti" encoded as Windows-1254.
+    let (encoded, _, _) = WINDOWS_1254.encode("Oyun Adı;Durum\nŞehir Oyunu;Bitti\n");
+    let (preview, rows) = scan_bytes(&encoded);
+    assert!(!preview.has_bom);
+    assert_eq!(preview.encoding, "windows-1254");
+    assert_eq!(preview.mapping.get("title").map(String::as_str), Some("Oyun Adı"));
+    assert_eq!(rows[0].title, "Şehir Oyunu");
+    assert_eq!(rows[0].status, "Beaten");
+  }
+
+  #[test]
+  fn maps_header_aliases() {
+    let headers: Vec<String> = ["Oyun Adı", "Price (TRY)", "OpenCritic", "Metascore", "Store Link"]
+      .iter()
+      .map(|h| h.to_string())
+      .collect();
+    let mapping = auto_mapping(&headers);
+    assert_eq!(mapping.get("title").map(String::as_str), Some("Oyun Adı"));
+    assert_eq!(mapping.get("priceTRY").map(String::as_str), Some("Price (TRY)"));
+    assert_eq!(mapping.get("ocScore").map(String::as_str), Some("OpenCritic"));
+    assert_eq!(mapping.get("mcScore").map(String::as_str), Some("Metascore"));
+    assert_eq!(mapping.get("url").map(String::as_str), Some("Store Link"));
+
+    let exported = auto_mapping(&["title".to_string(), "member".to_string(), "memberId".to_string()]);
+    assert_eq!(exported.get("memberId").map(String::as_str), Some("memberId"));
+
+    let played: Vec<String> = ["Name", "Store Link", "Hours Played", "Date Completed", "Status"]
+      .iter()
+      .map(|h| h.to_string())
+      .collect();
+    let mapping = auto_mapping(&played);
+    assert_eq!(mapping.get("url").map(String::as_str), Some("Store Link"));
+    assert!(!mapping.contains_key("accountId"));
+    assert!(!mapping.contains_key("ttbMedianMainH"));
+    assert!(!mapping.contains_key("acquiredAt"));
+
+    let generic = auto_mapping(&["Name".to_string(), "Score".to_string()]);
+    assert_eq!(generic.get("mcScore").map(String::as_str), Some("Score"));
+    assert!(!generic.contains_key("ocScore"));
+  }
+
+  #[test]
+  fn extracts_appid_from_urls() {
+    assert_eq!(steam_appid_in("https://store.steampowered.com/app/620/Portal_2/"), Some(620));
+    assert_eq!(steam_appid_in("steam://rungameid/1245620"), Some(1245620));
+    assert_eq!(steam_appid_in("https://steamcommunity.com/app/367520"), Some(367520));
+    assert_eq!(steam_appid_in("https://www.gog.com/game/hades"), None);
+
+    let (_, rows) = scan_bytes(b"Game\nPortal 2 - https://store.steampowered.com/app/620/Portal_2/\n");
+    assert_eq!(rows[0].title, "Portal 2");
+    assert_eq!(rows[0].appid, Some(620));
+    assert_eq!(rows[0].account_id.as_deref(), Some("Steam"));
+  }
+
+  #[test]
+  fn reports_errors_per_row() {
+    let (preview, rows) = scan_bytes(b"Title,Platform,Price,Date\nHades,PC,10,2023-01-05\n,PC,5,\nCeleste,Amiga,abc,someday\n");
+    assert_eq!(preview.total_rows, 3);
+    assert_eq!(preview.valid_rows, 1);
+    assert_eq!(preview.error_count, 2);
+    assert_eq!(rows[0].acquired_at.as_deref(), Some("2023-01-05"));
+    let fields: Vec<(u64, Option<&str>)> = preview.errors.iter().map(|e| (e.line, e.field.as_deref())).collect();
+    assert_eq!(
+      fields,
+      vec![(3, Some("title")), (4, Some("platform")), (4, Some("priceTRY")), (4, Some("acquiredAt"))]
+    );
+  }
+
+  #[test]
+  fn normalizes_statuses() {
+    for (raw, status) in [
+      ("Backlog", "Backlog"),
+      ("Not played", "Backlog"),
+      ("Unplayed", "Backlog"),
+      ("Plan to Play", "Backlog"),
+      ("Incomplete", "Backlog"),
+      ("Playing", "Playing"),
+      ("Played", "Playing"),
+      ("Completed", "Beaten"),
+      ("Finished", "Beaten"),
+      ("Dropped", "Abandoned"),
+      ("Wishlist", "Wishlist"),
+      ("Owned", "Owned"),
+      ("Oynanmadı", "Backlog"),
+      ("Bitti", "Beaten"),
+    ] {
+      assert_eq!(normalize_status(raw), Some(status), "{}", raw);
+    }
+    assert_eq!(normalize_status("???"), None);
+  }
+
+  #[test]
+  fn parses_numbers_and_dates() {
+    assert_eq!(parse_number("1.234,56"), Some(1234.56));
+    assert_eq!(parse_number("1,234.56"), Some(1234.56));
+    assert_eq!(parse_number("1.234.567"), Some(1234567.0));
+    assert_eq!(parse_number("1,234,567.5"), Some(1234567.5));
+    assert_eq!(parse_number("0.999"), Some(0.999));
+    assert_eq!(parse_number("12.500"), Some(12.5));
+    assert_eq!(parse_number("12,500"), Some(12.5));
+    assert_eq!(parse_number("59,99 TL"), Some(59.99));
+    assert_eq!(normalize_date("31.12.2023").as_deref(), Some("2023-12-31"));
+    assert_eq!(normalize_date("31/12/2023").as_deref(), Some("2023-12-31"));
+  }
+}
@@ -0,0 +1,622 @@
+use crate::commands::{now_unix, parse_timestamp, USER_AGENT};
+use crate::library::{self, LibraryItem};
+use crate::{offline, ratelimit};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+  collections::BTreeSet,
+  fs,
+  sync::OnceLock,
+};
+
+// ECB reference rates are quoted against the euro.
+const ECB_BASE: &str = "EUR";
+const DEFAULT_FX_RATES_URL: &str = "https://www.ecb.europa.eu/stats/eurofxref/eurofxref-hist.xml";
+// Rates are published on working days only; a rate older than this is no
+// longer the rate of the requested date and the price stays unconverted.
+const MAX_RATE_AGE_DAYS: i64 = 7;
+// Library prices without a currency code are Turkish lira (`priceTRY`).
+pub(crate) const DEFAULT_ITEM_CURRENCY: &str = "TRY";
+
+struct Rate {
+  date: String,
+  base: String,
+  quote: String,
+  rate: f64,
+}
+
+#[derive(Serialize)]
+pub struct FxImportSummary {
+  pub source: String,
+  pub rates: usize,
+  pub first_date: Option<String>,
+  pub last_date: Option<String>,
+  pub currencies: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct FxStatus {
+  pub rates: usize,
+  pub first_date: Option<String>,
+  pub last_date: Option<String>,
+  pub currencies: Vec<String>,
+  pub source_url: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FxConversion {
+  pub amount: f64,
+  pub from: String,
+  pub to: String,
+  /// Date the conversion was asked for.
+  pub date: String,
+  /// Date of the rate actually used: the latest on or before `date`, at most
+  /// `MAX_RATE_AGE_DAYS` earlier.
+  pub rate_date: String,
+  pub rate: f64,
+  pub converted: f64,
+}
+
+#[derive(Serialize)]
+pub struct ConvertedPrice {
+  pub item_id: String,
+  pub amount: f64,
+  pub currency: String,
+  pub acquired_at: Option<String>,
+  pub converted: Option<f64>,
+  pub rate_date: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ConvertedTotals {
+  pub currency: String,
+  pub total: f64,
+  pub converted_items: usize,
+  /// Priced items with no rate to the target near their acquisition date,
+  /// or with an unreadable currency code.
+  pub unconverted_items: Vec<String>,
+  pub items: Vec<ConvertedPrice>,
+}
+
+fn code(raw: &str) -> Option<String> {
+  let c = raw.trim().to_uppercase();
+  (c.len() == 3 && c.chars().all(|ch| ch.is_ascii_alphabetic())).then_some(c)
+}
+
+fn iso_date(raw: &str) -> Option<String> {
+  let ts = parse_timestamp(raw.trim())?;
+  chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.format("%Y-%m-%d").to_string())
+}
+
+fn today() -> String {
+  chrono::DateTime::from_timestamp(now_unix(), 0)
+    .map(|dt| dt.format("%Y-%m-%d").to_string())
+    .unwrap_or_default()
+}
+
+fn rate_value(v: &Value) -> Option<f64> {
+  let n = match v {
+    Value::Number(n) => n.as_f64()?,
+    Value::String(s) => parse_rate(s)?,
+    _ => return None,
+  };
+  (n.is_finite() && n > 0.0).then_some(n)
+}
+
+fn parse_rate(s: &str) -> Option<f64> {
+  let s = s.trim();
+  let s = if s.contains(',') && !s.contains('.') { s.replace(',', ".") } else { s.to_string() };
+  s.parse().ok().filter(|n: &f64| n.is_finite() && *n > 0.0)
+}
+
+fn push_rates(out: &mut Vec<Rate>, date: &str, base: &str, rates: &serde_json::Map<String, Value>) {
+  for (quote, v) in rates {
+    // A base quoted against itself carries no information; skip it.
+    if let (Some(quote), Some(rate)) = (code(quote).filter(|q| q != base), rate_value(v)) {
+      out.push(Rate {
+        date: date.to_string(),
+        base: base.to_string(),
+        quote,
+        rate,
+      });
+    }
+  }
+}
+
+/// `{"base","date","rates":{..}}` as served by ECB mirrors such as Frankfurter,
+/// the time-series variant with `rates` keyed by date, or an array of either.
+fn parse_json(v: &Value, out: &mut Vec<Rate>) -> Result<(), String> {
+  match v {
+    Value::Array(list) => list.iter().try_for_each(|e| parse_json(e, out)),
+    Value::Object(obj) => {
+      let rates = obj
+        .get("rates")
+        .and_then(Value::as_object)
+        .ok_or("Rate JSON has no \"rates\" object")?;
+      let base = obj
+        .get("base")
+        .and_then(Value::as_str)
+        .and_then(code)
+        .unwrap_or_else(|| ECB_BASE.into());
+      if rates.values().all(Value::is_object) {
+        for (date, day) in rates {
+          if let (Some(date), Some(day)) = (iso_date(date), day.as_object()) {
+            push_rates(out, &date, &base, day);
+          }
+        }
+      } else {
+        let date = obj
+          .get("date")
+          .and_then(Value::as_str)
+          .and_then(iso_date)
+          .unwrap_or_else(today);
+        push_rates(out, &date, &base, rates);
+      }
+      Ok(())
+    }
+    _ => Err("Unrecognized rate JSON".into()),
+  }
+}
+
+fn ecb_cube_regex() -> &'static Regex {
+  static RE: OnceLock<Regex> = OnceLock::new();
+  RE.get_or_init(|| {
+    Regex::new(r#"time=['"](\d{4}-\d{2}-\d{2})['"]|currency=['"]([A-Za-z]{3})['"]\s+rate=['"]([0-9.]+)['"]"#)
+      .expect("ecb regex")
+  })
+}
+
+/// ECB `eurofxref` XML: `<Cube time=..>` groups of `<Cube currency rate/>`.
+fn parse_ecb_xml(text: &str, out: &mut Vec<Rate>) {
+  let mut date = None;
+  for caps in ecb_cube_regex().captures_iter(text) {
+    if let Some(t) = caps.get(1) {
+      date = Some(t.as_str().to_string());
+    } else if let (Some(d), Some(quote), Some(rate)) =
+      (&date, caps.get(2), caps.get(3).and_then(|r| parse_rate(r.as_str())))
+    {
+      out.push(Rate {
+        date: d.clone(),
+        base: ECB_BASE.into(),
+        quote: quote.as_str().to_uppercase(),
+        rate,
+      });
+    }
+  }
+}
+
+/// Long CSV (`date,base,quote,rate`; `base` optional) or the wide ECB history
+/// layout (`Date,USD,JPY,...` against the euro).
+fn parse_csv(text: &str, out: &mut Vec<Rate>) -> Result<(), String> {
+  let first = text.lines().next().unwrap_or("");
+  let delimiter = [b',', b';', b'\t']
+    .into_iter()
+    .max_by_key(|d| first.bytes().filter(|b| b == d).count())
+    .unwrap_or(b',');
+  let mut reader = csv::ReaderBuilder::new()
+    .delimiter(delimiter)
+    .flexible(true)
+    .from_reader(text.as_bytes());
+  let headers: Vec<String> = reader
+    .headers()
+    .map_err(|e| e.to_string())?
+    .iter()
+    .map(|h| h.trim().trim_start_matches('\u{feff}').to_lowercase())
+    .collect();
+  let find = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+  let date_col = find(&["date", "time", "day"]).ok_or("Rate CSV needs a date column")?;
+  let base_col = find(&["base", "from"]);
+  let quote_col = find(&["quote", "currency", "to", "code"]);
+  let rate_col = find(&["rate", "value"]);
+
+  for record in reader.records() {
+    let record = record.map_err(|e| e.to_string())?;
+    let Some(date) = record.get(date_col).and_then(iso_date) else { continue };
+    let base = base_col
+      .and_then(|i| record.get(i))
+      .and_then(code)
+      .unwrap_or_else(|| ECB_BASE.into());
+    if let (Some(q), Some(r)) = (quote_col, rate_col) {
+      let quote = record.get(q).and_then(code).filter(|q| *q != base);
+      if let (Some(quote), Some(rate)) = (quote, record.get(r).and_then(parse_rate)) {
+        out.push(Rate { date, base, quote, rate });
+      }
+      continue;
+    }
+    for (i, header) in headers.iter().enumerate() {
+      if i == date_col || Some(i) == base_col {
+        continue;
+      }
+      // ECB history files use "N/A" for currencies not quoted that day.
+      if let (Some(quote), Some(rate)) = (code(header).filter(|q| *q != base), record.get(i).and_then(parse_rate)) {
+        out.push(Rate {
+          date: date.clone(),
+          base: base.clone(),
+          quote,
+          rate,
+        });
+      }
+    }
+  }
+  Ok(())
+}
+
+fn parse_rates(text: &str) -> Result<Vec<Rate>, String> {
+  let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+  let mut out = Vec::new();
+  match trimmed.chars().next() {
+    Some('<') => parse_ecb_xml(trimmed, &mut out),
+    Some('{') | Some('[') => {
+      let v: Value = serde_json::from_str(trimmed).map_err(|e| e.to_string())?;
+      parse_json(&v, &mut out)?;
+    }
+    Some(_) => parse_csv(trimmed, &mut out)?,
+    None => {}
+  }
+  if out.is_empty() {
+    return Err("No exchange rates found".into());
+  }
+  Ok(out)
+}
+
+fn store_rates(conn: &mut Connection, rates: &[Rate], source: &str) -> Result<FxImportSummary, String> {
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  for r in rates {
+    tx.execute(
+      "INSERT OR REPLACE INTO fx_rates (date, base, quote, rate, source) VALUES (?1, ?2, ?3, ?4, ?5)",
+      params![r.date, r.base, r.quote, r.rate, source],
+    )
+    .map_err(|e| e.to_string())?;
+  }
+  tx.commit().map_err(|e| e.to_string())?;
+  let currencies: BTreeSet<String> = rates
+    .iter()
+    .flat_map(|r| [r.base.clone(), r.quote.clone()])
+    .collect();
+  Ok(FxImportSummary {
+    source: source.to_string(),
+    rates: rates.len(),
+    first_date: rates.iter().map(|r| r.date.clone()).min(),
+    last_date: rates.iter().map(|r| r.date.clone()).max(),
+    currencies: currencies.into_iter().collect(),
+  })
+}
+
+fn rates_url() -> String {
+  std::env::var("FX_RATES_URL")
+    .ok()
+    .map(|v| v.trim().to_string())
+    .filter(|v| !v.is_empty())
+    .unwrap_or_else(|| DEFAULT_FX_RATES_URL.to_string())
+}
+
+/// `base`→`quote` rate in effect on `date`: the latest on or before it, no
+/// more than `MAX_RATE_AGE_DAYS` old. A later rate is never used for an
+/// earlier date.
+fn pair_rate(conn: &Connection, base: &str, quote: &str, date: &str) -> Result<Option<(f64, String)>, String> {
+  if base == quote {
+    return Ok(Some((1.0, date.to_string())));
+  }
+  let Some(ts) = parse_timestamp(date) else { return Ok(None) };
+  let oldest = chrono::DateTime::from_timestamp(ts - MAX_RATE_AGE_DAYS * 86_400, 0)
+    .map(|dt| dt.format("%Y-%m-%d").to_string())
+    .unwrap_or_default();
+  conn
+    .query_row(
+      "SELECT rate, date FROM fx_rates WHERE base = ?1 AND quote = ?2 AND date <= ?3 AND date >= ?4
+       ORDER BY date DESC LIMIT 1",
+      params![base, quote, date, oldest],
+      |r| Ok((r.get(0)?, r.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn days_apart(a: &str, b: &str) -> i64 {
+  let day = |s: &str| parse_timestamp(s).unwrap_or(0) / 86_400;
+  (day(a) - day(b)).abs()
+}
+
+/// Rate to turn one `from` into `to` on `date`, directly, inverted, or
+/// crossed through a shared base such as the euro. When several routes exist
+/// the one with the rate dated closest to `date` wins.
+pub(crate) fn rate_on(conn: &Connection, from: &str, to: &str, date: &str) -> Result<Option<(f64, String)>, String> {
+  if from == to {
+    return Ok(Some((1.0, date.to_string())));
+  }
+  let mut candidates = Vec::new();
+  if let Some(hit) = pair_rate(conn, from, to, date)? {
+    candidates.push(hit);
+  }
+  if let Some((r, d)) = pair_rate(conn, to, from, date)? {
+    candidates.push((1.0 / r, d));
+  }
+  let mut stmt = conn
+    .prepare("SELECT DISTINCT base FROM fx_rates")
+    .map_err(|e| e.to_string())?;
+  let bases: Vec<String> = stmt
+    .query_map([], |r| r.get(0))
+    .map_err(|e| e.to_string())?
+    .collect::<Result<_, _>>()
+    .map_err(|e| e.to_string())?;
+  for base in bases.iter().filter(|b| *b != from && *b != to) {
+    if let (Some((rf, df)), Some((rt, dt))) = (pair_rate(conn, base, from, date)?, pair_rate(conn, base, to, date)?) {
+      // A crossed rate is only as current as its older leg.
+      let d = if days_apart(&df, date) >= days_apart(&dt, date) { df } else { dt };
+      candidates.push((rt / rf, d));
+    }
+  }
+  Ok(candidates.into_iter().min_by_key(|(_, d)| days_apart(d, date)))
+}
+
+pub(crate) fn convert(conn: &Connection, amount: f64, from: &str, to: &str, date: Option<&str>) -> Result<Option<FxConversion>, String> {
+  let (Some(from), Some(to)) = (code(from), code(to)) else {
+    return Err(format!("Not a currency code: {} / {}", from, to));
+  };
+  let date = date.and_then(iso_date).unwrap_or_else(today);
+  Ok(rate_on(conn, &from, &to, &date)?.map(|(rate, rate_date)| FxConversion {
+    amount,
+    converted: (amount * rate * 100.0).round() / 100.0,
+    from,
+    to,
+    date,
+    rate_date,
+    rate,
+  }))
+}
+
+/// An item's price in `target`, at the rate of its acquisition date.
+pub(crate) fn convert_item(conn: &Connection, item: &LibraryItem, target: &str) -> Result<Option<FxConversion>, String> {
+  let Some(amount) = item.price_try else { return Ok(None) };
+  let from = item
+    .currency_code
+    .as_deref()
+    .filter(|c| !c.trim().is_empty())
+    .unwrap_or(DEFAULT_ITEM_CURRENCY);
+  convert(conn, amount, from, target, item.acquired_at.as_deref())
+}
+
+/// Loads rates from a local JSON, CSV or ECB XML file.
+#[tauri::command]
+pub fn fx_import_rates(path: String) -> Result<FxImportSummary, String> {
+  let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+  let rates = parse_rates(&text)?;
+  store_rates(&mut library::open_db()?, &rates, &path)
+}
+
+/// Fetches rates from `FX_RATES_URL` (the full ECB history by default, so old
+/// purchases have a rate; any endpoint answering in one of the import
+/// formats works).
+#[tauri::command]
+pub async fn fx_refresh_rates() -> Result<FxImportSummary, String> {
+  if offline::is_offline() {
+    return Err("Offline: exchange rates not refreshed".into());
+  }
+  let url = rates_url();
+  let client = reqwest::Client::builder()
+    .user_agent(USER_AGENT)
+    .build()
+    .map_err(|e| e.to_string())?;
+  ratelimit::acquire(&url).await;
+  let res = client.get(&url).send().await.map_err(|e| offline::note_error(&e))?;
+  offline::note_online();
+  if !res.status().is_success() {
+    return Err(format!("Exchange rates HTTP {}", res.status()));
+  }
+  let text = res.text().await.map_err(|e| e.to_string())?;
+  let rates = parse_rates(&text)?;
+  store_rates(&mut library::open_db()?, &rates, &url)
+}
+
+#[tauri::command]
+pub fn fx_status() -> Result<FxStatus, String> {
+  let conn = library::open_db()?;
+  let (rates, first_date, last_date): (usize, Option<String>, Option<String>) = conn
+    .query_row("SELECT COUNT(*), MIN(date), MAX(date) FROM fx_rates", [], |r| {
+      Ok((r.get::<_, i64>(0)? as usize, r.get(1)?, r.get(2)?))
+    })
+    .map_err(|e| e.to_string())?;
+  let mut stmt = conn
+    .prepare("SELECT base FROM fx_rates UNION SELECT quote FROM fx_rates ORDER BY 1")
+    .map_err(|e| e.to_string())?;
+  let currencies = stmt
+    .query_map([], |r| r.get(0))
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<String>, _>>()
+    .map_err(|e| e.to_string())?;
+  Ok(FxStatus {
+    rates,
+    first_date,
+    last_date,
+    currencies,
+    source_url: rates_url(),
+  })
+}
+
+#[tauri::command]
+pub fn fx_convert(amount: f64, from: String, to: String, date: Option<String>) -> Result<FxConversion, String> {
+  let conn = library::open_db()?;
+  convert(&conn, amount, &from, &to, date.as_deref())?
+    .ok_or_else(|| format!("No exchange rate between {} and {}", from, to))
+}
+
+/// Library prices converted to `currency` at each item's acquisition-date
+/// rate, so purchases made in different regions add up.
+#[tauri::command]
+pub fn library_convert_prices(currency: String, member_id: Option<String>) -> Result<ConvertedTotals, String> {
+  let target = code(&currency).ok_or_else(|| format!("Not a currency code: {}", currency))?;
+  let conn = library::open_db()?;
+  let mut items = Vec::new();
+  let mut unconverted = Vec::new();
+  let mut total = 0.0;
+  for item in library::load_items(&conn)? {
+    if item.status == "Wishlist" || (member_id.is_some() && item.member_id != member_id) {
+      continue;
+    }
+    let Some(amount) = item.price_try else { continue };
+    // A bad currency code on one item leaves that item unconverted.
+    let rate = convert_item(&conn, &item, &target).ok().flatten();
+    let converted = rate.as_ref().map(|r| r.converted);
+    match converted {
+      Some(c) => total += c,
+      None => unconverted.push(item.id.clone()),
+    }
+    items.push(ConvertedPrice {
+      item_id: item.id,
+      amount,
+      currency: item
+        .currency_code
+        .filter(|c| !c.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_ITEM_CURRENCY.into()),
+      acquired_at: item.acquired_at,
+      converted,
+      rate_date: rate.map(|r| r.rate_date),
+    });
+  }
+  Ok(ConvertedTotals {
+    currency: target,
+    total: (total * 100.0).round() / 100.0,
+    converted_items: items.len() - unconverted.len(),
+    unconverted_items: unconverted,
+    items,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn triples(rates: &[Rate]) -> Vec<(String, String, String, f64)> {
+    let mut out: Vec<_> = rates
+      .iter()
+      .map(|r| (r.date.clone(), r.base.clone(), r.quote.clone(), r.rate))
+      .collect();
+    out.sort_by(|a, b| (&a.0, &a.2).cmp(&(&b.0, &b.2)));
+    out
+  }
+
+  fn rate(date: &str, base: &str, quote: &str, rate: f64) -> (String, String, String, f64) {
+    (date.into(), base.into(), quote.into(), rate)
+  }
+
+  #[test]
+  fn parses_ecb_xml() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+  <Cube>
+    <Cube time="2024-03-08">
+      <Cube currency="USD" rate="1.0942"/>
+      <Cube currency="TRY" rate="34.8562"/>
+    </Cube>
+    <Cube time='2024-03-07'>
+      <Cube currency='USD' rate='1.0895'/>
+    </Cube>
+  </Cube>
+</gesmes:Envelope>"#;
+    assert_eq!(
+      triples(&parse_rates(xml).unwrap()),
+      vec![
+        rate("2024-03-07", "EUR", "USD", 1.0895),
+        rate("2024-03-08", "EUR", "TRY", 34.8562),
+        rate("2024-03-08", "EUR", "USD", 1.0942),
+      ]
+    );
+  }
+
+  #[test]
+  fn parses_json_shapes() {
+    let latest = r#"{"amount":1.0,"base":"USD","date":"2024-03-08","rates":{"EUR":0.9139,"TRY":"31,85"}}"#;
+    assert_eq!(
+      triples(&parse_rates(latest).unwrap()),
+      vec![rate("2024-03-08", "USD", "EUR", 0.9139), rate("2024-03-08", "USD", "TRY", 31.85)]
+    );
+
+    let series = r#"{"base":"EUR","rates":{"2024-03-07":{"USD":1.0895},"2024-03-08":{"USD":1.0942}}}"#;
+    assert_eq!(
+      triples(&parse_rates(series).unwrap()),
+      vec![rate("2024-03-07", "EUR", "USD", 1.0895), rate("2024-03-08", "EUR", "USD", 1.0942)]
+    );
+
+    let list = r#"[{"date":"2024-03-07","rates":{"GBP":0.8551}},{"base":"GBP","date":"2024-03-07","rates":{"E1R":1,"JPY":0}}]"#;
+    assert_eq!(triples(&parse_rates(list).unwrap()), vec![rate("2024-03-07", "EUR", "GBP", 0.8551)]);
+
+    assert!(parse_rates(r#"{"base":"EUR"}"#).is_err());
+  }
+
+  #[test]
+  fn parses_wide_csv() {
+    let csv = "\u{feff}Date,USD,JPY,TRY,\n2024-03-08,1.0942,160.89,N/A,\n2024-03-07,1.0895,161.57,34.7561,\n";
+    assert_eq!(
+      triples(&parse_rates(csv).unwrap()),
+      vec![
+        rate("2024-03-07", "EUR", "JPY", 161.57),
+        rate("2024-03-07", "EUR", "TRY", 34.7561),
+        rate("2024-03-07", "EUR", "USD", 1.0895),
+        rate("2024-03-08", "EUR", "JPY", 160.89),
+        rate("2024-03-08", "EUR", "USD", 1.0942),
+      ]
+    );
+  }
+
+  #[test]
+  fn parses_long_csv() {
+    let csv = "date;base;quote;rate\n2024-03-08;USD;TRY;31,85\n2024-03-08;;EUR;0.9139\n08.03.2024;USD;GBP;0.78\n";
+    assert_eq!(
+      triples(&parse_rates(csv).unwrap()),
+      vec![rate("2024-03-08", "USD", "TRY", 31.85)]
+    );
+    let json = r#"{"base":"EUR","date":"2024-03-08","rates":{"EUR":1,"USD":1.09}}"#;
+    assert_eq!(triples(&parse_rates(json).unwrap()), vec![rate("2024-03-08", "EUR", "USD", 1.09)]);
+    assert!(parse_rates("quote,rate\nUSD,1.1\n").is_err());
+  }
+
+  fn rates_db(rows: &[(&str, &str, &str, f64)]) -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn
+      .execute_batch(
+        "CREATE TABLE fx_rates (date TEXT NOT NULL, base TEXT NOT NULL, quote TEXT NOT NULL,
+           rate REAL NOT NULL, source TEXT, PRIMARY KEY (base, quote, date));",
+      )
+      .unwrap();
+    for (date, base, quote, rate) in rows {
+      conn
+        .execute(
+          "INSERT INTO fx_rates (date, base, quote, rate) VALUES (?1, ?2, ?3, ?4)",
+          params![date, base, quote, rate],
+        )
+        .unwrap();
+    }
+    conn
+  }
+
+  #[test]
+  fn only_uses_recent_rates_on_or_before_the_date() {
+    let conn = rates_db(&[
+      ("2024-03-08", "EUR", "USD", 1.1),
+      ("2024-03-08", "EUR", "TRY", 35.0),
+      ("2020-01-02", "EUR", "USD", 1.2),
+    ]);
+    // Over a weekend the Friday rate still applies, crossed through the euro.
+    let (rate, date) = rate_on(&conn, "USD", "TRY", "2024-03-10").unwrap().unwrap();
+    assert_eq!(date, "2024-03-08");
+    assert!((rate - 35.0 / 1.1).abs() < 1e-9);
+    // Neither a rate from years earlier nor one from after the date.
+    assert!(rate_on(&conn, "EUR", "USD", "2023-06-01").unwrap().is_none());
+    assert!(rate_on(&conn, "EUR", "USD", "2024-03-01").unwrap().is_none());
+    assert!(rate_on(&conn, "EUR", "USD", "2024-04-01").unwrap().is_none());
+  }
+
+  #[test]
+  fn bad_currency_code_is_an_error() {
+    let conn = rates_db(&[]);
+    let item = LibraryItem {
+      price_try: Some(10.0),
+      currency_code: Some("TL?".into()),
+      ..Default::default()
+    };
+    assert!(convert_item(&conn, &item, "EUR").is_err());
+    assert!(convert_item(&conn, &LibraryItem::default(), "EUR").unwrap().is_none());
+  }
+}
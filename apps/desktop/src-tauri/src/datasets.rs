@@ -0,0 +1,278 @@
+use crate::commands::{data_root, normalize_key};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  sync::{Mutex, OnceLock},
+  time::{Duration, Instant, SystemTime},
+};
+
+// The web app's `public/hookdata/` is bundled as the `hookdata/` resource. It
+// only commits the prebuilt `metacritic.index.json`; the raw CSV dumps are
+// optional there and can be imported into `<data_root>/datasets/`, which takes
+// precedence so users can refresh them without reinstalling.
+const HLTB_DATASET_FILE: &str = "hltb_data.csv";
+const METACRITIC_DATASET_FILE: &str = "games.csv";
+const METACRITIC_INDEX_FILE: &str = "metacritic.index.json";
+const RESOURCE_SUBDIR: &str = "hookdata";
+// How often a lookup re-checks whether the dataset file appeared or changed.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+static RESOURCE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Called from `setup` with the app's resource directory.
+pub fn set_resource_dir(dir: PathBuf) {
+  let _ = RESOURCE_DIR.set(dir.join(RESOURCE_SUBDIR));
+}
+
+fn user_dataset_dir() -> PathBuf {
+  let mut path = data_root();
+  path.push("datasets");
+  path
+}
+
+type Builder<T> = fn(&Path) -> HashMap<String, T>;
+
+/// The first of `names` found in the imported copies, else in the bundled
+/// resource, together with its position in `names`.
+fn dataset_path(names: &[&str]) -> Option<(PathBuf, usize)> {
+  std::iter::once(user_dataset_dir())
+    .chain(RESOURCE_DIR.get().cloned())
+    .flat_map(|dir| names.iter().enumerate().map(move |(i, name)| (dir.join(name), i)))
+    .find(|(p, _)| p.is_file())
+}
+
+fn read_rows(path: &Path) -> Vec<HashMap<String, String>> {
+  let mut reader = match csv::ReaderBuilder::new().flexible(true).from_path(path) {
+    Ok(r) => r,
+    Err(_) => return Vec::new(),
+  };
+  reader
+    .deserialize::<HashMap<String, String>>()
+    .filter_map(|row| row.ok())
+    .collect()
+}
+
+/// An index built from one dataset file, rebuilt when the file shows up,
+/// moves or changes instead of caching a miss for the life of the process.
+struct Loaded<T> {
+  source: Option<(PathBuf, Option<SystemTime>)>,
+  checked_at: Option<Instant>,
+  index: HashMap<String, T>,
+}
+
+type Index<T> = Mutex<Loaded<T>>;
+
+fn empty<T>() -> Index<T> {
+  Mutex::new(Loaded {
+    source: None,
+    checked_at: None,
+    index: HashMap::new(),
+  })
+}
+
+/// `sources` pairs each accepted file name with the builder for it, in order
+/// of preference.
+fn lookup<T: Copy>(cell: &Index<T>, sources: &[(&str, Builder<T>)], key: &str) -> Option<T> {
+  let mut loaded = cell.lock().ok()?;
+  if loaded.checked_at.is_none_or(|t| t.elapsed() >= RECHECK_INTERVAL) {
+    loaded.checked_at = Some(Instant::now());
+    let names: Vec<&str> = sources.iter().map(|(name, _)| *name).collect();
+    let found = dataset_path(&names);
+    let source = found.as_ref().map(|(p, _)| {
+      let mtime = fs::metadata(p).and_then(|m| m.modified()).ok();
+      (p.clone(), mtime)
+    });
+    if source != loaded.source {
+      loaded.index = found.map(|(p, i)| (sources[i].1)(&p)).unwrap_or_default();
+      loaded.source = source;
+    }
+  }
+  loaded.index.get(key).copied()
+}
+
+// normalized title -> (main hours, submission count); keeps the most-sampled row.
+fn build_hltb(path: &Path) -> HashMap<String, (f32, u32)> {
+  let mut index: HashMap<String, (f32, u32)> = HashMap::new();
+  for row in read_rows(path) {
+    let Some(title) = row.get("game_game_name").map(|s| s.trim()).filter(|s| !s.is_empty()) else {
+      continue;
+    };
+    let key = normalize_key(title);
+    if key.is_empty() {
+      continue;
+    }
+    let secs = row.get("game_comp_main").and_then(|v| v.trim().parse::<f32>().ok()).unwrap_or(0.0);
+    if secs <= 0.0 {
+      continue;
+    }
+    let count = row
+      .get("game_comp_main_count")
+      .and_then(|v| v.trim().parse::<u32>().ok())
+      .unwrap_or(0);
+    let hours = (secs / 3600.0 * 10.0).round() / 10.0;
+    let entry = index.entry(key).or_insert((hours, count));
+    if count > entry.1 {
+      *entry = (hours, count);
+    }
+  }
+  index
+}
+
+// normalized title -> best metascore across platforms.
+fn build_metacritic(path: &Path) -> HashMap<String, f32> {
+  let mut index: HashMap<String, f32> = HashMap::new();
+  for row in read_rows(path) {
+    let Some(title) = row.get("title").map(|s| s.trim()).filter(|s| !s.is_empty()) else {
+      continue;
+    };
+    let Some(score) = row.get("metascore").and_then(|v| v.trim().parse::<f32>().ok()) else {
+      continue;
+    };
+    let key = normalize_key(title);
+    if key.is_empty() {
+      continue;
+    }
+    let entry = index.entry(key).or_insert(score);
+    if score > *entry {
+      *entry = score;
+    }
+  }
+  index
+}
+
+#[derive(Deserialize)]
+struct MetacriticIndexFile {
+  index: HashMap<String, MetacriticIndexEntry>,
+}
+
+#[derive(Deserialize)]
+struct MetacriticIndexEntry {
+  score: f32,
+}
+
+// Same shape as `build_metacritic`, from the web app's prebuilt index. Its keys
+// are "<normalized title>|<platform>"; the title part is re-normalized since
+// the JS normalizer is not byte-for-byte ours.
+fn build_metacritic_index(path: &Path) -> HashMap<String, f32> {
+  let Some(file) = fs::read(path)
+    .ok()
+    .and_then(|bytes| serde_json::from_slice::<MetacriticIndexFile>(&bytes).ok())
+  else {
+    return HashMap::new();
+  };
+  let mut index: HashMap<String, f32> = HashMap::new();
+  for (key, entry) in file.index {
+    let title = key.rsplit_once('|').map_or(key.as_str(), |(title, _)| title);
+    let key = normalize_key(title);
+    if key.is_empty() {
+      continue;
+    }
+    let score = index.entry(key).or_insert(entry.score);
+    if entry.score > *score {
+      *score = entry.score;
+    }
+  }
+  index
+}
+
+const METACRITIC_SOURCES: [(&str, Builder<f32>); 2] = [
+  (METACRITIC_DATASET_FILE, build_metacritic),
+  (METACRITIC_INDEX_FILE, build_metacritic_index),
+];
+
+static HLTB_INDEX: Lazy<Index<(f32, u32)>> = Lazy::new(empty);
+static METACRITIC_INDEX: Lazy<Index<f32>> = Lazy::new(empty);
+
+/// Makes the next lookup re-resolve the dataset file.
+fn recheck<T>(cell: &Index<T>) {
+  if let Ok(mut loaded) = cell.lock() {
+    loaded.checked_at = None;
+  }
+}
+
+pub fn local_hltb_hours(key: &str) -> Option<f32> {
+  lookup(&HLTB_INDEX, &[(HLTB_DATASET_FILE, build_hltb)], key).map(|(hours, _)| hours)
+}
+
+pub fn local_metascore(key: &str) -> Option<f32> {
+  lookup(&METACRITIC_INDEX, &METACRITIC_SOURCES, key)
+}
+
+#[derive(Serialize)]
+pub struct DatasetInfo {
+  pub name: String,
+  pub path: Option<String>,
+  /// "imported" | "bundled" | "missing"
+  pub source: String,
+  pub size_bytes: u64,
+}
+
+fn info(name: &str, fallbacks: &[&str]) -> DatasetInfo {
+  let names: Vec<&str> = std::iter::once(name).chain(fallbacks.iter().copied()).collect();
+  let path = dataset_path(&names).map(|(p, _)| p);
+  let source = match &path {
+    Some(p) if p.starts_with(user_dataset_dir()) => "imported",
+    Some(_) => "bundled",
+    None => "missing",
+  };
+  DatasetInfo {
+    name: name.to_string(),
+    size_bytes: path.as_ref().and_then(|p| fs::metadata(p).ok()).map(|m| m.len()).unwrap_or(0),
+    path: path.map(|p| p.display().to_string()),
+    source: source.into(),
+  }
+}
+
+#[tauri::command]
+pub fn datasets_status() -> Vec<DatasetInfo> {
+  vec![
+    info(HLTB_DATASET_FILE, &[]),
+    info(METACRITIC_DATASET_FILE, &[METACRITIC_INDEX_FILE]),
+  ]
+}
+
+/// Copies an `hltb_data.csv` or Metacritic `games.csv` dump into the data
+/// directory, recognising which one it is by its header.
+#[tauri::command]
+pub fn datasets_import(path: String) -> Result<DatasetInfo, String> {
+  let mut reader = csv::ReaderBuilder::new()
+    .flexible(true)
+    .from_path(&path)
+    .map_err(|e| format!("{}: {}", path, e))?;
+  let headers = reader.headers().map_err(|e| e.to_string())?;
+  let has = |h: &str| headers.iter().any(|c| c.trim().trim_start_matches('\u{feff}') == h);
+  let name = if has("game_game_name") && has("game_comp_main") {
+    HLTB_DATASET_FILE
+  } else if has("title") && has("metascore") {
+    METACRITIC_DATASET_FILE
+  } else {
+    return Err(format!("{} is neither an HLTB nor a Metacritic dataset", path));
+  };
+  let dir = user_dataset_dir();
+  fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  let partial = dir.join(format!("{}.partial", name));
+  fs::copy(&path, &partial).map_err(|e| format!("{}: {}", path, e))?;
+  fs::rename(&partial, dir.join(name)).map_err(|e| e.to_string())?;
+  recheck(&HLTB_INDEX);
+  recheck(&METACRITIC_INDEX);
+  Ok(info(name, &[]))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn loads_bundled_metacritic_index() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+      .join("../../web/public/hookdata")
+      .join(METACRITIC_INDEX_FILE);
+    let index = build_metacritic_index(&path);
+    assert!(index.len() > 10_000, "only {} titles in {}", index.len(), path.display());
+    assert_eq!(index.get(&normalize_key("Chained Echoes")), Some(&91.0));
+    assert_eq!(index.get(&normalize_key("Rock Band 3")), Some(&93.0));
+  }
+}
@@ -0,0 +1,529 @@
+use crate::backup;
+use crate::commands::{jaccard_token_set, normalize_key, parse_timestamp};
+use crate::library::{self, Identity, LibraryItem};
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use strsim::jaro_winkler;
+
+const DEFAULT_THRESHOLD: f64 = 0.9;
+// Tokens shared by this many identities are too common to narrow the search.
+const MAX_BLOCK_SIZE: usize = 250;
+const ROMAN: [&str; 9] = ["ii", "iii", "iv", "v", "vi", "vii", "viii", "ix", "x"];
+
+#[derive(Serialize, Clone)]
+pub struct DuplicateCandidate {
+  pub id: String,
+  pub title: String,
+  pub platform: Option<String>,
+  pub appid: Option<u32>,
+  pub item_count: usize,
+  /// How many metadata fields are filled; the richest identity is proposed as the survivor.
+  pub richness: usize,
+}
+
+#[derive(Serialize)]
+pub struct MergeProposal {
+  pub keep_id: String,
+  pub identities: Vec<DuplicateCandidate>,
+  /// Weakest link that joined the cluster.
+  pub score: f64,
+  pub reasons: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct MergeResult {
+  pub kept: String,
+  pub removed: Vec<String>,
+  pub items_repointed: usize,
+  /// Library rows dropped because the member already had the game.
+  pub items_collapsed: usize,
+  pub fields_filled: usize,
+  pub backup: String,
+}
+
+struct Prepared<'a> {
+  identity: &'a Identity,
+  norm: String,
+  markers: BTreeSet<String>,
+}
+
+/// Comparison form of a title: `normalize_key` minus leading articles and
+/// stray separators, so "The Witcher 3: Wild Hunt – GOTY" and
+/// "Witcher 3 Wild Hunt" line up.
+fn comparable(title: &str) -> String {
+  normalize_key(title)
+    .split_whitespace()
+    .filter(|t| !matches!(*t, "-" | "&" | "|" | "/"))
+    .enumerate()
+    .filter(|(i, t)| !(*i == 0 && matches!(*t, "the" | "a" | "an")))
+    .map(|(_, t)| t)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Sequel numbers, roman numerals and release years. Titles whose markers
+/// differ ("Witcher 2" vs "Witcher 3", "Doom" vs "Doom (2016)") are never
+/// proposed, however similar the rest is.
+fn markers(title: &str, norm: &str) -> BTreeSet<String> {
+  let mut out: BTreeSet<String> = norm
+    .split_whitespace()
+    .filter(|t| t.chars().all(|c| c.is_ascii_digit()) || ROMAN.contains(t))
+    .map(str::to_string)
+    .collect();
+  for token in title.split(|c: char| !c.is_ascii_digit()) {
+    if token.len() == 4 && (token.starts_with("19") || token.starts_with("20")) {
+      out.insert(token.to_string());
+    }
+  }
+  out
+}
+
+fn richness(i: &Identity) -> usize {
+  [
+    i.platform.is_some(),
+    i.appid.is_some(),
+    i.igdb_cover_id.is_some(),
+    i.ttb_median_main_h.is_some(),
+    i.oc_score.is_some(),
+    i.mc_score.is_some(),
+    i.mc_user_score.is_some(),
+    i.mc_genres.as_ref().is_some_and(|g| !g.is_empty()),
+  ]
+  .iter()
+  .filter(|f| **f)
+  .count()
+}
+
+fn same_platform(a: &Identity, b: &Identity) -> bool {
+  match (a.platform.as_deref(), b.platform.as_deref()) {
+    (Some(x), Some(y)) => x.eq_ignore_ascii_case(y),
+    _ => true,
+  }
+}
+
+/// Pair score in 0..=1 with the reason, or None when the pair must not merge.
+fn score_pair(a: &Prepared, b: &Prepared) -> Option<(f64, String)> {
+  if !same_platform(a.identity, b.identity) {
+    return None;
+  }
+  match (a.identity.appid, b.identity.appid) {
+    (Some(x), Some(y)) if x == y => return Some((1.0, "same Steam appid".into())),
+    (Some(_), Some(_)) => return None,
+    _ => {}
+  }
+  if a.markers != b.markers || a.norm.is_empty() || b.norm.is_empty() {
+    return None;
+  }
+  if a.norm == b.norm {
+    return Some((0.99, "same normalized title".into()));
+  }
+  let jw = jaro_winkler(&a.norm, &b.norm);
+  let jaccard = jaccard_token_set(&a.norm, &b.norm);
+  let score = 0.6 * jw + 0.4 * jaccard;
+  Some((
+    score,
+    format!("similar titles (jaro-winkler {:.2}, token overlap {:.2})", jw, jaccard),
+  ))
+}
+
+/// Member indexes and the scored edges that joined them.
+type Cluster = (Vec<usize>, Vec<(f64, String)>);
+
+fn prepare(identity: &Identity) -> Prepared<'_> {
+  let norm = comparable(&identity.title);
+  Prepared {
+    markers: markers(&identity.title, &norm),
+    norm,
+    identity,
+  }
+}
+
+/// Groups identities whose pair score reaches `threshold`, with the edges that
+/// joined them. Edges are taken strongest first and two groups only join when
+/// every cross pair passes the `score_pair` guards, so an identity without an
+/// appid or platform can't chain two different games into one cluster.
+fn clusters(prepared: &[Prepared], threshold: f64) -> Vec<Cluster> {
+  // Only compare identities that share an appid or at least one title token.
+  let mut blocks: HashMap<String, Vec<usize>> = HashMap::new();
+  for (i, p) in prepared.iter().enumerate() {
+    if let Some(appid) = p.identity.appid {
+      blocks.entry(format!("#{}", appid)).or_default().push(i);
+    }
+    for token in p.norm.split_whitespace().collect::<HashSet<_>>() {
+      blocks.entry(token.to_string()).or_default().push(i);
+    }
+  }
+
+  let mut seen: HashSet<(usize, usize)> = HashSet::new();
+  let mut edges: Vec<(usize, usize, f64, String)> = Vec::new();
+  for members in blocks.values().filter(|m| m.len() > 1 && m.len() <= MAX_BLOCK_SIZE) {
+    for (x, &i) in members.iter().enumerate() {
+      for &j in &members[x + 1..] {
+        if !seen.insert((i.min(j), i.max(j))) {
+          continue;
+        }
+        if let Some((score, reason)) = score_pair(&prepared[i], &prepared[j]) {
+          if score >= threshold {
+            edges.push((i.min(j), i.max(j), score, reason));
+          }
+        }
+      }
+    }
+  }
+  edges.sort_by(|a, b| b.2.total_cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+
+  let mut group_of: Vec<usize> = (0..prepared.len()).collect();
+  let mut groups: HashMap<usize, Cluster> =
+    (0..prepared.len()).map(|i| (i, (vec![i], Vec::new()))).collect();
+  for (i, j, score, reason) in edges {
+    let (gi, gj) = (group_of[i], group_of[j]);
+    if gi != gj {
+      let compatible = groups[&gi]
+        .0
+        .iter()
+        .all(|&a| groups[&gj].0.iter().all(|&b| score_pair(&prepared[a], &prepared[b]).is_some()));
+      if !compatible {
+        continue;
+      }
+      let (members, joined) = groups.remove(&gj).unwrap_or_default();
+      for &m in &members {
+        group_of[m] = gi;
+      }
+      let group = groups.entry(gi).or_default();
+      group.0.extend(members);
+      group.1.extend(joined);
+    }
+    if let Some(group) = groups.get_mut(&gi) {
+      group.1.push((score, reason));
+    }
+  }
+  groups.into_values().filter(|(members, _)| members.len() > 1).collect()
+}
+
+/// Clusters identities that are probably the same game and proposes which one
+/// to keep. Nothing is changed until `library_merge_identities` is called.
+#[tauri::command]
+pub fn library_find_duplicates(threshold: Option<f64>) -> Result<Vec<MergeProposal>, String> {
+  let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD).clamp(0.5, 1.0);
+  let conn = library::open_db()?;
+  let identities = library::load_identities(&conn)?;
+  let mut item_counts: HashMap<String, usize> = HashMap::new();
+  for item in library::load_items(&conn)? {
+    *item_counts.entry(item.identity_id).or_default() += 1;
+  }
+
+  let prepared: Vec<Prepared> = identities.iter().map(prepare).collect();
+  let mut proposals: Vec<MergeProposal> = clusters(&prepared, threshold)
+    .into_iter()
+    .map(|(members, cluster_edges)| {
+      let score = cluster_edges.iter().map(|(s, _)| *s).fold(1.0, f64::min);
+      let reasons: BTreeSet<String> = cluster_edges.into_iter().map(|(_, r)| r).collect();
+      let mut candidates: Vec<DuplicateCandidate> = members
+        .iter()
+        .map(|&i| {
+          let identity = prepared[i].identity;
+          DuplicateCandidate {
+            id: identity.id.clone(),
+            title: identity.title.clone(),
+            platform: identity.platform.clone(),
+            appid: identity.appid,
+            item_count: item_counts.get(&identity.id).copied().unwrap_or(0),
+            richness: richness(identity),
+          }
+        })
+        .collect();
+      candidates.sort_by_key(|c| std::cmp::Reverse((c.richness, c.item_count)));
+      MergeProposal {
+        keep_id: candidates[0].id.clone(),
+        identities: candidates,
+        score: (score * 1000.0).round() / 1000.0,
+        reasons: reasons.into_iter().collect(),
+      }
+    })
+    .collect();
+  proposals.sort_by(|a, b| b.score.total_cmp(&a.score));
+  Ok(proposals)
+}
+
+/// Fills every empty field of `keep` from `other`. Returns how many were filled.
+fn fill<T: Clone>(slot: &mut Option<T>, from: &Option<T>) -> usize {
+  if slot.is_none() && from.is_some() {
+    *slot = from.clone();
+    1
+  } else {
+    0
+  }
+}
+
+fn absorb(keep: &mut Identity, other: &Identity) -> usize {
+  let mut filled = fill(&mut keep.platform, &other.platform)
+    + fill(&mut keep.appid, &other.appid)
+    + fill(&mut keep.igdb_cover_id, &other.igdb_cover_id)
+    + fill(&mut keep.ttb_source, &other.ttb_source)
+    + fill(&mut keep.ttb_median_main_h, &other.ttb_median_main_h)
+    + fill(&mut keep.oc_score, &other.oc_score)
+    + fill(&mut keep.mc_score, &other.mc_score)
+    + fill(&mut keep.mc_user_score, &other.mc_user_score)
+    + fill(&mut keep.critic_score_source, &other.critic_score_source);
+  if let Some(genres) = &other.mc_genres {
+    let merged = keep.mc_genres.get_or_insert_with(Vec::new);
+    for g in genres {
+      if !merged.iter().any(|m| m.eq_ignore_ascii_case(g)) {
+        merged.push(g.clone());
+        filled += 1;
+      }
+    }
+  }
+  filled
+}
+
+// How far along a status is; the furthest one survives a collapse.
+fn status_rank(status: &str) -> u8 {
+  match status {
+    "Wishlist" => 0,
+    "Owned" => 1,
+    "Backlog" => 2,
+    "Playing" => 3,
+    "Abandoned" => 4,
+    "Beaten" => 5,
+    _ => 0,
+  }
+}
+
+/// Folds rows where one member ends up owning the same game twice into one:
+/// the row with the furthest status (then the earliest acquisition) is kept
+/// and its empty fields are filled from the others. Rows on two different
+/// accounts are separate purchases and stay apart. Returns the surviving
+/// rows and the ids of the dropped ones.
+fn collapse_items(mut items: Vec<LibraryItem>) -> (Vec<LibraryItem>, Vec<String>) {
+  items.sort_by_key(|i| {
+    let acquired = i.acquired_at.as_deref().and_then(parse_timestamp).unwrap_or(i64::MAX);
+    (std::cmp::Reverse(status_rank(&i.status)), acquired)
+  });
+  let mut kept: Vec<LibraryItem> = Vec::new();
+  let mut dropped = Vec::new();
+  for item in items {
+    let same = kept.iter_mut().find(|k| {
+      k.member_id == item.member_id
+        && (k.account_id.is_none() || item.account_id.is_none() || k.account_id == item.account_id)
+    });
+    let Some(keep) = same else {
+      kept.push(item);
+      continue;
+    };
+    fill(&mut keep.account_id, &item.account_id);
+    // Price and currency only make sense together.
+    if keep.price_try.is_none() && item.price_try.is_some() {
+      keep.price_try = item.price_try;
+      keep.currency_code = item.currency_code.clone();
+    }
+    fill(&mut keep.acquired_at, &item.acquired_at);
+    fill(&mut keep.services, &item.services);
+    fill(&mut keep.oc_score, &item.oc_score);
+    fill(&mut keep.mc_score, &item.mc_score);
+    fill(&mut keep.ttb_median_main_h, &item.ttb_median_main_h);
+    dropped.push(item.id);
+  }
+  (kept, dropped)
+}
+
+/// Merges `merge_ids` into `keep_id`: library items and accounts are
+/// re-pointed, duplicate rows a member now has for the game are collapsed,
+/// empty metadata on the survivor is filled from the others, and the merged
+/// identities are deleted. A backup is taken first.
+#[tauri::command]
+pub fn library_merge_identities(keep_id: String, merge_ids: Vec<String>) -> Result<MergeResult, String> {
+  let merge_ids: Vec<String> = merge_ids.into_iter().filter(|id| *id != keep_id).collect();
+  if merge_ids.is_empty() {
+    return Err("Nothing to merge".into());
+  }
+  let mut conn = library::open_db()?;
+  let mut identities: HashMap<String, Identity> = library::load_identities(&conn)?
+    .into_iter()
+    .map(|i| (i.id.clone(), i))
+    .collect();
+  let mut keep = identities
+    .remove(&keep_id)
+    .ok_or_else(|| format!("Unknown identity: {}", keep_id))?;
+  let others: Vec<Identity> = merge_ids
+    .iter()
+    .map(|id| identities.remove(id).ok_or_else(|| format!("Unknown identity: {}", id)))
+    .collect::<Result<_, _>>()?;
+
+  let backup = backup::create_backup("pre_merge")?;
+  // Richest first, so its values win where several could fill the same field.
+  let mut ordered: Vec<&Identity> = others.iter().collect();
+  ordered.sort_by_key(|i| std::cmp::Reverse(richness(i)));
+  let fields_filled = ordered.iter().map(|other| absorb(&mut keep, other)).sum();
+
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  let mut items_repointed = 0;
+  for id in &merge_ids {
+    items_repointed += tx
+      .execute("UPDATE library SET identity_id = ?1 WHERE identity_id = ?2", params![keep_id, id])
+      .map_err(|e| e.to_string())?;
+    tx.execute("UPDATE accounts SET identity_id = ?1 WHERE identity_id = ?2", params![keep_id, id])
+      .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM identities WHERE id = ?1", [id])
+      .map_err(|e| e.to_string())?;
+  }
+  library::put_identity(&tx, &keep)?;
+  let rows: Vec<LibraryItem> = library::load_items(&tx)?
+    .into_iter()
+    .filter(|i| i.identity_id == keep_id)
+    .collect();
+  let (kept_rows, dropped) = collapse_items(rows);
+  for id in &dropped {
+    tx.execute("DELETE FROM library WHERE id = ?1", [id])
+      .map_err(|e| e.to_string())?;
+  }
+  for row in &kept_rows {
+    library::put_item(&tx, row)?;
+  }
+  tx.commit().map_err(|e| e.to_string())?;
+
+  Ok(MergeResult {
+    kept: keep_id,
+    removed: merge_ids,
+    items_repointed,
+    items_collapsed: dropped.len(),
+    fields_filled,
+    backup: backup.file_name,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn identity(id: &str, title: &str, platform: Option<&str>, appid: Option<u32>) -> Identity {
+    Identity {
+      id: id.into(),
+      title: title.into(),
+      platform: platform.map(str::to_string),
+      appid,
+      ..Default::default()
+    }
+  }
+
+  fn cluster_ids(identities: &[Identity]) -> Vec<Vec<String>> {
+    let prepared: Vec<Prepared> = identities.iter().map(prepare).collect();
+    let mut out: Vec<Vec<String>> = clusters(&prepared, DEFAULT_THRESHOLD)
+      .into_iter()
+      .map(|(members, _)| {
+        let mut ids: Vec<String> = members.iter().map(|&i| identities[i].id.clone()).collect();
+        ids.sort();
+        ids
+      })
+      .collect();
+    out.sort();
+    out
+  }
+
+  #[test]
+  fn same_title_and_platform_cluster() {
+    let list = [
+      identity("a", "Hades", Some("PC"), None),
+      identity("b", "Hades", Some("pc"), None),
+      identity("c", "Hades II", Some("PC"), None),
+    ];
+    assert_eq!(cluster_ids(&list), vec![vec!["a".to_string(), "b".to_string()]]);
+  }
+
+  #[test]
+  fn edition_suffix_still_matches() {
+    let list = [
+      identity("goty", "The Witcher 3: Wild Hunt – Game of the Year Edition", Some("PC"), None),
+      identity("base", "Witcher 3 Wild Hunt", Some("PC"), Some(292030)),
+    ];
+    assert_eq!(cluster_ids(&list), vec![vec!["base".to_string(), "goty".to_string()]]);
+  }
+
+  #[test]
+  fn sequels_and_platforms_stay_apart() {
+    let list = [
+      identity("w2", "The Witcher 2", Some("PC"), None),
+      identity("w3", "The Witcher 3", Some("PC"), None),
+      identity("doom", "Doom", Some("PC"), None),
+      identity("doom16", "Doom (2016)", Some("PC"), None),
+      identity("ps", "Celeste", Some("PlayStation"), None),
+      identity("sw", "Celeste", Some("Switch"), None),
+    ];
+    assert!(cluster_ids(&list).is_empty());
+  }
+
+  #[test]
+  fn no_chaining_through_an_identity_without_appid() {
+    let list = [
+      identity("a10", "DOOM Eternal", Some("PC"), Some(10)),
+      identity("bare", "Doom Eternal", None, None),
+      identity("a20", "DOOM Eternal", Some("PC"), Some(20)),
+    ];
+    let found = cluster_ids(&list);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].len(), 2);
+    assert!(found[0].contains(&"bare".to_string()));
+  }
+
+  #[test]
+  fn no_chaining_across_platforms() {
+    let list = [
+      identity("pc", "Hollow Knight", Some("PC"), None),
+      identity("any", "Hollow Knight", None, None),
+      identity("sw", "Hollow Knight", Some("Switch"), None),
+    ];
+    let found = cluster_ids(&list);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].len(), 2);
+  }
+
+  fn row(id: &str, member: &str, account: Option<&str>, status: &str, acquired: Option<&str>) -> LibraryItem {
+    LibraryItem {
+      id: id.into(),
+      identity_id: "keep".into(),
+      member_id: Some(member.into()),
+      account_id: account.map(Into::into),
+      status: status.into(),
+      acquired_at: acquired.map(Into::into),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn collapses_duplicate_rows_per_member() {
+    let priced = LibraryItem {
+      price_try: Some(12.5),
+      currency_code: Some("USD".into()),
+      ..row("csv", "ana", None, "Backlog", Some("2021-05-01T00:00:00Z"))
+    };
+    let items = vec![
+      priced,
+      row("steam", "ana", Some("acc-steam"), "Beaten", None),
+      row("wish", "ana", None, "Wishlist", Some("2019-01-01T00:00:00Z")),
+      row("gog", "ana", Some("acc-gog"), "Owned", Some("2022-01-01T00:00:00Z")),
+      row("other", "bo", None, "Backlog", None),
+    ];
+    let (kept, mut dropped) = collapse_items(items);
+    dropped.sort();
+    assert_eq!(dropped, vec!["csv", "wish"]);
+    let ids: Vec<&str> = kept.iter().map(|i| i.id.as_str()).collect();
+    assert_eq!(ids, vec!["steam", "other", "gog"]);
+    let steam = &kept[0];
+    assert_eq!(steam.status, "Beaten");
+    assert_eq!(steam.account_id.as_deref(), Some("acc-steam"));
+    assert_eq!((steam.price_try, steam.currency_code.as_deref()), (Some(12.5), Some("USD")));
+    assert_eq!(steam.acquired_at.as_deref(), Some("2021-05-01T00:00:00Z"));
+  }
+
+  #[test]
+  fn earliest_acquisition_breaks_status_ties() {
+    let items = vec![
+      row("late", "ana", None, "Backlog", Some("2023-01-01T00:00:00Z")),
+      row("early", "ana", None, "Backlog", Some("2020-01-01T00:00:00Z")),
+    ];
+    let (kept, dropped) = collapse_items(items);
+    assert_eq!(kept.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["early"]);
+    assert_eq!(dropped, vec!["late"]);
+  }
+}
@@ -0,0 +1,274 @@
+use crate::commands::{steam_region, CachedMetadata};
+use crate::currency::DEFAULT_ITEM_CURRENCY;
+use crate::library::{self, Identity};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::HashMap, fs, io::Write};
+
+// Column keys follow the `@tracker/core` field names. The importer reads
+// `memberId`/`accountId` back as ids, which is why the defaults export those;
+// `member`/`account` hold display names and are for reading only.
+// `priceTRY` and `pricePerHour` are in the item's own `currencyCode`, which is
+// always filled in (TRY when the item has none).
+const COLUMNS: [&str; 21] = [
+  "id",
+  "identityId",
+  "title",
+  "platform",
+  "appid",
+  "memberId",
+  "member",
+  "accountId",
+  "account",
+  "status",
+  "priceTRY",
+  "currencyCode",
+  "acquiredAt",
+  "services",
+  "ocScore",
+  "mcScore",
+  "ttbMedianMainH",
+  "ttbSource",
+  "steamPrice",
+  "steamCurrency",
+  "pricePerHour",
+];
+
+const DEFAULT_COLUMNS: [&str; 12] = [
+  "title",
+  "platform",
+  "memberId",
+  "accountId",
+  "status",
+  "priceTRY",
+  "currencyCode",
+  "acquiredAt",
+  "ocScore",
+  "ttbMedianMainH",
+  "steamPrice",
+  "pricePerHour",
+];
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ExportFilter {
+  pub members: Vec<String>,
+  pub statuses: Vec<String>,
+  pub platforms: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportSummary {
+  pub path: String,
+  pub format: String,
+  pub rows: usize,
+  pub columns: Vec<String>,
+}
+
+fn num(v: Option<f64>) -> Value {
+  v.and_then(|n| serde_json::Number::from_f64((n * 100.0).round() / 100.0))
+    .map(Value::Number)
+    .unwrap_or(Value::Null)
+}
+
+fn text(v: Option<&str>) -> Value {
+  v.map(|s| Value::String(s.to_string())).unwrap_or(Value::Null)
+}
+
+fn matches(filter: &[String], value: Option<&str>) -> bool {
+  filter.is_empty() || value.is_some_and(|v| filter.iter().any(|f| f.eq_ignore_ascii_case(v)))
+}
+
+/// Joins library items with their identity, member/account names and cached
+/// metadata. Item-level scores and times win over identity and cache values.
+fn build_rows(filter: &ExportFilter, cc: &str) -> Result<Vec<HashMap<&'static str, Value>>, String> {
+  let conn = library::open_db()?;
+  let identities: HashMap<String, Identity> = library::load_identities(&conn)?
+    .into_iter()
+    .map(|i| (i.id.clone(), i))
+    .collect();
+  let members: HashMap<String, String> = library::load_members(&conn)?
+    .into_iter()
+    .map(|m| (m.id, m.name))
+    .collect();
+  let accounts: HashMap<String, String> = library::load_accounts(&conn)?
+    .into_iter()
+    .map(|a| (a.id, a.label))
+    .collect();
+  let cache = CachedMetadata::load();
+
+  let mut rows = Vec::new();
+  for item in library::load_items(&conn)? {
+    let Some(identity) = identities.get(&item.identity_id) else { continue };
+    if !matches(&filter.members, item.member_id.as_deref())
+      || !matches(&filter.statuses, Some(item.status.as_str()))
+      || !matches(&filter.platforms, identity.platform.as_deref())
+    {
+      continue;
+    }
+    let ttb = item
+      .ttb_median_main_h
+      .or(identity.ttb_median_main_h)
+      .or_else(|| cache.hltb_hours(&identity.title).map(f64::from));
+    let oc = item
+      .oc_score
+      .or(identity.oc_score)
+      .or_else(|| cache.critic_score(&identity.title).map(f64::from));
+    let steam = identity.appid.and_then(|a| cache.steam_price(a, cc));
+    let per_hour = match (item.price_try, ttb) {
+      (Some(p), Some(h)) if h > 0.0 => Some((p / h * 100.0).round() / 100.0),
+      _ => None,
+    };
+    let currency = item
+      .currency_code
+      .as_deref()
+      .map(|c| c.trim().to_uppercase())
+      .filter(|c| !c.is_empty())
+      .unwrap_or_else(|| DEFAULT_ITEM_CURRENCY.into());
+
+    let mut row = HashMap::new();
+    row.insert("id", Value::String(item.id.clone()));
+    row.insert("identityId", Value::String(identity.id.clone()));
+    row.insert("title", Value::String(identity.title.clone()));
+    row.insert("platform", text(identity.platform.as_deref()));
+    row.insert("appid", identity.appid.map(Value::from).unwrap_or(Value::Null));
+    row.insert("memberId", text(item.member_id.as_deref()));
+    row.insert(
+      "member",
+      text(item.member_id.as_ref().map(|m| members.get(m).unwrap_or(m).as_str())),
+    );
+    row.insert("accountId", text(item.account_id.as_deref()));
+    row.insert(
+      "account",
+      text(item.account_id.as_ref().map(|a| accounts.get(a).unwrap_or(a).as_str())),
+    );
+    row.insert("status", Value::String(item.status.clone()));
+    row.insert("priceTRY", num(item.price_try));
+    row.insert("currencyCode", Value::String(currency));
+    row.insert("acquiredAt", text(item.acquired_at.as_deref()));
+    row.insert("services", text(item.services.as_ref().map(|s| s.join("; ")).as_deref()));
+    row.insert("ocScore", num(oc));
+    row.insert("mcScore", num(item.mc_score.or(identity.mc_score)));
+    row.insert("ttbMedianMainH", num(ttb));
+    row.insert("ttbSource", text(identity.ttb_source.as_deref()));
+    row.insert("steamPrice", num(steam.as_ref().map(|p| f64::from(p.price))));
+    row.insert("steamCurrency", text(steam.as_ref().map(|p| p.currency.as_str())));
+    row.insert("pricePerHour", num(per_hour));
+    rows.push(row);
+  }
+  rows.sort_by(|a, b| {
+    let key = |r: &HashMap<&str, Value>| r.get("title").and_then(|t| t.as_str()).unwrap_or("").to_lowercase();
+    key(a).cmp(&key(b))
+  });
+  Ok(rows)
+}
+
+fn cell(v: &Value) -> String {
+  match v {
+    Value::Null => String::new(),
+    Value::String(s) => s.clone(),
+    // Spreadsheets read "120" more happily than "120.0".
+    Value::Number(n) => match n.as_f64() {
+      Some(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", f as i64),
+      _ => n.to_string(),
+    },
+    other => other.to_string(),
+  }
+}
+
+fn write_delimited(path: &str, delimiter: u8, columns: &[&str], rows: &[HashMap<&str, Value>]) -> Result<(), String> {
+  let mut file = fs::File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+  // BOM so Excel opens UTF-8 (Turkish titles, member names) correctly.
+  file.write_all("\u{feff}".as_bytes()).map_err(|e| e.to_string())?;
+  let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(file);
+  writer.write_record(columns).map_err(|e| e.to_string())?;
+  for row in rows {
+    writer
+      .write_record(columns.iter().map(|c| row.get(c).map(cell).unwrap_or_default()))
+      .map_err(|e| e.to_string())?;
+  }
+  writer.flush().map_err(|e| e.to_string())
+}
+
+fn write_json(path: &str, columns: &[&str], rows: &[HashMap<&str, Value>]) -> Result<(), String> {
+  let out: Vec<Value> = rows
+    .iter()
+    .map(|row| {
+      let obj: Map<String, Value> = columns
+        .iter()
+        .map(|c| (c.to_string(), row.get(c).cloned().unwrap_or(Value::Null)))
+        .collect();
+      Value::Object(obj)
+    })
+    .collect();
+  let text = serde_json::to_string_pretty(&out).map_err(|e| e.to_string())?;
+  fs::write(path, text).map_err(|e| format!("{}: {}", path, e))
+}
+
+#[tauri::command]
+pub fn library_export_columns() -> Vec<&'static str> {
+  COLUMNS.to_vec()
+}
+
+/// Writes the library to `path` as "csv", "tsv" or "json" with the chosen
+/// columns (a sensible default set when omitted). Metadata comes from local
+/// caches only.
+#[tauri::command]
+pub fn library_export(
+  path: String,
+  format: String,
+  columns: Option<Vec<String>>,
+  filter: Option<ExportFilter>,
+  region: Option<String>,
+) -> Result<ExportSummary, String> {
+  let selected: Vec<&'static str> = match columns.filter(|c| !c.is_empty()) {
+    Some(requested) => requested
+      .iter()
+      .map(|c| {
+        COLUMNS
+          .iter()
+          .find(|k| k.eq_ignore_ascii_case(c))
+          .copied()
+          .ok_or_else(|| format!("Unknown export column: {}", c))
+      })
+      .collect::<Result<_, _>>()?,
+    None => DEFAULT_COLUMNS.to_vec(),
+  };
+  let cc = steam_region(region.as_deref());
+  let rows = build_rows(&filter.unwrap_or_default(), &cc)?;
+  let format = format.trim().to_lowercase();
+  match format.as_str() {
+    "csv" => write_delimited(&path, b',', &selected, &rows)?,
+    "tsv" => write_delimited(&path, b'\t', &selected, &rows)?,
+    "json" => write_json(&path, &selected, &rows)?,
+    other => return Err(format!("Unsupported export format: {}", other)),
+  }
+  Ok(ExportSummary {
+    path,
+    format,
+    rows: rows.len(),
+    columns: selected.iter().map(|c| c.to_string()).collect(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::csv_import::auto_mapping;
+
+  #[test]
+  fn exported_headers_map_back_onto_import_fields() {
+    for columns in [&COLUMNS[..], &DEFAULT_COLUMNS[..]] {
+      let headers: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+      let mapping = auto_mapping(&headers);
+      for (field, header) in &mapping {
+        assert_eq!(field, header, "{} read back from the wrong column", field);
+      }
+      for field in ["title", "status", "memberId", "accountId", "priceTRY", "currencyCode", "acquiredAt"] {
+        assert!(!columns.contains(&field) || mapping.contains_key(field), "{} not mapped", field);
+      }
+    }
+    let all: Vec<String> = COLUMNS.iter().map(|c| c.to_string()).collect();
+    assert_eq!(auto_mapping(&all).len(), 13);
+  }
+}
@@ -0,0 +1,499 @@
+use crate::commands::{now_unix, parse_timestamp, CachedMetadata};
+use crate::library::{self, Identity, LibraryItem};
+use crate::steam_local::{steam_local_playtime, LocalPlaytime};
+use crate::suggest::{hltb_hours, load_member_preferences};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, thread, time::Duration};
+
+const WEEK_SECS: i64 = 7 * 24 * 3600;
+const DEFAULT_HORIZON_WEEKS: f64 = 12.0;
+const PACE_LOOKBACK_WEEKS: i64 = 12;
+const PURCHASE_LOOKBACK_WEEKS: i64 = 52;
+// Deltas over less than this are too noisy to call a weekly pace.
+const MIN_PACE_SPAN_SECS: i64 = 3 * 24 * 3600;
+// Snapshots closer together than this add rows without adding information.
+const MIN_SNAPSHOT_INTERVAL_SECS: i64 = 6 * 3600;
+// A started game is never forecast as taking less than this.
+const MIN_REMAINING_HOURS: f64 = 0.5;
+// member id -> Steam install/account last used to record their playtime.
+const PLAYTIME_SOURCES_KEY: &str = "playtime_sources";
+const SCHEDULER_TICK: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PlaytimeSource {
+  steam_root: Option<String>,
+  steam_account: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PlannedPurchase {
+  pub title: String,
+  /// HLTB main-story hours; looked up in the HLTB cache when omitted.
+  pub hours: Option<f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ForecastRequest {
+  pub member_id: Option<String>,
+  /// "main" | "extras" | "completionist"; defaults to the member's preference.
+  pub hltb_style: Option<String>,
+  /// Skips the playtime history and uses this pace instead.
+  pub hours_per_week: Option<f64>,
+  pub steam_root: Option<String>,
+  pub steam_account: Option<String>,
+  pub horizon_weeks: Option<f64>,
+  /// Also count "Owned" games as backlog.
+  pub include_owned: bool,
+  pub planned_purchases: Vec<PlannedPurchase>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ForecastGame {
+  pub item_id: Option<String>,
+  pub title: String,
+  pub status: String,
+  pub remaining_hours: f64,
+  pub finish_week: Option<f64>,
+  pub finish_date: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BacklogForecast {
+  pub member_id: Option<String>,
+  pub hltb_style: String,
+  pub hours_per_week: Option<f64>,
+  /// "override" | "snapshots" | "steam_recent" | "none"
+  pub pace_source: String,
+  pub backlog_games: usize,
+  pub backlog_hours: f64,
+  /// Backlog games without an HLTB time; they are left out of the totals.
+  pub unknown_length: Vec<String>,
+  pub weeks_to_clear: Option<f64>,
+  pub clear_date: Option<String>,
+  /// HLTB hours added per week by purchases over the last year.
+  pub purchase_hours_per_week: f64,
+  /// Clear date if buying continues at that rate; None if it never clears.
+  pub clear_date_with_purchases: Option<String>,
+  pub planned_purchase_hours: f64,
+  /// Clear date once the planned purchases are added to the backlog.
+  pub clear_date_with_planned: Option<String>,
+  pub horizon_weeks: f64,
+  pub fits_in_horizon: Vec<ForecastGame>,
+  pub schedule: Vec<ForecastGame>,
+}
+
+fn round1(v: f64) -> f64 {
+  (v * 10.0).round() / 10.0
+}
+
+fn date_after_weeks(now: i64, weeks: f64) -> Option<String> {
+  let ts = now.checked_add((weeks * WEEK_SECS as f64) as i64)?;
+  chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.format("%Y-%m-%d").to_string())
+}
+
+fn weeks_for(hours: f64, pace: Option<f64>) -> Option<f64> {
+  pace.filter(|p| *p > 0.0).map(|p| hours / p)
+}
+
+/// Weeks to clear the backlog while purchases keep adding hours; None when
+/// buying keeps up with playing and it never clears.
+fn weeks_with_purchases(backlog_hours: f64, pace: Option<f64>, purchase_hours_per_week: f64) -> Option<f64> {
+  weeks_for(backlog_hours, pace.map(|p| p - purchase_hours_per_week))
+}
+
+/// Orders the backlog Playing-first, then shortest-first, and fills in when
+/// each game is done at `pace`. Returns the total remaining hours.
+fn schedule(games: &mut [ForecastGame], pace: Option<f64>, now: i64) -> f64 {
+  games.sort_by(|a, b| {
+    (b.status == "Playing")
+      .cmp(&(a.status == "Playing"))
+      .then(a.remaining_hours.total_cmp(&b.remaining_hours))
+  });
+  let mut cumulative = 0.0;
+  for game in games.iter_mut() {
+    cumulative += game.remaining_hours;
+    game.finish_week = weeks_for(cumulative, pace).map(round1);
+    game.finish_date = game.finish_week.and_then(|w| date_after_weeks(now, w));
+  }
+  cumulative
+}
+
+fn fits_in_horizon(games: &[ForecastGame], horizon_weeks: f64) -> Vec<ForecastGame> {
+  games
+    .iter()
+    .filter(|g| g.finish_week.is_some_and(|w| w <= horizon_weeks))
+    .cloned()
+    .collect()
+}
+
+fn load_local(req: &ForecastRequest) -> Option<Vec<LocalPlaytime>> {
+  if req.steam_root.is_none() && req.steam_account.is_none() {
+    return None;
+  }
+  steam_local_playtime(req.steam_root.clone(), req.steam_account.clone()).ok()
+}
+
+/// Stores the member's current Steam playtimes, unless the last snapshot is
+/// only a few hours old. Returns how many rows were written.
+pub(crate) fn record_snapshot(conn: &Connection, member_id: &str, rows: &[LocalPlaytime]) -> Result<usize, String> {
+  let now = now_unix();
+  let last: Option<i64> = conn
+    .query_row(
+      "SELECT MAX(taken_at) FROM playtime_snapshots WHERE member_id = ?1",
+      [member_id],
+      |r| r.get(0),
+    )
+    .map_err(|e| e.to_string())?;
+  if last.is_some_and(|t| now - t < MIN_SNAPSHOT_INTERVAL_SECS) {
+    return Ok(0);
+  }
+  let mut written = 0;
+  for row in rows {
+    let Some(minutes) = row.playtime_minutes else { continue };
+    written += conn
+      .execute(
+        "INSERT OR REPLACE INTO playtime_snapshots (member_id, appid, taken_at, minutes) VALUES (?1, ?2, ?3, ?4)",
+        params![member_id, row.appid, now, minutes],
+      )
+      .map_err(|e| e.to_string())?;
+  }
+  Ok(written)
+}
+
+fn playtime_sources(conn: &Connection) -> Result<HashMap<String, PlaytimeSource>, String> {
+  Ok(
+    library::get_setting(conn, PLAYTIME_SOURCES_KEY)?
+      .and_then(|v| serde_json::from_value(v).ok())
+      .unwrap_or_default(),
+  )
+}
+
+/// Remembers where a member's playtime came from so the scheduler can keep
+/// snapshotting it.
+fn remember_source(conn: &Connection, member_id: &str, source: PlaytimeSource) -> Result<(), String> {
+  let mut sources = playtime_sources(conn)?;
+  sources.insert(member_id.to_string(), source);
+  let value = serde_json::to_value(&sources).map_err(|e| e.to_string())?;
+  library::set_setting(conn, PLAYTIME_SOURCES_KEY, &value)
+}
+
+/// Snapshots every remembered member's Steam playtime once an hour;
+/// `record_snapshot` skips members whose last snapshot is still recent.
+pub fn start_scheduler() {
+  thread::spawn(|| loop {
+    if let Ok(conn) = library::open_db() {
+      for (member, source) in playtime_sources(&conn).unwrap_or_default() {
+        if let Ok(rows) = steam_local_playtime(source.steam_root, source.steam_account) {
+          let _ = record_snapshot(&conn, &member, &rows);
+        }
+      }
+    }
+    thread::sleep(SCHEDULER_TICK);
+  });
+}
+
+/// Hours per week from playtime growth across the recent snapshots. Growth
+/// is measured from the last snapshot before the window (or the first one in
+/// it, for a fresh history), so a game first seen mid-window counts from 0.
+fn snapshot_pace(conn: &Connection, member_id: &str, now: i64) -> Result<Option<f64>, String> {
+  let since = now - PACE_LOOKBACK_WEEKS * WEEK_SECS;
+  let (start, last): (Option<i64>, Option<i64>) = conn
+    .query_row(
+      "SELECT COALESCE(
+         (SELECT MAX(taken_at) FROM playtime_snapshots WHERE member_id = ?1 AND taken_at <= ?2),
+         (SELECT MIN(taken_at) FROM playtime_snapshots WHERE member_id = ?1 AND taken_at > ?2)
+       ), (SELECT MAX(taken_at) FROM playtime_snapshots WHERE member_id = ?1)",
+      params![member_id, since],
+      |r| Ok((r.get(0)?, r.get(1)?)),
+    )
+    .map_err(|e| e.to_string())?;
+  let (Some(start), Some(last)) = (start, last) else { return Ok(None) };
+  if last - start < MIN_PACE_SPAN_SECS {
+    return Ok(None);
+  }
+  let minutes: i64 = conn
+    .query_row(
+      "SELECT COALESCE(SUM(MAX(0, current - base)), 0) FROM (
+         SELECT MAX(a.minutes) AS current, COALESCE((
+           SELECT b.minutes FROM playtime_snapshots b
+           WHERE b.member_id = ?1 AND b.appid = a.appid AND b.taken_at <= ?2
+           ORDER BY b.taken_at DESC LIMIT 1
+         ), 0) AS base
+         FROM playtime_snapshots a
+         WHERE a.member_id = ?1 AND a.taken_at > ?2 GROUP BY a.appid
+       )",
+      params![member_id, start],
+      |r| r.get(0),
+    )
+    .map_err(|e| e.to_string())?;
+  Ok(Some(minutes as f64 / 60.0 / ((last - start) as f64 / WEEK_SECS as f64)))
+}
+
+fn is_backlog(item: &LibraryItem, include_owned: bool) -> bool {
+  match item.status.as_str() {
+    "Backlog" | "Playing" => true,
+    "Owned" => include_owned,
+    _ => false,
+  }
+}
+
+/// Records a playtime snapshot for a member without running a forecast. The
+/// Steam install/account is remembered and snapshotted hourly from then on
+/// by `start_scheduler`, so the pace history builds up in the background.
+#[tauri::command]
+pub fn library_record_playtime(
+  member_id: String,
+  steam_root: Option<String>,
+  steam_account: Option<String>,
+) -> Result<usize, String> {
+  let rows = steam_local_playtime(steam_root.clone(), steam_account.clone())?;
+  let conn = library::open_db()?;
+  remember_source(&conn, &member_id, PlaytimeSource { steam_root, steam_account })?;
+  record_snapshot(&conn, &member_id, &rows)
+}
+
+/// Forecasts when a member's backlog is cleared at their usual pace. Playing
+/// games come first, then the rest shortest-first, which is also the order
+/// `fits_in_horizon` is filled in.
+#[tauri::command]
+pub fn library_backlog_forecast(request: Option<ForecastRequest>) -> Result<BacklogForecast, String> {
+  let req = request.unwrap_or_default();
+  let conn = library::open_db()?;
+  let now = now_unix();
+  let prefs = match &req.member_id {
+    Some(m) => load_member_preferences(&conn, m)?,
+    None => Default::default(),
+  };
+  let style = req
+    .hltb_style
+    .clone()
+    .or(prefs.hltb_style)
+    .unwrap_or_else(|| "main".into());
+  let identities: HashMap<String, Identity> = library::load_identities(&conn)?
+    .into_iter()
+    .map(|i| (i.id.clone(), i))
+    .collect();
+  let items: Vec<LibraryItem> = library::load_items(&conn)?
+    .into_iter()
+    .filter(|i| req.member_id.is_none() || i.member_id == req.member_id)
+    .collect();
+  let cache = CachedMetadata::load();
+  let local = load_local(&req);
+  if let (Some(member), Some(rows)) = (&req.member_id, &local) {
+    let source = PlaytimeSource {
+      steam_root: req.steam_root.clone(),
+      steam_account: req.steam_account.clone(),
+    };
+    remember_source(&conn, member, source)?;
+    record_snapshot(&conn, member, rows)?;
+  }
+  let played: HashMap<u32, u32> = local
+    .iter()
+    .flatten()
+    .filter_map(|r| Some((r.appid, r.playtime_minutes?)))
+    .collect();
+
+  let (hours_per_week, pace_source) = if let Some(h) = req.hours_per_week.filter(|h| *h > 0.0) {
+    (Some(h), "override")
+  } else if let Some(h) = match &req.member_id {
+    Some(m) => snapshot_pace(&conn, m, now)?,
+    None => None,
+  } {
+    (Some(h), "snapshots")
+  } else if let Some(rows) = local.as_ref().filter(|r| !r.is_empty()) {
+    let minutes: u32 = rows.iter().filter_map(|r| r.playtime_2wks_minutes).sum();
+    (Some(minutes as f64 / 60.0 / 2.0), "steam_recent")
+  } else {
+    (None, "none")
+  };
+
+  let game_hours = |identity: &Identity, item: Option<&LibraryItem>| {
+    let times = cache.hltb_times(&identity.title);
+    item
+      .and_then(|i| i.ttb_median_main_h)
+      .or(identity.ttb_median_main_h)
+      .or(times.main.map(f64::from))
+      .filter(|h| *h > 0.0)
+      .map(|h| hltb_hours(h, &times, Some(&style)))
+  };
+
+  let mut games = Vec::new();
+  let mut unknown_length = Vec::new();
+  for item in items.iter().filter(|i| is_backlog(i, req.include_owned)) {
+    let Some(identity) = identities.get(&item.identity_id) else { continue };
+    let Some(total) = game_hours(identity, Some(item)) else {
+      unknown_length.push(identity.title.clone());
+      continue;
+    };
+    let done = identity
+      .appid
+      .and_then(|a| played.get(&a))
+      .map(|m| *m as f64 / 60.0)
+      .unwrap_or(0.0);
+    let remaining = if done > 0.0 {
+      (total - done).max(MIN_REMAINING_HOURS)
+    } else {
+      total
+    };
+    games.push(ForecastGame {
+      item_id: Some(item.id.clone()),
+      title: identity.title.clone(),
+      status: item.status.clone(),
+      remaining_hours: round1(remaining),
+      finish_week: None,
+      finish_date: None,
+    });
+  }
+  let horizon_weeks = req.horizon_weeks.filter(|w| *w > 0.0).unwrap_or(DEFAULT_HORIZON_WEEKS);
+  let backlog_hours = schedule(&mut games, hours_per_week, now);
+  let fits_in_horizon = fits_in_horizon(&games, horizon_weeks);
+
+  // Purchases over the last year, as HLTB hours added per week.
+  let since = now - PURCHASE_LOOKBACK_WEEKS * WEEK_SECS;
+  let purchased_hours: f64 = items
+    .iter()
+    .filter(|i| i.status != "Wishlist")
+    .filter(|i| i.acquired_at.as_deref().and_then(parse_timestamp).is_some_and(|t| t >= since))
+    .filter_map(|i| game_hours(identities.get(&i.identity_id)?, Some(i)))
+    .sum();
+  let purchase_hours_per_week = purchased_hours / PURCHASE_LOOKBACK_WEEKS as f64;
+
+  let planned_purchase_hours: f64 = req
+    .planned_purchases
+    .iter()
+    .filter_map(|p| {
+      let times = cache.hltb_times(&p.title);
+      p.hours
+        .or(times.main.map(f64::from))
+        .filter(|h| *h > 0.0)
+        .map(|h| hltb_hours(h, &times, Some(&style)))
+    })
+    .sum();
+
+  let weeks_to_clear = weeks_for(backlog_hours, hours_per_week);
+  Ok(BacklogForecast {
+    member_id: req.member_id.clone(),
+    hltb_style: style,
+    hours_per_week: hours_per_week.map(round1),
+    pace_source: pace_source.into(),
+    backlog_games: games.len(),
+    backlog_hours: round1(backlog_hours),
+    unknown_length,
+    weeks_to_clear: weeks_to_clear.map(round1),
+    clear_date: weeks_to_clear.and_then(|w| date_after_weeks(now, w)),
+    purchase_hours_per_week: round1(purchase_hours_per_week),
+    clear_date_with_purchases: weeks_with_purchases(backlog_hours, hours_per_week, purchase_hours_per_week)
+      .and_then(|w| date_after_weeks(now, w)),
+    planned_purchase_hours: round1(planned_purchase_hours),
+    clear_date_with_planned: weeks_for(backlog_hours + planned_purchase_hours, hours_per_week)
+      .and_then(|w| date_after_weeks(now, w)),
+    horizon_weeks,
+    fits_in_horizon,
+    schedule: games,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const NOW: i64 = 1_700_000_000;
+  const DAY: i64 = 24 * 3600;
+
+  fn snapshots_db(rows: &[(&str, u32, i64, i64)]) -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn
+      .execute_batch(
+        "CREATE TABLE playtime_snapshots (member_id TEXT NOT NULL, appid INTEGER NOT NULL,
+           taken_at INTEGER NOT NULL, minutes INTEGER NOT NULL, PRIMARY KEY (member_id, appid, taken_at));",
+      )
+      .unwrap();
+    for (member, appid, taken_at, minutes) in rows {
+      conn
+        .execute(
+          "INSERT INTO playtime_snapshots (member_id, appid, taken_at, minutes) VALUES (?1, ?2, ?3, ?4)",
+          params![member, appid, taken_at, minutes],
+        )
+        .unwrap();
+    }
+    conn
+  }
+
+  fn game(title: &str, status: &str, hours: f64) -> ForecastGame {
+    ForecastGame {
+      item_id: None,
+      title: title.into(),
+      status: status.into(),
+      remaining_hours: hours,
+      finish_week: None,
+      finish_date: None,
+    }
+  }
+
+  #[test]
+  fn snapshot_pace_measures_from_before_the_window() {
+    let before = NOW - PACE_LOOKBACK_WEEKS * WEEK_SECS - DAY;
+    let conn = snapshots_db(&[
+      ("m1", 10, before, 600),
+      ("m1", 10, NOW - 14 * DAY, 900),
+      // First seen mid-window: all of it is new playtime.
+      ("m1", 20, NOW - 14 * DAY, 120),
+      ("m1", 10, NOW, 1200),
+      ("m1", 20, NOW, 240),
+      ("m2", 10, NOW, 5000),
+    ]);
+    let weeks = (NOW - before) as f64 / WEEK_SECS as f64;
+    let pace = snapshot_pace(&conn, "m1", NOW).unwrap().unwrap();
+    assert!((pace - (600.0 + 240.0) / 60.0 / weeks).abs() < 1e-9, "{}", pace);
+    // One snapshot is no span at all.
+    assert_eq!(snapshot_pace(&conn, "m2", NOW).unwrap(), None);
+    assert_eq!(snapshot_pace(&conn, "nobody", NOW).unwrap(), None);
+  }
+
+  #[test]
+  fn snapshot_pace_starts_at_a_fresh_history() {
+    let conn = snapshots_db(&[
+      ("m1", 10, NOW - 7 * DAY, 6000),
+      ("m1", 10, NOW, 6300),
+      ("m1", 30, NOW, 60),
+    ]);
+    let pace = snapshot_pace(&conn, "m1", NOW).unwrap().unwrap();
+    assert!((pace - 6.0).abs() < 1e-9, "{}", pace);
+    let short = snapshots_db(&[("m1", 10, NOW - DAY, 60), ("m1", 10, NOW, 120)]);
+    assert_eq!(snapshot_pace(&short, "m1", NOW).unwrap(), None);
+  }
+
+  #[test]
+  fn schedules_playing_first_then_shortest() {
+    let mut games = vec![
+      game("Long", "Backlog", 40.0),
+      game("Short", "Backlog", 5.0),
+      game("Current", "Playing", 20.0),
+      game("Owned", "Owned", 10.0),
+    ];
+    let total = schedule(&mut games, Some(10.0), NOW);
+    assert_eq!(total, 75.0);
+    let order: Vec<(&str, Option<f64>)> = games.iter().map(|g| (g.title.as_str(), g.finish_week)).collect();
+    assert_eq!(
+      order,
+      vec![("Current", Some(2.0)), ("Short", Some(2.5)), ("Owned", Some(3.5)), ("Long", Some(7.5))]
+    );
+    assert_eq!(games[0].finish_date.as_deref(), date_after_weeks(NOW, 2.0).as_deref());
+
+    let fits: Vec<String> = fits_in_horizon(&games, 3.0).into_iter().map(|g| g.title).collect();
+    assert_eq!(fits, vec!["Current", "Short"]);
+
+    schedule(&mut games, None, NOW);
+    assert!(games.iter().all(|g| g.finish_week.is_none() && g.finish_date.is_none()));
+  }
+
+  #[test]
+  fn never_clears_when_purchases_keep_up() {
+    assert_eq!(weeks_with_purchases(100.0, Some(10.0), 5.0), Some(20.0));
+    assert_eq!(weeks_with_purchases(100.0, Some(10.0), 10.0), None);
+    assert_eq!(weeks_with_purchases(100.0, Some(10.0), 12.0), None);
+    assert_eq!(weeks_with_purchases(100.0, None, 0.0), None);
+  }
+}
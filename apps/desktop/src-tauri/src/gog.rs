@@ -0,0 +1,199 @@
+use crate::commands::parse_timestamp;
+use crate::steam::OwnedGame;
+use rusqlite::{Connection, OpenFlags};
+use std::{collections::HashMap, path::PathBuf};
+
+const DEFAULT_GALAXY_DB: &str = r"C:\ProgramData\GOG.com\Galaxy\storage\galaxy-2.0.db";
+
+fn open_galaxy_db(path: &str) -> Result<Connection, String> {
+  let path = PathBuf::from(path);
+  if !path.is_file() {
+    return Err(format!("GOG Galaxy database not found: {}", path.display()));
+  }
+  // Read-only so a running Galaxy client keeps its lock and data untouched.
+  Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+    .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn has_table(conn: &Connection, name: &str) -> bool {
+  conn
+    .query_row(
+      "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+      [name],
+      |_| Ok(()),
+    )
+    .is_ok()
+}
+
+/// Release keys the user owns on any platform Galaxy integrates with.
+fn owned_release_keys(conn: &Connection) -> Result<Vec<String>, String> {
+  let sql = if has_table(conn, "LibraryReleases") {
+    "SELECT DISTINCT releaseKey FROM LibraryReleases"
+  } else if has_table(conn, "ProductPurchaseDates") {
+    "SELECT DISTINCT gameReleaseKey FROM ProductPurchaseDates"
+  } else {
+    return Err("Unrecognised GOG Galaxy database: no LibraryReleases table".into());
+  };
+  let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+  let rows = stmt
+    .query_map([], |r| r.get::<_, String>(0))
+    .map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// `title` pieces win over `originalTitle`. Values are JSON like
+/// `{"title": "Cyberpunk 2077"}`.
+fn titles(conn: &Connection) -> Result<HashMap<String, String>, String> {
+  let mut stmt = conn
+    .prepare(
+      "SELECT gp.releaseKey, gpt.type, gp.value FROM GamePieces gp
+       JOIN GamePieceTypes gpt ON gpt.id = gp.gamePieceTypeId
+       WHERE gpt.type IN ('title', 'originalTitle')",
+    )
+    .map_err(|e| e.to_string())?;
+  let rows = stmt
+    .query_map([], |r| {
+      Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?, r.get::<_, Option<String>>(2)?))
+    })
+    .map_err(|e| e.to_string())?;
+  let mut out: HashMap<String, String> = HashMap::new();
+  for row in rows {
+    let (key, kind, value) = row.map_err(|e| e.to_string())?;
+    let title = value
+      .and_then(|v| serde_json::from_str::<serde_json::Value>(&v).ok())
+      .and_then(|v| v.get("title").and_then(|t| t.as_str()).map(str::to_string))
+      .filter(|t| !t.trim().is_empty());
+    if let Some(title) = title {
+      if kind == "title" || !out.contains_key(&key) {
+        out.insert(key, title);
+      }
+    }
+  }
+  Ok(out)
+}
+
+fn playtimes(conn: &Connection) -> HashMap<String, u32> {
+  let mut out = HashMap::new();
+  if !has_table(conn, "GameTimes") {
+    return out;
+  }
+  if let Ok(mut stmt) = conn.prepare("SELECT releaseKey, SUM(minutesInGame) FROM GameTimes GROUP BY releaseKey") {
+    if let Ok(rows) = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, Option<i64>>(1)?))) {
+      for (key, minutes) in rows.flatten() {
+        if let Some(m) = minutes.filter(|m| *m > 0) {
+          out.insert(key, m as u32);
+        }
+      }
+    }
+  }
+  out
+}
+
+fn last_played(conn: &Connection) -> HashMap<String, i64> {
+  let mut out = HashMap::new();
+  if !has_table(conn, "LastPlayedDates") {
+    return out;
+  }
+  if let Ok(mut stmt) = conn.prepare("SELECT gameReleaseKey, lastPlayedDate FROM LastPlayedDates") {
+    if let Ok(rows) = stmt.query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, Option<String>>(1)?))) {
+      for (key, date) in rows.flatten() {
+        if let Some(ts) = date.as_deref().and_then(parse_timestamp) {
+          out.insert(key, ts);
+        }
+      }
+    }
+  }
+  out
+}
+
+/// Reads owned games from a GOG Galaxy 2.0 `galaxy-2.0.db`, across every
+/// integrated platform, keyed by their Galaxy release key.
+#[tauri::command]
+pub fn gog_galaxy_import(db_path: Option<String>) -> Result<Vec<OwnedGame>, String> {
+  let path = db_path
+    .map(|p| p.trim().to_string())
+    .filter(|p| !p.is_empty())
+    .unwrap_or_else(|| DEFAULT_GALAXY_DB.to_string());
+  let conn = open_galaxy_db(&path)?;
+  let keys = owned_release_keys(&conn)?;
+  let titles = titles(&conn)?;
+  let times = playtimes(&conn);
+  let played = last_played(&conn);
+
+  let mut games: Vec<OwnedGame> = keys
+    .into_iter()
+    .filter_map(|key| {
+      let name = titles.get(&key)?.clone();
+      let (store, id) = key.split_once('_').unwrap_or(("generic", key.as_str()));
+      Some(OwnedGame {
+        store: store.to_string(),
+        external_id: id.to_string(),
+        appid: if store == "steam" { id.parse::<u32>().ok() } else { None },
+        name,
+        playtime_minutes: times.get(&key).copied(),
+        last_played: played.get(&key).copied(),
+        release_key: Some(key.clone()),
+      })
+    })
+    .collect();
+  games.sort_by_key(|g| g.name.to_lowercase());
+  Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Just the tables and columns the importer reads from a real galaxy-2.0.db.
+  const FIXTURE: &str = "
+    CREATE TABLE LibraryReleases (releaseKey TEXT, userId INTEGER);
+    CREATE TABLE GamePieceTypes (id INTEGER PRIMARY KEY, type TEXT);
+    CREATE TABLE GamePieces (releaseKey TEXT, gamePieceTypeId INTEGER, value TEXT);
+    CREATE TABLE GameTimes (releaseKey TEXT, minutesInGame INTEGER);
+    CREATE TABLE LastPlayedDates (gameReleaseKey TEXT, lastPlayedDate TEXT);
+    INSERT INTO LibraryReleases VALUES ('gog_1207658924', 1), ('steam_620', 1), ('epic_fn', 1), ('gog_404', 1);
+    INSERT INTO GamePieceTypes VALUES (1, 'title'), (2, 'originalTitle'), (3, 'meta');
+    INSERT INTO GamePieces VALUES
+      ('gog_1207658924', 2, '{\"title\":\"The Witcher: Enhanced Edition\"}'),
+      ('gog_1207658924', 1, '{\"title\":\"The Witcher\"}'),
+      ('steam_620', 2, '{\"title\":\"Portal 2\"}'),
+      ('epic_fn', 1, '{\"title\":\"  \"}'),
+      ('epic_fn', 3, '{\"title\":\"Not a title piece\"}');
+    INSERT INTO GameTimes VALUES ('steam_620', 90), ('steam_620', 30), ('gog_1207658924', 0);
+    INSERT INTO LastPlayedDates VALUES ('steam_620', '2023-04-05 18:30:00'), ('gog_1207658924', NULL);
+  ";
+
+  #[test]
+  fn imports_galaxy_fixture() {
+    let path = std::env::temp_dir().join(format!("gog_galaxy_test_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    Connection::open(&path).unwrap().execute_batch(FIXTURE).unwrap();
+    let games = gog_galaxy_import(Some(path.display().to_string()));
+    let _ = std::fs::remove_file(&path);
+    let games = games.unwrap();
+
+    // Releases without a usable title are skipped.
+    let names: Vec<&str> = games.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, vec!["Portal 2", "The Witcher"]);
+    let portal = &games[0];
+    assert_eq!((portal.store.as_str(), portal.external_id.as_str()), ("steam", "620"));
+    assert_eq!(portal.appid, Some(620));
+    assert_eq!(portal.playtime_minutes, Some(120));
+    assert_eq!(portal.last_played, parse_timestamp("2023-04-05 18:30:00"));
+    assert_eq!(portal.release_key.as_deref(), Some("steam_620"));
+    let witcher = &games[1];
+    assert_eq!((witcher.store.as_str(), witcher.appid), ("gog", None));
+    assert_eq!((witcher.playtime_minutes, witcher.last_played), (None, None));
+  }
+
+  #[test]
+  fn rejects_unknown_databases() {
+    assert!(gog_galaxy_import(Some("/definitely/not/galaxy-2.0.db".into())).is_err());
+    let path = std::env::temp_dir().join(format!("gog_galaxy_empty_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    Connection::open(&path).unwrap().execute_batch("CREATE TABLE Other (x INTEGER);").unwrap();
+    let result = gog_galaxy_import(Some(path.display().to_string()));
+    let _ = std::fs::remove_file(&path);
+    assert!(result.err().is_some_and(|e| e.contains("Unrecognised")));
+  }
+}
@@ -0,0 +1,309 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+};
+
+#[derive(Serialize, Clone)]
+pub struct LauncherGame {
+  /// "epic" | "gog" | "amazon"
+  pub store: String,
+  /// Launcher id (Legendary app name, GOG product id, Nile id).
+  pub app_name: String,
+  pub title: String,
+  pub installed: bool,
+  pub install_path: Option<String>,
+  pub platform: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HeroicLibrary {
+  pub config_dir: String,
+  pub games: Vec<LauncherGame>,
+}
+
+fn candidate_dirs() -> Vec<PathBuf> {
+  let mut dirs_out = Vec::new();
+  if let Some(config) = dirs::config_dir() {
+    dirs_out.push(config.join("heroic"));
+  }
+  if let Some(home) = dirs::home_dir() {
+    dirs_out.push(home.join(".config/heroic"));
+    dirs_out.push(home.join(".var/app/com.heroicgameslauncher.hgl/config/heroic"));
+  }
+  dirs_out
+}
+
+fn resolve_config_dir(config_dir: Option<&str>) -> Result<PathBuf, String> {
+  if let Some(dir) = config_dir.map(str::trim).filter(|d| !d.is_empty()) {
+    let path = PathBuf::from(dir);
+    if !path.is_dir() {
+      return Err(format!("Heroic config directory not found: {}", dir));
+    }
+    return Ok(path);
+  }
+  candidate_dirs()
+    .into_iter()
+    .find(|p| p.is_dir())
+    .ok_or_else(|| "Could not find a Heroic config directory; pass it explicitly".to_string())
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+  let text = fs::read_to_string(path).ok()?;
+  serde_json::from_str(&text).ok()
+}
+
+fn str_field<'a>(v: &'a Value, keys: &[&str]) -> Option<&'a str> {
+  keys
+    .iter()
+    .find_map(|k| v.get(*k).and_then(|s| s.as_str()))
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+}
+
+/// One entry from any of Heroic's library/store_cache files. They share the
+/// `app_name`/`title`/`is_installed`/`install` shape across runners.
+fn parse_entry(store: &str, v: &Value) -> Option<LauncherGame> {
+  if v.get("is_dlc").and_then(|d| d.as_bool()).unwrap_or(false) {
+    return None;
+  }
+  let app_name = str_field(v, &["app_name", "appName", "id"])?;
+  let title = str_field(v, &["title", "app_title"]).unwrap_or(app_name);
+  let install = v.get("install");
+  let install_path = install
+    .and_then(|i| str_field(i, &["install_path"]))
+    .map(str::to_string);
+  Some(LauncherGame {
+    store: store.to_string(),
+    app_name: app_name.to_string(),
+    title: title.to_string(),
+    installed: v.get("is_installed").and_then(|b| b.as_bool()).unwrap_or(false),
+    install_path,
+    platform: install.and_then(|i| str_field(i, &["platform"])).map(str::to_string),
+  })
+}
+
+fn array_at<'a>(doc: &'a Value, key: &str) -> &'a [Value] {
+  doc.get(key).and_then(|v| v.as_array()).map(Vec::as_slice).unwrap_or(&[])
+}
+
+type Library = BTreeMap<(String, String), LauncherGame>;
+
+fn add_owned(library: &mut Library, game: LauncherGame) {
+  library
+    .entry((game.store.clone(), game.app_name.clone()))
+    .and_modify(|existing| {
+      existing.installed |= game.installed;
+      if existing.install_path.is_none() {
+        existing.install_path = game.install_path.clone();
+      }
+      if existing.platform.is_none() {
+        existing.platform = game.platform.clone();
+      }
+    })
+    .or_insert(game);
+}
+
+fn mark_installed(library: &mut Library, store: &str, app_name: &str, title: Option<&str>, path: Option<&str>, platform: Option<&str>) {
+  let game = library
+    .entry((store.to_string(), app_name.to_string()))
+    .or_insert_with(|| LauncherGame {
+      store: store.to_string(),
+      app_name: app_name.to_string(),
+      title: title.unwrap_or(app_name).to_string(),
+      installed: false,
+      install_path: None,
+      platform: None,
+    });
+  game.installed = true;
+  if let Some(p) = path {
+    game.install_path = Some(p.to_string());
+  }
+  if let Some(p) = platform {
+    game.platform = Some(p.to_string());
+  }
+}
+
+fn read_epic(root: &Path, library: &mut Library) {
+  if let Some(doc) = read_json(&root.join("store_cache/legendary_library.json")) {
+    for game in array_at(&doc, "library").iter().filter_map(|v| parse_entry("epic", v)) {
+      add_owned(library, game);
+    }
+  }
+  // Legendary keeps one metadata file per owned app, which survives even
+  // when Heroic's own cache has been cleared.
+  let legendary = root.join("legendaryConfig/legendary");
+  if let Ok(entries) = fs::read_dir(legendary.join("metadata")) {
+    for entry in entries.flatten() {
+      let path = entry.path();
+      if path.extension().and_then(|e| e.to_str()) != Some("json") {
+        continue;
+      }
+      let Some(doc) = read_json(&path) else { continue };
+      let is_dlc = doc
+        .pointer("/metadata/mainGameItem")
+        .is_some_and(|v| !v.is_null());
+      if is_dlc {
+        continue;
+      }
+      if let Some(game) = parse_entry("epic", &doc) {
+        add_owned(library, game);
+      }
+    }
+  }
+  if let Some(Value::Object(installed)) = read_json(&legendary.join("installed.json")) {
+    for (app_name, v) in &installed {
+      mark_installed(
+        library,
+        "epic",
+        app_name,
+        str_field(v, &["title"]),
+        str_field(v, &["install_path"]),
+        str_field(v, &["platform"]),
+      );
+    }
+  }
+}
+
+fn read_gog(root: &Path, library: &mut Library) {
+  for file in ["store_cache/gog_library.json", "gog_store/library.json"] {
+    if let Some(doc) = read_json(&root.join(file)) {
+      for game in array_at(&doc, "games").iter().filter_map(|v| parse_entry("gog", v)) {
+        add_owned(library, game);
+      }
+    }
+  }
+  if let Some(doc) = read_json(&root.join("gog_store/installed.json")) {
+    for v in array_at(&doc, "installed") {
+      if let Some(app_name) = str_field(v, &["appName", "app_name"]) {
+        mark_installed(library, "gog", app_name, None, str_field(v, &["install_path"]), str_field(v, &["platform"]));
+      }
+    }
+  }
+}
+
+fn read_amazon(root: &Path, library: &mut Library) {
+  if let Some(doc) = read_json(&root.join("store_cache/nile_library.json")) {
+    for game in array_at(&doc, "library").iter().filter_map(|v| parse_entry("amazon", v)) {
+      add_owned(library, game);
+    }
+  }
+  if let Some(Value::Array(installed)) = read_json(&root.join("nile_config/nile/installed.json")) {
+    for v in &installed {
+      if let Some(id) = str_field(v, &["id"]) {
+        mark_installed(library, "amazon", id, None, str_field(v, &["path"]), None);
+      }
+    }
+  }
+}
+
+/// Lists Epic, GOG and Amazon games known to Heroic Games Launcher from its
+/// local config files; no account login is needed.
+#[tauri::command]
+pub fn heroic_library(config_dir: Option<String>) -> Result<HeroicLibrary, String> {
+  let root = resolve_config_dir(config_dir.as_deref())?;
+  let mut library = Library::new();
+  read_epic(&root, &mut library);
+  read_gog(&root, &mut library);
+  read_amazon(&root, &mut library);
+
+  let mut games: Vec<LauncherGame> = library.into_values().collect();
+  games.sort_by_key(|g| g.title.to_lowercase());
+  Ok(HeroicLibrary {
+    config_dir: root.display().to_string(),
+    games,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn write(root: &Path, rel: &str, text: &str) {
+    let path = root.join(rel);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, text).unwrap();
+  }
+
+  // A trimmed-down ~/.config/heroic with one file of each kind the importer reads.
+  fn fixture(root: &Path) {
+    write(
+      root,
+      "store_cache/legendary_library.json",
+      r#"{"library": [
+        {"app_name": "Fortnite", "title": "Fortnite", "is_installed": false},
+        {"app_name": "HadesDLC", "title": "Hades Soundtrack", "is_dlc": true}
+      ]}"#,
+    );
+    write(
+      root,
+      "legendaryConfig/legendary/metadata/Sugar.json",
+      r#"{"app_name": "Sugar", "app_title": "Hades", "metadata": {"mainGameItem": null}}"#,
+    );
+    write(
+      root,
+      "legendaryConfig/legendary/metadata/SugarDLC.json",
+      r#"{"app_name": "SugarDLC", "app_title": "Hades Extra", "metadata": {"mainGameItem": {"id": "x"}}}"#,
+    );
+    write(
+      root,
+      "legendaryConfig/legendary/installed.json",
+      r#"{"Sugar": {"title": "Hades", "install_path": "/games/Hades", "platform": "Windows"}}"#,
+    );
+    write(
+      root,
+      "store_cache/gog_library.json",
+      r#"{"games": [{"app_name": "1207658924", "title": "The Witcher", "install": {"platform": "windows"}}]}"#,
+    );
+    write(
+      root,
+      "gog_store/installed.json",
+      r#"{"installed": [{"appName": "1207658924", "install_path": "/games/Witcher", "platform": "linux"}]}"#,
+    );
+    write(
+      root,
+      "store_cache/nile_library.json",
+      r#"{"library": [{"app_name": "amzn1.adg.product.1", "title": "Amazon Game"}]}"#,
+    );
+    write(root, "nile_config/nile/installed.json", r#"[{"id": "amzn1.adg.product.2", "path": "/games/Other"}]"#);
+  }
+
+  #[test]
+  fn reads_heroic_fixture() {
+    let root = std::env::temp_dir().join(format!("heroic_test_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fixture(&root);
+    let result = heroic_library(Some(root.display().to_string()));
+    let _ = fs::remove_dir_all(&root);
+    let games = result.unwrap().games;
+
+    let summary: Vec<(&str, &str, &str, bool)> = games
+      .iter()
+      .map(|g| (g.store.as_str(), g.app_name.as_str(), g.title.as_str(), g.installed))
+      .collect();
+    assert_eq!(
+      summary,
+      vec![
+        ("amazon", "amzn1.adg.product.1", "Amazon Game", false),
+        // Installed but missing from the library cache: listed under its id.
+        ("amazon", "amzn1.adg.product.2", "amzn1.adg.product.2", true),
+        ("epic", "Fortnite", "Fortnite", false),
+        ("epic", "Sugar", "Hades", true),
+        ("gog", "1207658924", "The Witcher", true),
+      ]
+    );
+    let hades = &games[3];
+    assert_eq!(hades.install_path.as_deref(), Some("/games/Hades"));
+    assert_eq!(hades.platform.as_deref(), Some("Windows"));
+    let witcher = &games[4];
+    assert_eq!(witcher.install_path.as_deref(), Some("/games/Witcher"));
+    assert_eq!(witcher.platform.as_deref(), Some("linux"));
+  }
+
+  #[test]
+  fn missing_config_dir_is_an_error() {
+    assert!(heroic_library(Some("/definitely/not/heroic".into())).is_err());
+  }
+}
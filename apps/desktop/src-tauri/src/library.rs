@@ -0,0 +1,783 @@
+use crate::backup;
+use crate::commands::{data_root, now_unix};
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+  fs,
+  path::PathBuf,
+  sync::atomic::{AtomicU32, Ordering},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const LIBRARY_DB_FILE: &str = "library.db";
+const DEXIE_IMPORTED_KEY: &str = "dexie_imported_at";
+
+pub(crate) const STATUSES: [&str; 6] = ["Backlog", "Playing", "Beaten", "Abandoned", "Wishlist", "Owned"];
+
+// Schema migrations, applied in order; `PRAGMA user_version` records how many
+// have run. Append new steps, never edit shipped ones.
+const MIGRATIONS: &[&str] = &[
+  // v1: mirrors the Dexie v8 schema.
+  "CREATE TABLE identities (
+     id TEXT PRIMARY KEY,
+     title TEXT NOT NULL,
+     platform TEXT,
+     appid INTEGER,
+     igdb_cover_id TEXT,
+     ttb_source TEXT,
+     ttb_median_main_h REAL,
+     oc_score REAL,
+     mc_score REAL,
+     mc_user_score REAL,
+     mc_genres TEXT,
+     critic_score_source TEXT
+   );
+   CREATE INDEX identities_appid ON identities(appid);
+   CREATE TABLE accounts (
+     id TEXT PRIMARY KEY,
+     platform TEXT NOT NULL,
+     label TEXT NOT NULL,
+     identity_id TEXT
+   );
+   CREATE TABLE members (
+     id TEXT PRIMARY KEY,
+     name TEXT NOT NULL
+   );
+   CREATE TABLE library (
+     id TEXT PRIMARY KEY,
+     identity_id TEXT NOT NULL,
+     account_id TEXT,
+     member_id TEXT,
+     status TEXT NOT NULL,
+     price_try REAL,
+     currency_code TEXT,
+     acquired_at TEXT,
+     services TEXT,
+     oc_score REAL,
+     mc_score REAL,
+     ttb_median_main_h REAL
+   );
+   CREATE INDEX library_identity ON library(identity_id);
+   CREATE INDEX library_member ON library(member_id);
+   CREATE INDEX library_status ON library(status);
+   CREATE TABLE settings (
+     key TEXT PRIMARY KEY,
+     value TEXT NOT NULL
+   );
+   CREATE TABLE rawg_games (
+     id INTEGER PRIMARY KEY,
+     slug TEXT,
+     title_key TEXT,
+     data TEXT NOT NULL
+   );
+   CREATE INDEX rawg_games_title_key ON rawg_games(title_key);
+   CREATE TABLE meta (
+     key TEXT PRIMARY KEY,
+     value TEXT NOT NULL
+   );",
+  // v2: Steam playtime over time, for hours-per-week estimates.
+  "CREATE TABLE playtime_snapshots (
+     member_id TEXT NOT NULL,
+     appid INTEGER NOT NULL,
+     taken_at INTEGER NOT NULL,
+     minutes INTEGER NOT NULL,
+     PRIMARY KEY (member_id, appid, taken_at)
+   );
+   CREATE INDEX playtime_snapshots_taken ON playtime_snapshots(member_id, taken_at);",
+  // v3: dated exchange rates; 1 `base` = `rate` `quote`.
+  "CREATE TABLE fx_rates (
+     date TEXT NOT NULL,
+     base TEXT NOT NULL,
+     quote TEXT NOT NULL,
+     rate REAL NOT NULL,
+     source TEXT,
+     PRIMARY KEY (base, quote, date)
+   );",
+  // v4: every observed Steam price, plus user target-price alerts.
+  "CREATE TABLE price_history (
+     appid INTEGER NOT NULL,
+     region TEXT NOT NULL,
+     observed_at INTEGER NOT NULL,
+     price REAL NOT NULL,
+     initial_price REAL,
+     discount_percent INTEGER,
+     currency TEXT NOT NULL,
+     PRIMARY KEY (appid, region, observed_at)
+   );
+   CREATE TABLE price_alerts (
+     id TEXT PRIMARY KEY,
+     appid INTEGER NOT NULL,
+     region TEXT NOT NULL,
+     target_price REAL NOT NULL,
+     currency TEXT,
+     created_at INTEGER NOT NULL,
+     triggered_at INTEGER,
+     triggered_price REAL
+   );
+   CREATE INDEX price_alerts_appid ON price_alerts(appid, region);",
+];
+
+// These mirror the TypeScript types in `@tracker/core`, so they use the same
+// camelCase field names and can be passed straight through from the web app.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Identity {
+  pub id: String,
+  pub title: String,
+  pub platform: Option<String>,
+  pub appid: Option<u32>,
+  pub igdb_cover_id: Option<String>,
+  pub ttb_source: Option<String>,
+  pub ttb_median_main_h: Option<f64>,
+  pub oc_score: Option<f64>,
+  pub mc_score: Option<f64>,
+  pub mc_user_score: Option<f64>,
+  pub mc_genres: Option<Vec<String>>,
+  pub critic_score_source: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LibraryItem {
+  pub id: String,
+  pub identity_id: String,
+  pub account_id: Option<String>,
+  pub member_id: Option<String>,
+  pub status: String,
+  #[serde(rename = "priceTRY")]
+  pub price_try: Option<f64>,
+  pub currency_code: Option<String>,
+  pub acquired_at: Option<String>,
+  pub services: Option<Vec<String>>,
+  pub oc_score: Option<f64>,
+  pub mc_score: Option<f64>,
+  pub ttb_median_main_h: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Account {
+  pub id: String,
+  pub platform: String,
+  pub label: String,
+  pub identity_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Member {
+  pub id: String,
+  pub name: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct DexieImportSummary {
+  pub identities: usize,
+  pub library: usize,
+  pub accounts: usize,
+  pub members: usize,
+  pub settings: usize,
+  pub rawg_games: usize,
+  pub skipped: usize,
+  pub imported_at: i64,
+}
+
+fn db_path() -> PathBuf {
+  let mut path = data_root();
+  path.push(LIBRARY_DB_FILE);
+  path
+}
+
+fn user_version(conn: &Connection) -> Result<usize, String> {
+  let version = conn
+    .query_row("PRAGMA user_version", [], |r| r.get::<_, i64>(0))
+    .map_err(|e| e.to_string())? as usize;
+  if version > MIGRATIONS.len() {
+    return Err(format!(
+      "library.db is at schema v{} but this build only knows v{}",
+      version,
+      MIGRATIONS.len()
+    ));
+  }
+  Ok(version)
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), String> {
+  if user_version(conn)? == MIGRATIONS.len() {
+    return Ok(());
+  }
+  // Take the write lock before deciding what to run: the schedulers open the
+  // database at startup too, and whoever loses the race must see the winner's
+  // version rather than re-running (and re-backing-up) the same migrations.
+  let tx = conn
+    .transaction_with_behavior(TransactionBehavior::Immediate)
+    .map_err(|e| e.to_string())?;
+  let version = user_version(&tx)?;
+  if version == MIGRATIONS.len() {
+    return Ok(());
+  }
+  if version > 0 {
+    backup::create_backup("pre_migration")?;
+  }
+  apply_migrations(&tx, version)?;
+  tx.commit().map_err(|e| e.to_string())
+}
+
+fn apply_migrations(tx: &Transaction, from: usize) -> Result<(), String> {
+  for (i, sql) in MIGRATIONS.iter().enumerate().skip(from) {
+    tx.execute_batch(sql).map_err(|e| format!("library migration v{}: {}", i + 1, e))?;
+    tx.pragma_update(None, "user_version", (i + 1) as i64)
+      .map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+/// Opens `library.db`, creating it and running pending migrations as needed.
+pub(crate) fn open_db() -> Result<Connection, String> {
+  let path = db_path();
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  }
+  let mut conn = Connection::open(&path).map_err(|e| e.to_string())?;
+  conn.busy_timeout(Duration::from_secs(10)).map_err(|e| e.to_string())?;
+  conn
+    .execute_batch("PRAGMA journal_mode = WAL;")
+    .map_err(|e| e.to_string())?;
+  migrate(&mut conn)?;
+  Ok(conn)
+}
+
+/// Fresh row id like `id-lq3v9k2x0001`, unique within this process and
+/// practically unique across runs.
+pub(crate) fn new_id(prefix: &str) -> String {
+  static SEQ: AtomicU32 = AtomicU32::new(0);
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_nanos())
+    .unwrap_or(0);
+  let seq = SEQ.fetch_add(1, Ordering::Relaxed) & 0xffff;
+  format!("{}-{:x}{:04x}", prefix, nanos, seq)
+}
+
+fn to_json<T: Serialize>(v: &Option<T>) -> Option<String> {
+  v.as_ref().and_then(|v| serde_json::to_string(v).ok())
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(v: Option<String>) -> Option<T> {
+  v.and_then(|s| serde_json::from_str(&s).ok())
+}
+
+fn check_status(status: &str) -> Result<(), String> {
+  if STATUSES.contains(&status) {
+    Ok(())
+  } else {
+    Err(format!("Unknown status: {}", status))
+  }
+}
+
+fn require_id(id: &str, kind: &str) -> Result<(), String> {
+  if id.trim().is_empty() {
+    return Err(format!("{} id is required", kind));
+  }
+  Ok(())
+}
+
+const IDENTITY_COLUMNS: &str = "id, title, platform, appid, igdb_cover_id, ttb_source, ttb_median_main_h, \
+  oc_score, mc_score, mc_user_score, mc_genres, critic_score_source";
+const ITEM_COLUMNS: &str = "id, identity_id, account_id, member_id, status, price_try, currency_code, \
+  acquired_at, services, oc_score, mc_score, ttb_median_main_h";
+
+fn identity_from_row(r: &Row) -> rusqlite::Result<Identity> {
+  Ok(Identity {
+    id: r.get(0)?,
+    title: r.get(1)?,
+    platform: r.get(2)?,
+    appid: r.get(3)?,
+    igdb_cover_id: r.get(4)?,
+    ttb_source: r.get(5)?,
+    ttb_median_main_h: r.get(6)?,
+    oc_score: r.get(7)?,
+    mc_score: r.get(8)?,
+    mc_user_score: r.get(9)?,
+    mc_genres: from_json(r.get(10)?),
+    critic_score_source: r.get(11)?,
+  })
+}
+
+fn item_from_row(r: &Row) -> rusqlite::Result<LibraryItem> {
+  Ok(LibraryItem {
+    id: r.get(0)?,
+    identity_id: r.get(1)?,
+    account_id: r.get(2)?,
+    member_id: r.get(3)?,
+    status: r.get(4)?,
+    price_try: r.get(5)?,
+    currency_code: r.get(6)?,
+    acquired_at: r.get(7)?,
+    services: from_json(r.get(8)?),
+    oc_score: r.get(9)?,
+    mc_score: r.get(10)?,
+    ttb_median_main_h: r.get(11)?,
+  })
+}
+
+pub(crate) fn put_identity(conn: &Connection, v: &Identity) -> Result<(), String> {
+  conn
+    .execute(
+      &format!("INSERT OR REPLACE INTO identities ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)", IDENTITY_COLUMNS),
+      params![
+        v.id,
+        v.title,
+        v.platform,
+        v.appid,
+        v.igdb_cover_id,
+        v.ttb_source,
+        v.ttb_median_main_h,
+        v.oc_score,
+        v.mc_score,
+        v.mc_user_score,
+        to_json(&v.mc_genres),
+        v.critic_score_source
+      ],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) fn put_item(conn: &Connection, v: &LibraryItem) -> Result<(), String> {
+  conn
+    .execute(
+      &format!("INSERT OR REPLACE INTO library ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)", ITEM_COLUMNS),
+      params![
+        v.id,
+        v.identity_id,
+        v.account_id,
+        v.member_id,
+        v.status,
+        v.price_try,
+        v.currency_code,
+        v.acquired_at,
+        to_json(&v.services),
+        v.oc_score,
+        v.mc_score,
+        v.ttb_median_main_h
+      ],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+fn put_account(conn: &Connection, v: &Account) -> Result<(), String> {
+  conn
+    .execute(
+      "INSERT OR REPLACE INTO accounts (id, platform, label, identity_id) VALUES (?1, ?2, ?3, ?4)",
+      params![v.id, v.platform, v.label, v.identity_id],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+fn put_member(conn: &Connection, v: &Member) -> Result<(), String> {
+  conn
+    .execute(
+      "INSERT OR REPLACE INTO members (id, name) VALUES (?1, ?2)",
+      params![v.id, v.name],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_identities(conn: &Connection) -> Result<Vec<Identity>, String> {
+  let mut stmt = conn
+    .prepare(&format!("SELECT {} FROM identities ORDER BY title COLLATE NOCASE", IDENTITY_COLUMNS))
+    .map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], identity_from_row).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_items(conn: &Connection) -> Result<Vec<LibraryItem>, String> {
+  let mut stmt = conn
+    .prepare(&format!("SELECT {} FROM library ORDER BY id", ITEM_COLUMNS))
+    .map_err(|e| e.to_string())?;
+  let rows = stmt.query_map([], item_from_row).map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_accounts(conn: &Connection) -> Result<Vec<Account>, String> {
+  let mut stmt = conn
+    .prepare("SELECT id, platform, label, identity_id FROM accounts ORDER BY label COLLATE NOCASE")
+    .map_err(|e| e.to_string())?;
+  let rows = stmt
+    .query_map([], |r| {
+      Ok(Account {
+        id: r.get(0)?,
+        platform: r.get(1)?,
+        label: r.get(2)?,
+        identity_id: r.get(3)?,
+      })
+    })
+    .map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_members(conn: &Connection) -> Result<Vec<Member>, String> {
+  let mut stmt = conn
+    .prepare("SELECT id, name FROM members ORDER BY name COLLATE NOCASE")
+    .map_err(|e| e.to_string())?;
+  let rows = stmt
+    .query_map([], |r| Ok(Member { id: r.get(0)?, name: r.get(1)? }))
+    .map_err(|e| e.to_string())?;
+  rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_setting(conn: &Connection, key: &str) -> Result<Option<Value>, String> {
+  let raw: Option<String> = conn
+    .query_row("SELECT value FROM settings WHERE key = ?1", [key], |r| r.get(0))
+    .optional()
+    .map_err(|e| e.to_string())?;
+  Ok(raw.and_then(|v| serde_json::from_str(&v).ok()))
+}
+
+pub(crate) fn set_setting(conn: &Connection, key: &str, value: &Value) -> Result<(), String> {
+  conn
+    .execute(
+      "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+      params![key, value.to_string()],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+fn delete_by_id(table: &str, id: &str) -> Result<bool, String> {
+  let conn = open_db()?;
+  let n = conn
+    .execute(&format!("DELETE FROM {} WHERE id = ?1", table), [id])
+    .map_err(|e| e.to_string())?;
+  Ok(n > 0)
+}
+
+#[tauri::command]
+pub fn library_list_identities() -> Result<Vec<Identity>, String> {
+  load_identities(&open_db()?)
+}
+
+#[tauri::command]
+pub fn library_get_identity(id: String) -> Result<Option<Identity>, String> {
+  let conn = open_db()?;
+  conn
+    .query_row(
+      &format!("SELECT {} FROM identities WHERE id = ?1", IDENTITY_COLUMNS),
+      [&id],
+      identity_from_row,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn library_upsert_identity(identity: Identity) -> Result<Identity, String> {
+  require_id(&identity.id, "Identity")?;
+  if identity.title.trim().is_empty() {
+    return Err("Identity title is required".into());
+  }
+  put_identity(&open_db()?, &identity)?;
+  Ok(identity)
+}
+
+/// Deletes an identity. Refuses while library items still point at it.
+#[tauri::command]
+pub fn library_delete_identity(id: String) -> Result<bool, String> {
+  let conn = open_db()?;
+  let refs: i64 = conn
+    .query_row("SELECT COUNT(*) FROM library WHERE identity_id = ?1", [&id], |r| r.get(0))
+    .map_err(|e| e.to_string())?;
+  if refs > 0 {
+    return Err(format!("Identity {} is still used by {} library item(s)", id, refs));
+  }
+  delete_by_id("identities", &id)
+}
+
+#[tauri::command]
+pub fn library_list_items(member_id: Option<String>) -> Result<Vec<LibraryItem>, String> {
+  let items = load_items(&open_db()?)?;
+  Ok(match member_id {
+    Some(m) => items.into_iter().filter(|i| i.member_id.as_deref() == Some(m.as_str())).collect(),
+    None => items,
+  })
+}
+
+#[tauri::command]
+pub fn library_get_item(id: String) -> Result<Option<LibraryItem>, String> {
+  let conn = open_db()?;
+  conn
+    .query_row(&format!("SELECT {} FROM library WHERE id = ?1", ITEM_COLUMNS), [&id], item_from_row)
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn library_upsert_item(item: LibraryItem) -> Result<LibraryItem, String> {
+  require_id(&item.id, "Library item")?;
+  check_status(&item.status)?;
+  let conn = open_db()?;
+  let known: bool = conn
+    .query_row("SELECT 1 FROM identities WHERE id = ?1", [&item.identity_id], |_| Ok(()))
+    .optional()
+    .map_err(|e| e.to_string())?
+    .is_some();
+  if !known {
+    return Err(format!("Unknown identity: {}", item.identity_id));
+  }
+  put_item(&conn, &item)?;
+  Ok(item)
+}
+
+#[tauri::command]
+pub fn library_delete_item(id: String) -> Result<bool, String> {
+  delete_by_id("library", &id)
+}
+
+#[tauri::command]
+pub fn library_list_accounts() -> Result<Vec<Account>, String> {
+  load_accounts(&open_db()?)
+}
+
+#[tauri::command]
+pub fn library_upsert_account(account: Account) -> Result<Account, String> {
+  require_id(&account.id, "Account")?;
+  put_account(&open_db()?, &account)?;
+  Ok(account)
+}
+
+#[tauri::command]
+pub fn library_delete_account(id: String) -> Result<bool, String> {
+  let conn = open_db()?;
+  conn
+    .execute("UPDATE library SET account_id = NULL WHERE account_id = ?1", [&id])
+    .map_err(|e| e.to_string())?;
+  delete_by_id("accounts", &id)
+}
+
+#[tauri::command]
+pub fn library_list_members() -> Result<Vec<Member>, String> {
+  load_members(&open_db()?)
+}
+
+#[tauri::command]
+pub fn library_upsert_member(member: Member) -> Result<Member, String> {
+  require_id(&member.id, "Member")?;
+  if member.name.trim().is_empty() {
+    return Err("Member name is required".into());
+  }
+  put_member(&open_db()?, &member)?;
+  Ok(member)
+}
+
+#[tauri::command]
+pub fn library_delete_member(id: String) -> Result<bool, String> {
+  let conn = open_db()?;
+  conn
+    .execute("UPDATE library SET member_id = NULL WHERE member_id = ?1", [&id])
+    .map_err(|e| e.to_string())?;
+  delete_by_id("members", &id)
+}
+
+/// Table name -> rows from a Dexie dump. Accepts both the `dexie-export-import`
+/// format (`{ data: { data: [{ tableName, rows }] } }`) and a plain
+/// `{ "identities": [...], "library": [...] }` object.
+fn dexie_tables(doc: &Value) -> Vec<(String, Vec<Value>)> {
+  let exported = doc
+    .pointer("/data/data")
+    .and_then(|d| d.as_array())
+    .map(|tables| {
+      tables
+        .iter()
+        .filter_map(|t| {
+          let name = t.get("tableName")?.as_str()?.to_string();
+          let rows = t.get("rows")?.as_array()?.clone();
+          Some((name, rows))
+        })
+        .collect::<Vec<_>>()
+    });
+  if let Some(tables) = exported {
+    return tables;
+  }
+  doc
+    .as_object()
+    .map(|obj| {
+      obj
+        .iter()
+        .filter_map(|(k, v)| Some((k.clone(), v.as_array()?.clone())))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn import_rows(tx: &Transaction, table: &str, rows: &[Value], summary: &mut DexieImportSummary) {
+  for row in rows.iter().cloned() {
+    let ok = match table {
+      "identities" => match serde_json::from_value::<Identity>(row) {
+        Ok(v) if !v.id.is_empty() && !v.title.is_empty() => put_identity(tx, &v).map(|_| summary.identities += 1).is_ok(),
+        _ => false,
+      },
+      "library" => match serde_json::from_value::<LibraryItem>(row) {
+        Ok(v) if !v.id.is_empty() && check_status(&v.status).is_ok() => put_item(tx, &v).map(|_| summary.library += 1).is_ok(),
+        _ => false,
+      },
+      "accounts" => match serde_json::from_value::<Account>(row) {
+        Ok(v) if !v.id.is_empty() => put_account(tx, &v).map(|_| summary.accounts += 1).is_ok(),
+        _ => false,
+      },
+      "members" => match serde_json::from_value::<Member>(row) {
+        Ok(v) if !v.id.is_empty() => put_member(tx, &v).map(|_| summary.members += 1).is_ok(),
+        _ => false,
+      },
+      "settings" => match (row.get("key").and_then(|k| k.as_str()), row.get("value")) {
+        (Some(key), Some(value)) => tx
+          .execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![key, value.to_string()],
+          )
+          .map(|_| summary.settings += 1)
+          .is_ok(),
+        _ => false,
+      },
+      "rawgGames" => match row.get("id").and_then(|i| i.as_i64()) {
+        Some(id) => tx
+          .execute(
+            "INSERT OR REPLACE INTO rawg_games (id, slug, title_key, data) VALUES (?1, ?2, ?3, ?4)",
+            params![
+              id,
+              row.get("slug").and_then(|s| s.as_str()),
+              row.get("titleKey").and_then(|s| s.as_str()),
+              row.to_string()
+            ],
+          )
+          .map(|_| summary.rawg_games += 1)
+          .is_ok(),
+        None => false,
+      },
+      _ => return,
+    };
+    if !ok {
+      summary.skipped += 1;
+    }
+  }
+}
+
+/// One-shot migration of the WebView's Dexie database from a JSON dump.
+/// Refuses to run twice unless `force` is set; existing rows with the same id
+/// are overwritten.
+#[tauri::command]
+pub fn library_import_dexie(path: String, force: Option<bool>) -> Result<DexieImportSummary, String> {
+  let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+  let doc: Value = serde_json::from_str(text.trim_start_matches('\u{feff}')).map_err(|e| format!("{}: {}", path, e))?;
+  let tables = dexie_tables(&doc);
+  if tables.is_empty() {
+    return Err("No Dexie tables found in the dump".into());
+  }
+
+  let mut conn = open_db()?;
+  let previous: Option<String> = conn
+    .query_row("SELECT value FROM meta WHERE key = ?1", [DEXIE_IMPORTED_KEY], |r| r.get(0))
+    .optional()
+    .map_err(|e| e.to_string())?;
+  if previous.is_some() && !force.unwrap_or(false) {
+    return Err("The Dexie library has already been imported; pass force to import again".into());
+  }
+
+  backup::create_backup("pre_import")?;
+  let mut summary = DexieImportSummary {
+    imported_at: now_unix(),
+    ..Default::default()
+  };
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  for (name, rows) in &tables {
+    import_rows(&tx, name, rows, &mut summary);
+  }
+  tx.execute(
+    "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+    params![DEXIE_IMPORTED_KEY, summary.imported_at.to_string()],
+  )
+  .map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())?;
+  Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn tables(conn: &Connection) -> Vec<String> {
+    let mut stmt = conn
+      .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+      .unwrap();
+    let names = stmt.query_map([], |r| r.get(0)).unwrap();
+    names.collect::<Result<_, _>>().unwrap()
+  }
+
+  #[test]
+  fn migrates_a_fresh_database_once() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrate(&mut conn).unwrap();
+    assert_eq!(user_version(&conn).unwrap(), MIGRATIONS.len());
+    let names = tables(&conn);
+    for table in ["identities", "library", "settings", "playtime_snapshots", "fx_rates", "price_history", "price_alerts"] {
+      assert!(names.iter().any(|n| n == table), "missing {}", table);
+    }
+    // Already current: nothing runs, so nothing fails on existing tables.
+    migrate(&mut conn).unwrap();
+    assert_eq!(tables(&conn), names);
+  }
+
+  #[test]
+  fn upgrades_keep_existing_rows() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    {
+      let tx = conn.transaction().unwrap();
+      tx.execute_batch(MIGRATIONS[0]).unwrap();
+      tx.pragma_update(None, "user_version", 1).unwrap();
+      tx.commit().unwrap();
+    }
+    let identity = Identity {
+      id: "id-1".into(),
+      title: "Hades".into(),
+      appid: Some(1145360),
+      mc_genres: Some(vec!["Action".into(), "Roguelike".into()]),
+      ..Default::default()
+    };
+    let item = LibraryItem {
+      id: "item-1".into(),
+      identity_id: "id-1".into(),
+      status: "Beaten".into(),
+      price_try: Some(129.0),
+      services: Some(vec!["Game Pass".into()]),
+      ..Default::default()
+    };
+    put_identity(&conn, &identity).unwrap();
+    put_item(&conn, &item).unwrap();
+
+    let tx = conn.transaction().unwrap();
+    apply_migrations(&tx, user_version(&tx).unwrap()).unwrap();
+    tx.commit().unwrap();
+    assert_eq!(user_version(&conn).unwrap(), MIGRATIONS.len());
+
+    let identities = load_identities(&conn).unwrap();
+    assert_eq!(identities.len(), 1);
+    assert_eq!(identities[0].appid, Some(1145360));
+    assert_eq!(identities[0].mc_genres.as_deref(), Some(&["Action".to_string(), "Roguelike".to_string()][..]));
+    let items = load_items(&conn).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!((items[0].status.as_str(), items[0].price_try), ("Beaten", Some(129.0)));
+    assert_eq!(items[0].services.as_deref(), Some(&["Game Pass".to_string()][..]));
+  }
+
+  #[test]
+  fn refuses_a_newer_schema() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.pragma_update(None, "user_version", (MIGRATIONS.len() + 1) as i64).unwrap();
+    let err = migrate(&mut conn).unwrap_err();
+    assert!(err.contains("only knows"), "{}", err);
+  }
+}
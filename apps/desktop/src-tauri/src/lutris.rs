@@ -0,0 +1,277 @@
+use rusqlite::{types::ValueRef, Connection, OpenFlags};
+use serde::Serialize;
+use serde_yaml::Value as Yaml;
+use std::{
+  collections::HashSet,
+  fs,
+  path::{Path, PathBuf},
+};
+
+#[derive(Serialize)]
+pub struct LutrisGame {
+  pub id: i64,
+  pub slug: String,
+  pub name: String,
+  /// Lutris runner: "wine", "steam", "linux", or an emulator like "dolphin".
+  pub runner: Option<String>,
+  pub platform: Option<String>,
+  pub playtime_minutes: Option<u32>,
+  pub last_played: Option<i64>,
+  pub directory: Option<String>,
+  /// Executable, ROM or disc image from the game's YAML config.
+  pub executable: Option<String>,
+  /// Linked store service ("steam", "gog", "humblebundle", ...) and its id.
+  pub service: Option<String>,
+  pub service_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct LutrisLibrary {
+  pub data_dir: String,
+  pub games: Vec<LutrisGame>,
+}
+
+// (data dir holding pga.db, games/ config dir)
+fn candidate_dirs() -> Vec<(PathBuf, PathBuf)> {
+  let mut out = Vec::new();
+  if let Some(home) = dirs::home_dir() {
+    out.push((home.join(".local/share/lutris"), home.join(".config/lutris")));
+    out.push((
+      home.join(".var/app/net.lutris.Lutris/data/lutris"),
+      home.join(".var/app/net.lutris.Lutris/config/lutris"),
+    ));
+  }
+  out
+}
+
+fn resolve_dirs(data_dir: Option<&str>) -> Result<(PathBuf, PathBuf), String> {
+  if let Some(dir) = data_dir.map(str::trim).filter(|d| !d.is_empty()) {
+    let path = PathBuf::from(dir);
+    if !path.join("pga.db").is_file() {
+      return Err(format!("Lutris pga.db not found in {}", dir));
+    }
+    // Older installs (and copied or Flatpak data dirs) keep games/*.yml next
+    // to pga.db; otherwise they live under ~/.config.
+    let config = if path.join("games").is_dir() {
+      path.clone()
+    } else {
+      dirs::config_dir()
+        .map(|c| c.join("lutris"))
+        .filter(|c| c.join("games").is_dir())
+        .unwrap_or_else(|| path.clone())
+    };
+    return Ok((path, config));
+  }
+  candidate_dirs()
+    .into_iter()
+    .find(|(data, _)| data.join("pga.db").is_file())
+    .ok_or_else(|| "Could not find a Lutris installation; pass its data directory explicitly".to_string())
+}
+
+fn game_columns(conn: &Connection) -> Result<HashSet<String>, String> {
+  let mut stmt = conn.prepare("PRAGMA table_info(games)").map_err(|e| e.to_string())?;
+  let rows = stmt
+    .query_map([], |r| r.get::<_, String>(1))
+    .map_err(|e| e.to_string())?;
+  rows.collect::<Result<HashSet<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Platform names for emulator runners, used when Lutris left `platform` empty.
+fn runner_platform(runner: &str) -> Option<&'static str> {
+  Some(match runner {
+    "dolphin" => "Nintendo GameCube/Wii",
+    "cemu" => "Nintendo Wii U",
+    "citra" => "Nintendo 3DS",
+    "yuzu" | "ryujinx" => "Nintendo Switch",
+    "desmume" | "melonds" => "Nintendo DS",
+    "mupen64plus" => "Nintendo 64",
+    "snes9x" => "Super Nintendo",
+    "pcsx2" => "Sony PlayStation 2",
+    "rpcs3" => "Sony PlayStation 3",
+    "ppsspp" => "Sony PSP",
+    "duckstation" | "pcsxr" => "Sony PlayStation",
+    "xemu" => "Microsoft Xbox",
+    "dosbox" => "MS-DOS",
+    "scummvm" => "ScummVM",
+    "mame" => "Arcade",
+    "linux" => "Linux",
+    "wine" => "Windows",
+    _ => return None,
+  })
+}
+
+/// `playtime` is REAL hours in current databases, but older ones stored text
+/// such as "12.5" or "12.5 hours".
+fn playtime_hours(value: ValueRef<'_>) -> Option<f64> {
+  match value {
+    ValueRef::Real(h) => Some(h),
+    ValueRef::Integer(h) => Some(h as f64),
+    ValueRef::Text(t) => String::from_utf8_lossy(t).split_whitespace().next()?.parse().ok(),
+    _ => None,
+  }
+}
+
+fn yaml_str<'a>(v: &'a Yaml, key: &str) -> Option<&'a str> {
+  v.get(key).and_then(|s| s.as_str()).map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Pulls the launch target out of `games/<configpath>.yml`: `exe` for
+/// wine/native, `main_file` for most emulators, `iso`/`rom` for the rest.
+fn config_executable(config_dir: &Path, configpath: &str) -> Option<String> {
+  let text = fs::read_to_string(config_dir.join("games").join(format!("{}.yml", configpath))).ok()?;
+  let doc: Yaml = serde_yaml::from_str(&text).ok()?;
+  let game = doc.get("game")?;
+  ["exe", "main_file", "iso", "rom", "appid"]
+    .iter()
+    .find_map(|k| yaml_str(game, k))
+    .map(str::to_string)
+}
+
+/// Reads installed games from Lutris's `pga.db` and their YAML configs,
+/// including emulated console games no other importer can see.
+#[tauri::command]
+pub fn lutris_library(data_dir: Option<String>) -> Result<LutrisLibrary, String> {
+  let (data, config) = resolve_dirs(data_dir.as_deref())?;
+  let db_path = data.join("pga.db");
+  let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+    .map_err(|e| format!("{}: {}", db_path.display(), e))?;
+
+  // Columns were added over Lutris releases; select NULL for missing ones.
+  let columns = game_columns(&conn)?;
+  let col = |name: &str| {
+    if columns.contains(name) {
+      name.to_string()
+    } else {
+      format!("NULL AS {}", name)
+    }
+  };
+  let sql = format!(
+    "SELECT id, slug, name, {}, {}, {}, {}, {}, {}, {}, {} FROM games WHERE installed = 1",
+    col("runner"),
+    col("platform"),
+    col("playtime"),
+    col("lastplayed"),
+    col("directory"),
+    col("configpath"),
+    col("service"),
+    col("service_id"),
+  );
+  let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+  let rows = stmt
+    .query_map([], |r| {
+      Ok((
+        r.get::<_, i64>(0)?,
+        r.get::<_, Option<String>>(1)?,
+        r.get::<_, Option<String>>(2)?,
+        r.get::<_, Option<String>>(3)?,
+        r.get::<_, Option<String>>(4)?,
+        playtime_hours(r.get_ref(5)?),
+        r.get::<_, Option<i64>>(6)?,
+        r.get::<_, Option<String>>(7)?,
+        r.get::<_, Option<String>>(8)?,
+        r.get::<_, Option<String>>(9)?,
+        r.get::<_, Option<String>>(10)?,
+      ))
+    })
+    .map_err(|e| e.to_string())?;
+
+  let non_empty = |s: Option<String>| s.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+  let mut games = Vec::new();
+  for row in rows {
+    let (id, slug, name, runner, platform, playtime, lastplayed, directory, configpath, service, service_id) =
+      row.map_err(|e| e.to_string())?;
+    let slug = non_empty(slug).unwrap_or_default();
+    let Some(name) = non_empty(name).or_else(|| Some(slug.clone()).filter(|s| !s.is_empty())) else {
+      continue;
+    };
+    let runner = non_empty(runner);
+    let platform = non_empty(platform).or_else(|| runner.as_deref().and_then(runner_platform).map(str::to_string));
+    games.push(LutrisGame {
+      id,
+      slug,
+      name,
+      platform,
+      // Lutris stores playtime as fractional hours.
+      playtime_minutes: playtime.filter(|h| *h > 0.0).map(|h| (h * 60.0).round() as u32),
+      last_played: lastplayed.filter(|t| *t > 0),
+      directory: non_empty(directory),
+      executable: non_empty(configpath).and_then(|c| config_executable(&config, &c)),
+      service: non_empty(service),
+      service_id: non_empty(service_id),
+      runner,
+    });
+  }
+  games.sort_by_key(|g| g.name.to_lowercase());
+  Ok(LutrisLibrary {
+    data_dir: data.display().to_string(),
+    games,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn fixture_dir(name: &str, schema: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lutris_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("games")).unwrap();
+    Connection::open(dir.join("pga.db")).unwrap().execute_batch(schema).unwrap();
+    dir
+  }
+
+  #[test]
+  fn reads_current_pga_db_and_configs() {
+    let dir = fixture_dir(
+      "current",
+      "CREATE TABLE games (id INTEGER PRIMARY KEY, slug TEXT, name TEXT, runner TEXT, platform TEXT,
+         playtime REAL, lastplayed INTEGER, directory TEXT, configpath TEXT, installed INTEGER,
+         service TEXT, service_id TEXT);
+       INSERT INTO games VALUES
+         (1, 'metroid-prime', 'Metroid Prime', 'dolphin', '', 12.25, 1700000000, '/roms', 'metroid-prime-1', 1, NULL, NULL),
+         (2, 'the-witcher', 'The Witcher', 'wine', 'Windows', 0, 0, '/games/witcher', 'the-witcher-2', 1, 'gog', '1207658924'),
+         (3, 'uninstalled', 'Uninstalled', 'linux', NULL, 3, NULL, NULL, NULL, 0, NULL, NULL),
+         (4, 'slug-only', '  ', 'linux', NULL, NULL, NULL, NULL, NULL, 1, NULL, NULL);",
+    );
+    fs::write(dir.join("games/metroid-prime-1.yml"), "game:\n  main_file: /roms/metroid.iso\n").unwrap();
+    fs::write(dir.join("games/the-witcher-2.yml"), "game:\n  exe: /games/witcher/witcher.exe\n").unwrap();
+    let result = lutris_library(Some(dir.display().to_string()));
+    let _ = fs::remove_dir_all(&dir);
+    let games = result.unwrap().games;
+
+    let names: Vec<&str> = games.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, vec!["Metroid Prime", "slug-only", "The Witcher"]);
+    let metroid = &games[0];
+    assert_eq!(metroid.platform.as_deref(), Some("Nintendo GameCube/Wii"));
+    assert_eq!(metroid.playtime_minutes, Some(735));
+    assert_eq!(metroid.last_played, Some(1700000000));
+    assert_eq!(metroid.executable.as_deref(), Some("/roms/metroid.iso"));
+    let witcher = &games[2];
+    assert_eq!((witcher.playtime_minutes, witcher.last_played), (None, None));
+    assert_eq!(witcher.executable.as_deref(), Some("/games/witcher/witcher.exe"));
+    assert_eq!((witcher.service.as_deref(), witcher.service_id.as_deref()), (Some("gog"), Some("1207658924")));
+  }
+
+  #[test]
+  fn reads_old_schemas_with_text_playtime() {
+    let dir = fixture_dir(
+      "old",
+      "CREATE TABLE games (id INTEGER PRIMARY KEY, slug TEXT, name TEXT, runner TEXT, playtime TEXT,
+         installed INTEGER);
+       INSERT INTO games VALUES (1, 'quake', 'Quake', 'linux', '1.5 hours', 1);",
+    );
+    let result = lutris_library(Some(dir.display().to_string()));
+    let _ = fs::remove_dir_all(&dir);
+    let games = result.unwrap().games;
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].platform.as_deref(), Some("Linux"));
+    assert_eq!(games[0].playtime_minutes, Some(90));
+    assert_eq!((games[0].service.as_deref(), games[0].executable.as_deref()), (None, None));
+  }
+
+  #[test]
+  fn explicit_dir_needs_pga_db() {
+    let missing = std::env::temp_dir().join(format!("lutris_missing_{}", std::process::id()));
+    assert!(lutris_library(Some(missing.display().to_string())).is_err());
+  }
+}
@@ -1,15 +1,143 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod analytics;
+mod backup;
+mod breaker;
 mod commands;
-use commands::{get_opencritic_score, get_steam_price_try, hltb_clear_cache, hltb_search};
+mod csv_import;
+mod currency;
+mod datasets;
+mod dedupe;
+mod export;
+mod forecast;
+mod gog;
+mod heroic;
+mod library;
+mod lutris;
+mod offline;
+mod playnite;
+mod price_history;
+mod ratelimit;
+mod steam;
+mod steam_applist;
+mod steam_local;
+mod suggest;
+mod vdf;
+use analytics::library_spending_analytics;
+use backup::{backup_create, backup_list, backup_prune, backup_restore};
+use breaker::{get_provider_status, reset_provider_breaker};
+use commands::{
+  diagnose_providers, get_opencritic_score, get_steam_price_try, hltb_clear_cache, hltb_search,
+};
+use csv_import::{csv_import_commit, csv_import_preview};
+use currency::{fx_convert, fx_import_rates, fx_refresh_rates, fx_status, library_convert_prices};
+use datasets::{datasets_import, datasets_status};
+use dedupe::{library_find_duplicates, library_merge_identities};
+use export::{library_export, library_export_columns};
+use forecast::{library_backlog_forecast, library_record_playtime};
+use gog::gog_galaxy_import;
+use heroic::heroic_library;
+use library::{
+  library_delete_account, library_delete_identity, library_delete_item, library_delete_member, library_get_identity,
+  library_get_item, library_import_dexie, library_list_accounts, library_list_identities, library_list_items,
+  library_list_members, library_upsert_account, library_upsert_identity, library_upsert_item, library_upsert_member,
+};
+use lutris::lutris_library;
+use offline::{get_offline_status, set_offline_mode};
+use playnite::{playnite_export, playnite_import};
+use price_history::{
+  price_alert_delete, price_alert_list, price_alert_set, price_history, price_refresh_watched, price_summaries,
+};
+use ratelimit::{get_rate_limits, set_rate_limit};
+use steam::{get_owned_games, get_steam_achievements, get_steam_app_metadata, get_steam_wishlist};
+use steam_applist::{steam_applist_import, steam_resolve_appid, steam_resolve_appids};
+use steam_local::{steam_local_accounts, steam_local_playtime, steam_scan_installed};
+use suggest::{get_member_preferences, library_suggest, set_member_preferences};
+use tauri::Manager;
 
 fn main() {
+  backup::start_scheduler();
+  forecast::start_scheduler();
   tauri::Builder::default()
+    .setup(|app| {
+      if let Ok(dir) = app.path().resource_dir() {
+        datasets::set_resource_dir(dir);
+      }
+      commands::rekey_title_caches();
+      price_history::start_scheduler(app.handle().clone());
+      Ok(())
+    })
     .invoke_handler(tauri::generate_handler![
       hltb_search,
       hltb_clear_cache,
       get_steam_price_try,
-      get_opencritic_score
+      get_opencritic_score,
+      diagnose_providers,
+      get_rate_limits,
+      set_rate_limit,
+      get_provider_status,
+      reset_provider_breaker,
+      get_offline_status,
+      set_offline_mode,
+      steam_applist_import,
+      steam_resolve_appid,
+      steam_resolve_appids,
+      steam_scan_installed,
+      steam_local_accounts,
+      steam_local_playtime,
+      get_steam_wishlist,
+      get_owned_games,
+      get_steam_achievements,
+      get_steam_app_metadata,
+      gog_galaxy_import,
+      heroic_library,
+      lutris_library,
+      playnite_import,
+      playnite_export,
+      library_list_identities,
+      library_get_identity,
+      library_upsert_identity,
+      library_delete_identity,
+      library_list_items,
+      library_get_item,
+      library_upsert_item,
+      library_delete_item,
+      library_list_accounts,
+      library_upsert_account,
+      library_delete_account,
+      library_list_members,
+      library_upsert_member,
+      library_delete_member,
+      library_import_dexie,
+      backup_create,
+      backup_list,
+      backup_restore,
+      backup_prune,
+      csv_import_preview,
+      csv_import_commit,
+      library_export_columns,
+      library_export,
+      library_find_duplicates,
+      library_merge_identities,
+      library_suggest,
+      get_member_preferences,
+      set_member_preferences,
+      library_spending_analytics,
+      library_backlog_forecast,
+      library_record_playtime,
+      fx_import_rates,
+      fx_refresh_rates,
+      fx_status,
+      fx_convert,
+      library_convert_prices,
+      price_history,
+      price_summaries,
+      price_alert_set,
+      price_alert_list,
+      price_alert_delete,
+      price_refresh_watched,
+      datasets_status,
+      datasets_import
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
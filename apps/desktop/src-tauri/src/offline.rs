@@ -0,0 +1,81 @@
+use crate::commands::now_unix;
+use serde::Serialize;
+use std::sync::atomic::{AtomicI64, AtomicU8, Ordering};
+
+const MODE_AUTO: u8 = 0;
+const MODE_ON: u8 = 1;
+const MODE_OFF: u8 = 2;
+
+// After a connectivity failure, treat the network as down for this long
+// before letting the next lookup probe it again.
+const AUTO_RECHECK_SECS: i64 = 60;
+
+static MODE: AtomicU8 = AtomicU8::new(MODE_AUTO);
+static DETECTED_AT: AtomicI64 = AtomicI64::new(0);
+
+fn mode_name(mode: u8) -> &'static str {
+  match mode {
+    MODE_ON => "on",
+    MODE_OFF => "off",
+    _ => "auto",
+  }
+}
+
+/// True when lookups must be served from caches and local datasets only.
+pub fn is_offline() -> bool {
+  match MODE.load(Ordering::Relaxed) {
+    MODE_ON => true,
+    MODE_OFF => false,
+    _ => {
+      let detected = DETECTED_AT.load(Ordering::Relaxed);
+      detected != 0 && now_unix().saturating_sub(detected) < AUTO_RECHECK_SECS
+    }
+  }
+}
+
+/// Stringifies a request error, flagging the network as down when it is a
+/// connect or timeout failure rather than an HTTP-level one.
+pub fn note_error(e: &reqwest::Error) -> String {
+  if e.is_connect() || e.is_timeout() {
+    DETECTED_AT.store(now_unix(), Ordering::Relaxed);
+  }
+  e.to_string()
+}
+
+/// Any response at all means the network is reachable again.
+pub fn note_online() {
+  DETECTED_AT.store(0, Ordering::Relaxed);
+}
+
+#[derive(Serialize)]
+pub struct OfflineStatus {
+  /// "auto" | "on" | "off"
+  pub mode: String,
+  pub offline: bool,
+  pub detected_at: Option<i64>,
+}
+
+#[tauri::command]
+pub fn get_offline_status() -> OfflineStatus {
+  let detected = DETECTED_AT.load(Ordering::Relaxed);
+  OfflineStatus {
+    mode: mode_name(MODE.load(Ordering::Relaxed)).into(),
+    offline: is_offline(),
+    detected_at: if detected == 0 { None } else { Some(detected) },
+  }
+}
+
+#[tauri::command]
+pub fn set_offline_mode(mode: String) -> Result<OfflineStatus, String> {
+  let value = match mode.trim().to_lowercase().as_str() {
+    "auto" => MODE_AUTO,
+    "on" | "offline" | "true" => MODE_ON,
+    "off" | "online" | "false" => MODE_OFF,
+    other => return Err(format!("Unknown offline mode '{}'", other)),
+  };
+  MODE.store(value, Ordering::Relaxed);
+  if value != MODE_ON {
+    DETECTED_AT.store(0, Ordering::Relaxed);
+  }
+  Ok(get_offline_status())
+}
@@ -0,0 +1,322 @@
+use crate::commands::parse_timestamp;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs;
+
+// Playnite's built-in Steam library plugin; lets Playnite link exported
+// entries with an appid back to the Steam integration.
+const STEAM_PLUGIN_ID: &str = "cb91dfc9-b977-43bf-8e70-55f46e410fab";
+// Playnite has no wishlist; exported wishlist entries are hidden and carry
+// this tag, which the importer reads back as "Wishlist".
+const WISHLIST_TAG: &str = "Wishlist";
+
+#[derive(Serialize)]
+pub struct PlayniteGame {
+  pub name: String,
+  pub source: Option<String>,
+  pub platforms: Vec<String>,
+  pub genres: Vec<String>,
+  /// Our `Status` value mapped from Playnite's completion status.
+  pub status: String,
+  pub completion_status: Option<String>,
+  pub playtime_minutes: Option<u32>,
+  pub last_played: Option<i64>,
+  pub added_at: Option<i64>,
+  pub installed: bool,
+  pub user_score: Option<f64>,
+  pub critic_score: Option<f64>,
+  pub community_score: Option<f64>,
+  /// Store id from the library plugin (Steam appid, GOG id, ...).
+  pub game_id: Option<String>,
+  pub appid: Option<u32>,
+}
+
+/// One library row as the web app sends it for export.
+#[derive(Deserialize)]
+pub struct PlayniteExportItem {
+  pub id: String,
+  pub title: String,
+  pub platform: Option<String>,
+  pub status: String,
+  pub source: Option<String>,
+  pub appid: Option<u32>,
+  pub playtime_minutes: Option<u32>,
+  pub acquired_at: Option<String>,
+  pub oc_score: Option<f64>,
+  pub mc_score: Option<f64>,
+  pub mc_user_score: Option<f64>,
+}
+
+/// Maps Playnite's default completion statuses onto our `Status` values.
+/// Custom or missing statuses fall back to "Owned".
+fn status_from_playnite(completion: Option<&str>) -> &'static str {
+  let Some(c) = completion else { return "Owned" };
+  match c.trim().to_lowercase().as_str() {
+    "not played" | "plan to play" | "on hold" => "Backlog",
+    "playing" => "Playing",
+    "beaten" | "completed" => "Beaten",
+    "abandoned" => "Abandoned",
+    _ => "Owned",
+  }
+}
+
+/// None for statuses Playnite has no completion status for: "Owned" is left
+/// unset so it reads back as "Owned", "Wishlist" is tagged instead.
+fn status_to_playnite(status: &str) -> Option<&'static str> {
+  match status {
+    "Backlog" => Some("Plan to Play"),
+    "Playing" => Some("Playing"),
+    "Beaten" => Some("Beaten"),
+    "Abandoned" => Some("Abandoned"),
+    _ => None,
+  }
+}
+
+/// Exporters disagree on casing (`Name` vs `name`), so look keys up loosely.
+fn field<'a>(obj: &'a Value, key: &str) -> Option<&'a Value> {
+  obj
+    .as_object()?
+    .iter()
+    .find(|(k, _)| k.eq_ignore_ascii_case(key))
+    .map(|(_, v)| v)
+    .filter(|v| !v.is_null())
+}
+
+/// Playnite references sources, platforms and statuses either by name or as
+/// `{ "Id": ..., "Name": ... }` objects.
+fn name_of(v: &Value) -> Option<String> {
+  let name = match v {
+    Value::String(s) => s.as_str(),
+    Value::Object(_) => field(v, "Name")?.as_str()?,
+    _ => return None,
+  };
+  Some(name.trim().to_string()).filter(|n| !n.is_empty())
+}
+
+fn names(v: Option<&Value>) -> Vec<String> {
+  match v {
+    Some(Value::Array(items)) => items.iter().filter_map(name_of).collect(),
+    Some(other) => name_of(other).into_iter().collect(),
+    None => Vec::new(),
+  }
+}
+
+fn number(v: Option<&Value>) -> Option<f64> {
+  match v? {
+    Value::Number(n) => n.as_f64(),
+    Value::String(s) => s.trim().parse().ok(),
+    _ => None,
+  }
+}
+
+fn parse_game(v: &Value) -> Option<PlayniteGame> {
+  let name = field(v, "Name").and_then(name_of)?;
+  let completion_status = field(v, "CompletionStatus").and_then(name_of);
+  let source = field(v, "Source").and_then(name_of);
+  let game_id = field(v, "GameId").and_then(|g| match g {
+    Value::Number(n) => Some(n.to_string()),
+    other => name_of(other),
+  });
+  let is_steam = source.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("steam"))
+    || field(v, "PluginId")
+      .and_then(|p| p.as_str())
+      .is_some_and(|p| p.eq_ignore_ascii_case(STEAM_PLUGIN_ID));
+  let wishlisted = names(field(v, "Tags")).iter().any(|t| t.eq_ignore_ascii_case(WISHLIST_TAG));
+  let status = if wishlisted {
+    "Wishlist"
+  } else {
+    status_from_playnite(completion_status.as_deref())
+  };
+  Some(PlayniteGame {
+    status: status.to_string(),
+    appid: if is_steam { game_id.as_deref().and_then(|g| g.parse().ok()) } else { None },
+    platforms: names(field(v, "Platforms").or_else(|| field(v, "Platform"))),
+    genres: names(field(v, "Genres")),
+    // Playnite tracks playtime in seconds.
+    playtime_minutes: number(field(v, "Playtime")).filter(|s| *s > 0.0).map(|s| (s / 60.0).round() as u32),
+    last_played: field(v, "LastActivity").and_then(|d| d.as_str()).and_then(parse_timestamp),
+    added_at: field(v, "Added").and_then(|d| d.as_str()).and_then(parse_timestamp),
+    installed: field(v, "IsInstalled").and_then(|b| b.as_bool()).unwrap_or(false),
+    user_score: number(field(v, "UserScore")),
+    critic_score: number(field(v, "CriticScore")),
+    community_score: number(field(v, "CommunityScore")),
+    name,
+    source,
+    completion_status,
+    game_id,
+  })
+}
+
+/// Reads a Playnite library export (a JSON array of games, or an object with
+/// a `Games` array) and maps it onto our statuses.
+#[tauri::command]
+pub fn playnite_import(path: String) -> Result<Vec<PlayniteGame>, String> {
+  let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+  let doc: Value = serde_json::from_str(text.trim_start_matches('\u{feff}')).map_err(|e| format!("{}: {}", path, e))?;
+  let games = match &doc {
+    Value::Array(items) => items,
+    other => field(other, "Games")
+      .and_then(|g| g.as_array())
+      .ok_or_else(|| "Not a Playnite export: expected an array of games".to_string())?,
+  };
+  Ok(games.iter().filter_map(parse_game).collect())
+}
+
+/// Writes library rows as Playnite-compatible JSON so they can be pulled into
+/// Playnite with a JSON library importer. Returns the number of games written.
+#[tauri::command]
+pub fn playnite_export(items: Vec<PlayniteExportItem>, path: String) -> Result<usize, String> {
+  let games: Vec<Value> = items
+    .iter()
+    .map(|item| {
+      let mut game = Map::new();
+      game.insert("Id".into(), json!(item.id));
+      game.insert("Name".into(), json!(item.title));
+      if let Some(status) = status_to_playnite(&item.status) {
+        game.insert("CompletionStatus".into(), json!({ "Name": status }));
+      }
+      if item.status == "Wishlist" {
+        game.insert("Hidden".into(), json!(true));
+        game.insert("Tags".into(), json!([{ "Name": WISHLIST_TAG }]));
+      }
+      if let Some(appid) = item.appid {
+        game.insert("GameId".into(), json!(appid.to_string()));
+        game.insert("PluginId".into(), json!(STEAM_PLUGIN_ID));
+        game.insert("Source".into(), json!({ "Name": "Steam" }));
+      } else if let Some(source) = &item.source {
+        game.insert("Source".into(), json!({ "Name": source }));
+      }
+      if let Some(platform) = &item.platform {
+        game.insert("Platforms".into(), json!([{ "Name": platform }]));
+      }
+      if let Some(minutes) = item.playtime_minutes {
+        game.insert("Playtime".into(), json!(u64::from(minutes) * 60));
+      }
+      if let Some(added) = &item.acquired_at {
+        game.insert("Added".into(), json!(added));
+      }
+      // Playnite scores are 0-100 integers.
+      let score = |s: Option<f64>| s.map(|v| v.round().clamp(0.0, 100.0) as u32);
+      if let Some(s) = score(item.oc_score.or(item.mc_score)) {
+        game.insert("CriticScore".into(), json!(s));
+      }
+      // Metacritic user scores are out of 10.
+      if let Some(s) = score(item.mc_user_score.map(|u| u * 10.0)) {
+        game.insert("CommunityScore".into(), json!(s));
+      }
+      Value::Object(game)
+    })
+    .collect();
+  let text = serde_json::to_string_pretty(&games).map_err(|e| e.to_string())?;
+  fs::write(&path, text).map_err(|e| format!("{}: {}", path, e))?;
+  Ok(games.len())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn export_item(id: &str, status: &str) -> PlayniteExportItem {
+    PlayniteExportItem {
+      id: id.into(),
+      title: format!("Game {}", id),
+      platform: Some("PC".into()),
+      status: status.into(),
+      source: None,
+      appid: None,
+      playtime_minutes: None,
+      acquired_at: None,
+      oc_score: None,
+      mc_score: None,
+      mc_user_score: None,
+    }
+  }
+
+  #[test]
+  fn statuses_survive_a_round_trip() {
+    let statuses = ["Backlog", "Playing", "Beaten", "Abandoned", "Wishlist", "Owned"];
+    let items: Vec<PlayniteExportItem> = statuses
+      .iter()
+      .enumerate()
+      .map(|(i, s)| export_item(&i.to_string(), s))
+      .collect();
+    let path = std::env::temp_dir().join(format!("playnite-roundtrip-{}.json", std::process::id()));
+    let path = path.display().to_string();
+    assert_eq!(playnite_export(items, path.clone()).unwrap(), statuses.len());
+    let games = playnite_import(path.clone());
+    let _ = fs::remove_file(&path);
+    let back: Vec<String> = games.unwrap().into_iter().map(|g| g.status).collect();
+    assert_eq!(back, statuses);
+  }
+
+  // Shaped like a Playnite library export: PascalCase keys, `{Id, Name}`
+  // references, playtime in seconds; the second game uses loose casing.
+  const FIXTURE: &str = r#"{"Games": [
+    {
+      "Name": "Portal 2",
+      "PluginId": "cb91dfc9-b977-43bf-8e70-55f46e410fab",
+      "GameId": "620",
+      "Source": {"Id": "s1", "Name": "Steam"},
+      "Platforms": [{"Id": "p1", "Name": "PC (Windows)"}],
+      "Genres": [{"Name": "Puzzle"}, {"Name": "Platformer"}],
+      "CompletionStatus": {"Id": "c1", "Name": "Completed"},
+      "Playtime": 36090,
+      "LastActivity": "2023-04-05T18:30:00Z",
+      "Added": "2021-01-02T03:04:05Z",
+      "IsInstalled": true,
+      "UserScore": 95,
+      "CriticScore": "95"
+    },
+    {
+      "name": "Celeste",
+      "source": "itch.io",
+      "gameId": 42,
+      "platform": "PC (Linux)",
+      "completionStatus": "On Hold",
+      "playtime": 0
+    },
+    {"Name": "Custom Status", "CompletionStatus": {"Name": "Replaying"}},
+    {"Name": "  "},
+    {"Source": "Steam"}
+  ]}"#;
+
+  #[test]
+  fn imports_playnite_fixture() {
+    let path = std::env::temp_dir().join(format!("playnite-fixture-{}.json", std::process::id()));
+    // Windows exporters like to start with a BOM.
+    fs::write(&path, format!("\u{feff}{}", FIXTURE)).unwrap();
+    let result = playnite_import(path.display().to_string());
+    let _ = fs::remove_file(&path);
+    let games = result.unwrap();
+
+    let summary: Vec<(&str, &str)> = games.iter().map(|g| (g.name.as_str(), g.status.as_str())).collect();
+    assert_eq!(summary, vec![("Portal 2", "Beaten"), ("Celeste", "Backlog"), ("Custom Status", "Owned")]);
+    let portal = &games[0];
+    assert_eq!(portal.appid, Some(620));
+    assert_eq!(portal.source.as_deref(), Some("Steam"));
+    assert_eq!(portal.platforms, vec!["PC (Windows)"]);
+    assert_eq!(portal.genres, vec!["Puzzle", "Platformer"]);
+    assert_eq!(portal.playtime_minutes, Some(602));
+    assert_eq!(portal.last_played, parse_timestamp("2023-04-05T18:30:00Z"));
+    assert!(portal.installed);
+    assert_eq!((portal.user_score, portal.critic_score), (Some(95.0), Some(95.0)));
+    let celeste = &games[1];
+    // Not a Steam game, so its numeric id isn't an appid.
+    assert_eq!((celeste.game_id.as_deref(), celeste.appid), (Some("42"), None));
+    assert_eq!(celeste.platforms, vec!["PC (Linux)"]);
+    assert_eq!(celeste.playtime_minutes, None);
+    assert!(!celeste.installed);
+  }
+
+  #[test]
+  fn accepts_a_bare_array_and_rejects_other_json() {
+    let path = std::env::temp_dir().join(format!("playnite-array-{}.json", std::process::id()));
+    fs::write(&path, r#"[{"Name": "Hades"}]"#).unwrap();
+    let games = playnite_import(path.display().to_string());
+    fs::write(&path, r#"{"Items": []}"#).unwrap();
+    let other = playnite_import(path.display().to_string());
+    let _ = fs::remove_file(&path);
+    assert_eq!(games.unwrap().iter().map(|g| g.status.as_str()).collect::<Vec<_>>(), vec!["Owned"]);
+    assert!(other.is_err());
+  }
+}
@@ -0,0 +1,600 @@
+use crate::commands::{now_unix, steam_region, SteamPrice};
+use crate::library;
+use crate::steam::{fetch_prices, http_client};
+use crate::{breaker, offline};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use std::{collections::BTreeSet, thread, time::Duration};
+use tauri::Emitter;
+
+const PRICE_ALERT_EVENT: &str = "price-alert";
+const LAST_REFRESH_KEY: &str = "price_refresh_at";
+const DEFAULT_REFRESH_HOURS: i64 = 12;
+const SCHEDULER_TICK: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Serialize, Clone)]
+pub struct PricePoint {
+  pub observed_at: i64,
+  pub price: f64,
+  pub initial_price: Option<f64>,
+  pub discount_percent: Option<i64>,
+  pub currency: String,
+}
+
+#[derive(Serialize)]
+pub struct PriceSummary {
+  pub appid: u32,
+  pub region: String,
+  pub observations: usize,
+  pub current: Option<PricePoint>,
+  /// Lowest observed in the current price's currency; a region that changed
+  /// currency (Steam moved Turkey from TRY to USD) isn't compared across it.
+  pub lowest: Option<PricePoint>,
+  pub current_discount_percent: Option<i64>,
+  /// The current price is at (or below) the lowest ever observed.
+  pub is_lowest_ever: bool,
+}
+
+#[derive(Serialize)]
+pub struct PriceHistory {
+  pub summary: PriceSummary,
+  /// Oldest first.
+  pub points: Vec<PricePoint>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PriceAlert {
+  pub id: String,
+  pub appid: u32,
+  pub region: String,
+  pub target_price: f64,
+  /// Only prices in this currency trigger the alert. Defaults to the currency
+  /// of the latest observation when the alert is set, or the first one after.
+  pub currency: Option<String>,
+  pub created_at: i64,
+  pub triggered_at: Option<i64>,
+  pub triggered_price: Option<f64>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PriceAlertHit {
+  pub alert_id: String,
+  pub appid: u32,
+  pub region: String,
+  pub title: Option<String>,
+  pub price: f64,
+  pub currency: String,
+  pub target_price: f64,
+  pub discount_percent: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct PriceRefreshSummary {
+  pub regions: Vec<String>,
+  pub checked: usize,
+  pub priced: usize,
+  pub alerts: Vec<PriceAlertHit>,
+}
+
+fn env_num(key: &str, default: i64) -> i64 {
+  std::env::var(key)
+    .ok()
+    .and_then(|v| v.trim().parse().ok())
+    .unwrap_or(default)
+}
+
+fn point_from_row(r: &Row) -> rusqlite::Result<PricePoint> {
+  Ok(PricePoint {
+    observed_at: r.get(0)?,
+    price: r.get(1)?,
+    initial_price: r.get(2)?,
+    discount_percent: r.get(3)?,
+    currency: r.get(4)?,
+  })
+}
+
+const POINT_COLUMNS: &str = "observed_at, price, initial_price, discount_percent, currency";
+
+fn alert_from_row(r: &Row) -> rusqlite::Result<PriceAlert> {
+  Ok(PriceAlert {
+    id: r.get(0)?,
+    appid: r.get(1)?,
+    region: r.get(2)?,
+    target_price: r.get(3)?,
+    currency: r.get(4)?,
+    created_at: r.get(5)?,
+    triggered_at: r.get(6)?,
+    triggered_price: r.get(7)?,
+  })
+}
+
+const ALERT_COLUMNS: &str = "id, appid, region, target_price, currency, created_at, triggered_at, triggered_price";
+
+/// Appends one observation per priced app. Called wherever Steam prices are
+/// cached, so every lookup ends up in the history.
+pub(crate) fn record_prices(cc: &str, prices: &[(u32, Option<&SteamPrice>)]) -> Result<usize, String> {
+  let mut conn = library::open_db()?;
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  let now = now_unix();
+  let mut written = 0;
+  for (appid, price) in prices {
+    let Some(p) = price else { continue };
+    written += tx
+      .execute(
+        "INSERT OR REPLACE INTO price_history
+           (appid, region, observed_at, price, initial_price, discount_percent, currency)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+          appid,
+          cc,
+          now,
+          f64::from(p.price),
+          p.initial_price.map(f64::from),
+          p.discount_percent,
+          p.currency
+        ],
+      )
+      .map_err(|e| e.to_string())?;
+  }
+  tx.commit().map_err(|e| e.to_string())?;
+  Ok(written)
+}
+
+fn summary(conn: &Connection, appid: u32, cc: &str) -> Result<PriceSummary, String> {
+  let one = |order: &str, currency: Option<&str>| {
+    conn
+      .query_row(
+        &format!(
+          "SELECT {} FROM price_history WHERE appid = ?1 AND region = ?2 AND (?3 IS NULL OR currency = ?3)
+           ORDER BY {} LIMIT 1",
+          POINT_COLUMNS, order
+        ),
+        params![appid, cc, currency],
+        point_from_row,
+      )
+      .optional()
+      .map_err(|e| e.to_string())
+  };
+  let current = one("observed_at DESC", None)?;
+  // Earliest time the lowest price was seen, in the currency quoted now.
+  let lowest = match &current {
+    Some(c) => one("price ASC, observed_at ASC", Some(&c.currency))?,
+    None => None,
+  };
+  let observations: i64 = conn
+    .query_row(
+      "SELECT COUNT(*) FROM price_history WHERE appid = ?1 AND region = ?2",
+      params![appid, cc],
+      |r| r.get(0),
+    )
+    .map_err(|e| e.to_string())?;
+  let current_discount_percent = current.as_ref().and_then(|c| {
+    c.discount_percent.or_else(|| {
+      c.initial_price
+        .filter(|i| *i > c.price)
+        .map(|i| ((1.0 - c.price / i) * 100.0).round() as i64)
+    })
+  });
+  let is_lowest_ever = matches!((&current, &lowest), (Some(c), Some(l)) if c.price <= l.price);
+  Ok(PriceSummary {
+    appid,
+    region: cc.to_string(),
+    observations: observations as usize,
+    current,
+    lowest,
+    current_discount_percent,
+    is_lowest_ever,
+  })
+}
+
+fn title_for(conn: &Connection, appid: u32) -> Option<String> {
+  conn
+    .query_row("SELECT title FROM identities WHERE appid = ?1 LIMIT 1", [appid], |r| r.get(0))
+    .optional()
+    .ok()
+    .flatten()
+}
+
+/// Fires every armed alert whose latest observed price (since the alert was
+/// set) is at or below its target, marks it triggered and emits `price-alert`.
+/// Works from the stored history, so a hit recorded without an app handle is
+/// reported by the next check.
+pub(crate) fn check_alerts(app: &tauri::AppHandle) -> Vec<PriceAlertHit> {
+  let hits = library::open_db().and_then(|conn| trigger_alerts(&conn)).unwrap_or_default();
+  for hit in &hits {
+    let _ = app.emit(PRICE_ALERT_EVENT, hit.clone());
+  }
+  hits
+}
+
+/// Currency of the latest observation, or with `since` the first one at or
+/// after it.
+fn observed_currency(conn: &Connection, appid: u32, cc: &str, since: Option<i64>) -> Result<Option<String>, String> {
+  let (filter, order) = match since {
+    Some(_) => ("observed_at >= ?3", "ASC"),
+    None => ("?3 IS NULL", "DESC"),
+  };
+  conn
+    .query_row(
+      &format!(
+        "SELECT currency FROM price_history WHERE appid = ?1 AND region = ?2 AND {} ORDER BY observed_at {} LIMIT 1",
+        filter, order
+      ),
+      params![appid, cc, since],
+      |r| r.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn trigger_alerts(conn: &Connection) -> Result<Vec<PriceAlertHit>, String> {
+  let mut stmt = conn
+    .prepare(&format!(
+      "SELECT {} FROM price_alerts WHERE triggered_at IS NULL",
+      ALERT_COLUMNS
+    ))
+    .map_err(|e| e.to_string())?;
+  let armed = stmt
+    .query_map([], alert_from_row)
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string())?;
+  let mut hits = Vec::new();
+  for mut alert in armed {
+    if alert.currency.is_none() {
+      // Set before anything was observed: the first price seen fixes it.
+      alert.currency = observed_currency(conn, alert.appid, &alert.region, Some(alert.created_at))?;
+      if alert.currency.is_some() {
+        conn
+          .execute(
+            "UPDATE price_alerts SET currency = ?1 WHERE id = ?2",
+            params![alert.currency, alert.id],
+          )
+          .map_err(|e| e.to_string())?;
+      }
+    }
+    let latest = conn
+      .query_row(
+        &format!(
+          "SELECT {} FROM price_history WHERE appid = ?1 AND region = ?2 AND observed_at >= ?3
+           ORDER BY observed_at DESC LIMIT 1",
+          POINT_COLUMNS
+        ),
+        params![alert.appid, alert.region, alert.created_at],
+        point_from_row,
+      )
+      .optional()
+      .map_err(|e| e.to_string())?;
+    let Some(point) = latest else { continue };
+    let currency_ok = alert
+      .currency
+      .as_deref()
+      .is_none_or(|c| c.eq_ignore_ascii_case(&point.currency));
+    if !currency_ok || point.price > alert.target_price {
+      continue;
+    }
+    conn
+      .execute(
+        "UPDATE price_alerts SET triggered_at = ?1, triggered_price = ?2 WHERE id = ?3",
+        params![now_unix(), point.price, alert.id],
+      )
+      .map_err(|e| e.to_string())?;
+    hits.push(PriceAlertHit {
+      title: title_for(conn, alert.appid),
+      alert_id: alert.id,
+      appid: alert.appid,
+      region: alert.region,
+      price: point.price,
+      currency: point.currency,
+      target_price: alert.target_price,
+      discount_percent: point.discount_percent,
+    });
+  }
+  Ok(hits)
+}
+
+fn watch_region() -> String {
+  steam_region(std::env::var("PRICE_WATCH_REGION").ok().as_deref())
+}
+
+/// Anything with an armed alert in `cc`, plus the library's wishlisted appids
+/// when `cc` is the watch region; other regions only track their alerts.
+fn watched_appids(conn: &Connection, cc: &str, watch_region: &str) -> Result<Vec<u32>, String> {
+  let mut stmt = conn
+    .prepare(
+      "SELECT i.appid FROM library l JOIN identities i ON i.id = l.identity_id
+       WHERE ?1 = ?2 AND l.status = 'Wishlist' AND i.appid IS NOT NULL
+       UNION
+       SELECT appid FROM price_alerts WHERE region = ?1 AND triggered_at IS NULL",
+    )
+    .map_err(|e| e.to_string())?;
+  let ids = stmt
+    .query_map([cc, watch_region], |r| r.get(0))
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<u32>, _>>()
+    .map_err(|e| e.to_string());
+  ids
+}
+
+/// The region wishlist prices are watched in (`PRICE_WATCH_REGION`) plus every
+/// region that has an armed alert.
+fn watched_regions(conn: &Connection) -> Result<Vec<String>, String> {
+  let mut regions: BTreeSet<String> = BTreeSet::new();
+  regions.insert(watch_region());
+  let mut stmt = conn
+    .prepare("SELECT DISTINCT region FROM price_alerts WHERE triggered_at IS NULL")
+    .map_err(|e| e.to_string())?;
+  for region in stmt.query_map([], |r| r.get::<_, String>(0)).map_err(|e| e.to_string())? {
+    regions.insert(region.map_err(|e| e.to_string())?);
+  }
+  Ok(regions.into_iter().collect())
+}
+
+async fn refresh(app: &tauri::AppHandle, regions: Vec<String>) -> Result<PriceRefreshSummary, String> {
+  if offline::is_offline() {
+    return Err("Offline: prices not refreshed".into());
+  }
+  breaker::check("steam-store")?;
+  let client = http_client()?;
+  let mut checked = 0;
+  let mut priced = 0;
+  let watch_region = watch_region();
+  for cc in &regions {
+    let appids = watched_appids(&library::open_db()?, cc, &watch_region)?;
+    checked += appids.len();
+    // `fetch_prices` caches, and so records, every price it sees.
+    priced += fetch_prices(&client, &appids, cc).await?.len();
+  }
+  let conn = library::open_db()?;
+  library::set_setting(&conn, LAST_REFRESH_KEY, &now_unix().into())?;
+  Ok(PriceRefreshSummary {
+    regions,
+    checked,
+    priced,
+    alerts: check_alerts(app),
+  })
+}
+
+/// Refreshes watched prices every `PRICE_REFRESH_HOURS` (12 by default).
+pub fn start_scheduler(app: tauri::AppHandle) {
+  thread::spawn(move || loop {
+    let interval = env_num("PRICE_REFRESH_HOURS", DEFAULT_REFRESH_HOURS).max(1) * 3600;
+    let due = library::open_db()
+      .and_then(|conn| library::get_setting(&conn, LAST_REFRESH_KEY))
+      .map(|last| last.and_then(|v| v.as_i64()).is_none_or(|t| now_unix() - t >= interval))
+      .unwrap_or(false);
+    if due {
+      if let Ok(regions) = library::open_db().and_then(|conn| watched_regions(&conn)) {
+        let _ = tauri::async_runtime::block_on(refresh(&app, regions));
+      }
+    }
+    thread::sleep(SCHEDULER_TICK);
+  });
+}
+
+#[tauri::command]
+pub fn price_history(appid: u32, region: Option<String>, limit: Option<usize>) -> Result<PriceHistory, String> {
+  let cc = steam_region(region.as_deref());
+  let conn = library::open_db()?;
+  let mut stmt = conn
+    .prepare(&format!(
+      "SELECT {} FROM price_history WHERE appid = ?1 AND region = ?2 ORDER BY observed_at DESC LIMIT ?3",
+      POINT_COLUMNS
+    ))
+    .map_err(|e| e.to_string())?;
+  let limit = limit.map(|l| l as i64).unwrap_or(-1);
+  let mut points = stmt
+    .query_map(params![appid, cc, limit], point_from_row)
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string())?;
+  points.reverse();
+  Ok(PriceHistory {
+    summary: summary(&conn, appid, &cc)?,
+    points,
+  })
+}
+
+/// Current price, current discount and lowest-ever price for several apps.
+#[tauri::command]
+pub fn price_summaries(appids: Vec<u32>, region: Option<String>) -> Result<Vec<PriceSummary>, String> {
+  let cc = steam_region(region.as_deref());
+  let conn = library::open_db()?;
+  appids.into_iter().map(|appid| summary(&conn, appid, &cc)).collect()
+}
+
+fn set_alert(
+  conn: &Connection,
+  appid: u32,
+  cc: String,
+  target_price: f64,
+  currency: Option<String>,
+  now: i64,
+) -> Result<PriceAlert, String> {
+  let existing: Option<String> = conn
+    .query_row(
+      "SELECT id FROM price_alerts WHERE appid = ?1 AND region = ?2",
+      params![appid, cc],
+      |r| r.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())?;
+  // Without an explicit currency the alert follows the one prices are quoted in now.
+  let currency = match currency.map(|c| c.trim().to_uppercase()).filter(|c| !c.is_empty()) {
+    Some(c) => Some(c),
+    None => observed_currency(conn, appid, &cc, None)?,
+  };
+  let alert = PriceAlert {
+    id: existing.unwrap_or_else(|| library::new_id("alert")),
+    appid,
+    region: cc,
+    target_price,
+    currency,
+    created_at: now,
+    triggered_at: None,
+    triggered_price: None,
+  };
+  conn
+    .execute(
+      &format!(
+        "INSERT OR REPLACE INTO price_alerts ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, NULL, NULL)",
+        ALERT_COLUMNS
+      ),
+      params![alert.id, alert.appid, alert.region, alert.target_price, alert.currency, alert.created_at],
+    )
+    .map_err(|e| e.to_string())?;
+  Ok(alert)
+}
+
+/// Sets (or re-arms) the alert for `appid` in `region`.
+#[tauri::command]
+pub fn price_alert_set(
+  appid: u32,
+  region: Option<String>,
+  target_price: f64,
+  currency: Option<String>,
+) -> Result<PriceAlert, String> {
+  if !(target_price.is_finite() && target_price >= 0.0) {
+    return Err(format!("Invalid target price: {}", target_price));
+  }
+  let cc = steam_region(region.as_deref());
+  let conn = library::open_db()?;
+  set_alert(&conn, appid, cc, target_price, currency, now_unix())
+}
+
+#[tauri::command]
+pub fn price_alert_list() -> Result<Vec<PriceAlert>, String> {
+  let conn = library::open_db()?;
+  let mut stmt = conn
+    .prepare(&format!("SELECT {} FROM price_alerts ORDER BY created_at DESC", ALERT_COLUMNS))
+    .map_err(|e| e.to_string())?;
+  let alerts = stmt
+    .query_map([], alert_from_row)
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string());
+  alerts
+}
+
+#[tauri::command]
+pub fn price_alert_delete(id: String) -> Result<bool, String> {
+  let conn = library::open_db()?;
+  let n = conn
+    .execute("DELETE FROM price_alerts WHERE id = ?1", [id])
+    .map_err(|e| e.to_string())?;
+  Ok(n > 0)
+}
+
+/// Fetches current prices for wishlisted and alerted apps right away instead
+/// of waiting for the scheduler. `region` limits the refresh to one region.
+#[tauri::command]
+pub async fn price_refresh_watched(app: tauri::AppHandle, region: Option<String>) -> Result<PriceRefreshSummary, String> {
+  let regions = match region {
+    Some(r) => vec![steam_region(Some(&r))],
+    None => watched_regions(&library::open_db()?)?,
+  };
+  refresh(&app, regions).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const APP: u32 = 620;
+
+  fn prices_db() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn
+      .execute_batch(
+        "CREATE TABLE price_history (appid INTEGER NOT NULL, region TEXT NOT NULL, observed_at INTEGER NOT NULL,
+           price REAL NOT NULL, initial_price REAL, discount_percent INTEGER, currency TEXT NOT NULL,
+           PRIMARY KEY (appid, region, observed_at));
+         CREATE TABLE price_alerts (id TEXT PRIMARY KEY, appid INTEGER NOT NULL, region TEXT NOT NULL,
+           target_price REAL NOT NULL, currency TEXT, created_at INTEGER NOT NULL, triggered_at INTEGER,
+           triggered_price REAL);",
+      )
+      .unwrap();
+    conn
+  }
+
+  fn observe(conn: &Connection, cc: &str, at: i64, price: f64, currency: &str) {
+    conn
+      .execute(
+        "INSERT INTO price_history (appid, region, observed_at, price, currency) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![APP, cc, at, price, currency],
+      )
+      .unwrap();
+  }
+
+  fn alert_currency(conn: &Connection, id: &str) -> Option<String> {
+    conn
+      .query_row("SELECT currency FROM price_alerts WHERE id = ?1", [id], |r| r.get(0))
+      .unwrap()
+  }
+
+  #[test]
+  fn lowest_price_stays_within_the_current_currency() {
+    let conn = prices_db();
+    observe(&conn, "tr", 100, 499.0, "TRY");
+    observe(&conn, "tr", 200, 249.0, "TRY");
+    observe(&conn, "tr", 300, 19.99, "USD");
+    let s = summary(&conn, APP, "tr").unwrap();
+    assert_eq!(s.observations, 3);
+    assert_eq!(s.current.as_ref().map(|p| p.currency.as_str()), Some("USD"));
+    assert_eq!(s.lowest.as_ref().map(|p| (p.price, p.observed_at)), Some((19.99, 300)));
+    assert!(s.is_lowest_ever);
+
+    observe(&conn, "tr", 400, 24.99, "USD");
+    let s = summary(&conn, APP, "tr").unwrap();
+    assert_eq!(s.lowest.as_ref().map(|p| (p.price, p.currency.as_str())), Some((19.99, "USD")));
+    assert!(!s.is_lowest_ever);
+
+    assert!(summary(&conn, APP, "us").unwrap().current.is_none());
+  }
+
+  #[test]
+  fn alert_set_before_any_price_takes_the_first_currency() {
+    let conn = prices_db();
+    let alert = set_alert(&conn, APP, "tr".into(), 20.0, None, 1000).unwrap();
+    assert_eq!(alert.currency, None);
+    assert!(trigger_alerts(&conn).unwrap().is_empty());
+
+    // The first price is above target but pins the currency to TRY...
+    observe(&conn, "tr", 1100, 499.0, "TRY");
+    assert!(trigger_alerts(&conn).unwrap().is_empty());
+    assert_eq!(alert_currency(&conn, &alert.id).as_deref(), Some("TRY"));
+
+    // ...so a cheap-looking USD price after the switch doesn't fire it.
+    observe(&conn, "tr", 1200, 9.99, "USD");
+    assert!(trigger_alerts(&conn).unwrap().is_empty());
+
+    let other = set_alert(&conn, APP, "us".into(), 20.0, None, 1000).unwrap();
+    observe(&conn, "us", 1300, 14.99, "USD");
+    let hits = trigger_alerts(&conn).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!((hits[0].alert_id.as_str(), hits[0].price), (other.id.as_str(), 14.99));
+    assert_eq!(alert_currency(&conn, &other.id).as_deref(), Some("USD"));
+  }
+
+  #[test]
+  fn rearmed_alert_ignores_prices_seen_before_it() {
+    let conn = prices_db();
+    observe(&conn, "us", 100, 9.99, "USD");
+    let first = set_alert(&conn, APP, "us".into(), 10.0, None, 50).unwrap();
+    assert_eq!(first.currency.as_deref(), Some("USD"));
+    assert_eq!(trigger_alerts(&conn).unwrap().len(), 1);
+    assert!(trigger_alerts(&conn).unwrap().is_empty());
+
+    let rearmed = set_alert(&conn, APP, "us".into(), 10.0, None, 200).unwrap();
+    assert_eq!(rearmed.id, first.id);
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM price_alerts", [], |r| r.get(0)).unwrap();
+    assert_eq!(count, 1);
+    assert!(trigger_alerts(&conn).unwrap().is_empty());
+
+    observe(&conn, "us", 300, 12.99, "USD");
+    assert!(trigger_alerts(&conn).unwrap().is_empty());
+    observe(&conn, "us", 400, 7.49, "USD");
+    let hits = trigger_alerts(&conn).unwrap();
+    assert_eq!(hits.iter().map(|h| h.price).collect::<Vec<_>>(), vec![7.49]);
+  }
+}
@@ -0,0 +1,221 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{
+  collections::HashMap,
+  sync::Mutex,
+  thread,
+  time::{Duration, Instant},
+};
+
+/// Token-bucket limit for one provider: `requests` per `per_secs`, with at
+/// most `burst` requests allowed back to back.
+#[derive(Serialize, Clone, Copy)]
+pub struct RateLimit {
+  pub requests: u32,
+  pub per_secs: u32,
+  pub burst: u32,
+}
+
+impl RateLimit {
+  const fn new(requests: u32, per_secs: u32, burst: u32) -> Self {
+    Self { requests, per_secs, burst }
+  }
+
+  fn refill_per_sec(&self) -> f64 {
+    self.requests.max(1) as f64 / self.per_secs.max(1) as f64
+  }
+}
+
+// Steam's appdetails bans at roughly 200 requests / 5 minutes; stay well under.
+const STEAM_STORE_LIMIT: RateLimit = RateLimit::new(150, 300, 10);
+const STEAM_API_LIMIT: RateLimit = RateLimit::new(100, 60, 10);
+const HLTB_LIMIT: RateLimit = RateLimit::new(30, 60, 3);
+const OPENCRITIC_LIMIT: RateLimit = RateLimit::new(60, 60, 4);
+const DEFAULT_LIMIT: RateLimit = RateLimit::new(60, 60, 5);
+
+pub const PROVIDERS: [&str; 4] = ["steam-store", "steam-api", "hltb", "opencritic"];
+
+struct Bucket {
+  limit: RateLimit,
+  tokens: f64,
+  last: Instant,
+}
+
+impl Bucket {
+  fn new(limit: RateLimit) -> Self {
+    Self { limit, tokens: limit.burst.max(1) as f64, last: Instant::now() }
+  }
+
+  /// Takes one token and returns how long the caller must wait before
+  /// sending. Tokens may go negative so concurrent callers queue up in order.
+  fn reserve(&mut self) -> Duration {
+    let now = Instant::now();
+    let rate = self.limit.refill_per_sec();
+    let elapsed = now.duration_since(self.last).as_secs_f64();
+    self.tokens = (self.tokens + elapsed * rate).min(self.limit.burst.max(1) as f64);
+    self.last = now;
+    self.tokens -= 1.0;
+    if self.tokens >= 0.0 {
+      Duration::ZERO
+    } else {
+      Duration::from_secs_f64(-self.tokens / rate)
+    }
+  }
+}
+
+static BUCKETS: Lazy<Mutex<HashMap<&'static str, Bucket>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static OVERRIDES: Lazy<Mutex<HashMap<&'static str, RateLimit>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Maps a request host onto the provider whose limit applies to it.
+pub fn provider_for_host(host: &str) -> &'static str {
+  let host = host.to_ascii_lowercase();
+  if host == "store.steampowered.com" {
+    "steam-store"
+  } else if host == "api.steampowered.com" {
+    "steam-api"
+  } else if host.ends_with("howlongtobeat.com") {
+    "hltb"
+  } else if host.contains("opencritic") {
+    "opencritic"
+  } else {
+    "other"
+  }
+}
+
+fn provider_for_url(url: &str) -> &'static str {
+  // A custom storefront base (STEAM_STORE_BASE_URL) still shares Steam's budget.
+  if let Ok(base) = std::env::var("STEAM_STORE_BASE_URL") {
+    let base = base.trim().trim_end_matches('/');
+    if !base.is_empty() && url.starts_with(base) {
+      return "steam-store";
+    }
+  }
+  reqwest::Url::parse(url)
+    .ok()
+    .and_then(|u| u.host_str().map(provider_for_host))
+    .unwrap_or("other")
+}
+
+fn env_var_for(provider: &str) -> Option<&'static str> {
+  match provider {
+    "steam-store" => Some("STEAM_STORE_RATE_LIMIT"),
+    "steam-api" => Some("STEAM_API_RATE_LIMIT"),
+    "hltb" => Some("HLTB_RATE_LIMIT"),
+    "opencritic" => Some("OPENCRITIC_RATE_LIMIT"),
+    _ => None,
+  }
+}
+
+/// Parses `"<requests>/<seconds>"` with an optional `"@<burst>"` suffix,
+/// e.g. `"150/300@10"`.
+fn parse_limit(text: &str) -> Option<RateLimit> {
+  let (rate, burst) = match text.trim().split_once('@') {
+    Some((rate, burst)) => (rate, Some(burst.trim().parse::<u32>().ok()?)),
+    None => (text.trim(), None),
+  };
+  let (requests, per_secs) = rate.split_once('/')?;
+  let requests = requests.trim().parse::<u32>().ok()?;
+  let per_secs = per_secs.trim().parse::<u32>().ok()?;
+  if requests == 0 || per_secs == 0 {
+    return None;
+  }
+  Some(RateLimit::new(requests, per_secs, burst.unwrap_or(requests.min(10)).max(1)))
+}
+
+fn default_limit(provider: &str) -> RateLimit {
+  match provider {
+    "steam-store" => STEAM_STORE_LIMIT,
+    "steam-api" => STEAM_API_LIMIT,
+    "hltb" => HLTB_LIMIT,
+    "opencritic" => OPENCRITIC_LIMIT,
+    _ => DEFAULT_LIMIT,
+  }
+}
+
+/// Effective limit: runtime override, then environment, then built-in default.
+pub fn limit_for(provider: &str) -> RateLimit {
+  if let Some(limit) = OVERRIDES.lock().ok().and_then(|o| o.get(provider).copied()) {
+    return limit;
+  }
+  env_var_for(provider)
+    .and_then(|var| std::env::var(var).ok())
+    .and_then(|v| parse_limit(&v))
+    .unwrap_or_else(|| default_limit(provider))
+}
+
+fn reserve(provider: &'static str) -> Duration {
+  let limit = limit_for(provider);
+  let mut buckets = match BUCKETS.lock() {
+    Ok(b) => b,
+    Err(poisoned) => poisoned.into_inner(),
+  };
+  buckets
+    .entry(provider)
+    .or_insert_with(|| Bucket::new(limit))
+    .reserve()
+}
+
+/// Waits until a request to `url` is allowed by its provider's bucket.
+pub async fn acquire(url: &str) {
+  let wait = reserve(provider_for_url(url));
+  if !wait.is_zero() {
+    tokio::time::sleep(wait).await;
+  }
+}
+
+/// Blocking variant of [`acquire`] for the `reqwest::blocking` callers.
+pub fn acquire_blocking(url: &str) {
+  let wait = reserve(provider_for_url(url));
+  if !wait.is_zero() {
+    thread::sleep(wait);
+  }
+}
+
+fn static_provider(provider: &str) -> Option<&'static str> {
+  PROVIDERS.iter().copied().find(|p| *p == provider)
+}
+
+#[derive(Serialize)]
+pub struct ProviderRateLimit {
+  pub provider: String,
+  pub limit: RateLimit,
+}
+
+#[tauri::command]
+pub fn get_rate_limits() -> Vec<ProviderRateLimit> {
+  PROVIDERS
+    .iter()
+    .map(|p| ProviderRateLimit { provider: (*p).into(), limit: limit_for(p) })
+    .collect()
+}
+
+/// Overrides a provider's limit for this session. Passing `None` for
+/// `requests` restores the environment/default limit.
+#[tauri::command]
+pub fn set_rate_limit(
+  provider: String,
+  requests: Option<u32>,
+  per_secs: Option<u32>,
+  burst: Option<u32>,
+) -> Result<ProviderRateLimit, String> {
+  let key = static_provider(provider.trim())
+    .ok_or_else(|| format!("Unknown provider '{}'", provider))?;
+  {
+    let mut overrides = OVERRIDES.lock().map_err(|e| e.to_string())?;
+    match requests {
+      Some(requests) if requests > 0 => {
+        let per_secs = per_secs.unwrap_or(60).max(1);
+        let burst = burst.unwrap_or(requests.min(10)).max(1);
+        overrides.insert(key, RateLimit::new(requests, per_secs, burst));
+      }
+      _ => {
+        overrides.remove(key);
+      }
+    }
+  }
+  // Rebuild the bucket so the new limit takes effect immediately.
+  if let Ok(mut buckets) = BUCKETS.lock() {
+    buckets.remove(key);
+  }
+  Ok(ProviderRateLimit { provider: key.into(), limit: limit_for(key) })
+}
@@ -0,0 +1,679 @@
+use crate::commands::{
+  cache_steam_prices, is_expired, now_unix, parse_steam_price, read_cache_map, steam_prices_request, steam_region,
+  steam_store_base, update_cache_map, SteamPrice, USER_AGENT,
+};
+use crate::{breaker, offline, price_history, ratelimit};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+const WISHLIST_MAX_PAGES: u32 = 50;
+const PRICE_BATCH_SIZE: usize = 50;
+const STEAM_API_BASE: &str = "https://api.steampowered.com";
+const PRIVATE_PROFILE_ERROR: &str =
+  "Steam profile is private: set Game details to Public in Steam's privacy settings to read achievements";
+const ACHIEVEMENTS_CACHE_FILE: &str = "steam_achievements_cache.json";
+const SCHEMA_CACHE_FILE: &str = "steam_schema_cache.json";
+const ACHIEVEMENTS_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+const SCHEMA_CACHE_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+const METADATA_CACHE_FILE: &str = "steam_metadata_cache.json";
+// Store facts (genres, developers, release date) rarely change.
+const METADATA_CACHE_TTL_SECS: i64 = 90 * 24 * 60 * 60;
+
+#[derive(Serialize)]
+pub struct WishlistItem {
+  pub appid: u32,
+  pub name: String,
+  pub added: Option<i64>,
+  /// Steam's wishlist rank; 0 means unranked.
+  pub priority: Option<u32>,
+  pub price: Option<SteamPrice>,
+}
+
+/// A game owned on some store, in the shape every library importer returns.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OwnedGame {
+  /// "steam" | "gog" | "epic" | ...
+  pub store: String,
+  /// Store-specific id (appid for Steam, product id elsewhere).
+  pub external_id: String,
+  /// GOG Galaxy style `<platform>_<id>` key, when the source has one.
+  pub release_key: Option<String>,
+  pub appid: Option<u32>,
+  pub name: String,
+  pub playtime_minutes: Option<u32>,
+  pub last_played: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AchievementUnlock {
+  pub api_name: String,
+  pub display_name: Option<String>,
+  pub unlocked_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AchievementProgress {
+  pub appid: u32,
+  pub unlocked: u32,
+  pub total: u32,
+  pub percent: Option<f32>,
+  pub last_unlock: Option<i64>,
+  pub unlocks: Vec<AchievementUnlock>,
+  pub stale: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct AchievementCacheEntry {
+  progress: AchievementProgress,
+  ts: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SchemaCacheEntry {
+  /// api name -> display name
+  names: HashMap<String, String>,
+  ts: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SteamPlatforms {
+  pub windows: bool,
+  pub mac: bool,
+  pub linux: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SteamAppMetadata {
+  pub appid: u32,
+  pub name: String,
+  #[serde(rename = "type")]
+  pub app_type: Option<String>,
+  pub genres: Vec<String>,
+  pub categories: Vec<String>,
+  pub release_date: Option<String>,
+  pub coming_soon: bool,
+  pub developers: Vec<String>,
+  pub publishers: Vec<String>,
+  pub platforms: SteamPlatforms,
+  pub header_image: Option<String>,
+  pub controller_support: Option<String>,
+  pub metacritic_score: Option<u32>,
+  /// "verified" | "playable" | "unsupported" | "unknown"
+  pub deck_compatibility: Option<String>,
+  pub stale: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct MetadataCacheEntry {
+  meta: SteamAppMetadata,
+  ts: i64,
+}
+
+#[derive(Deserialize)]
+struct WishlistEntry {
+  name: Option<String>,
+  added: Option<i64>,
+  priority: Option<Value>,
+}
+
+pub(crate) fn http_client() -> Result<reqwest::Client, String> {
+  reqwest::Client::builder()
+    .user_agent(USER_AGENT)
+    .build()
+    .map_err(|e| e.to_string())
+}
+
+/// `76561198…` ids go through `/profiles/`, anything else is a vanity name.
+fn profile_path(steam_id: &str) -> String {
+  let id = steam_id.trim();
+  if id.len() == 17 && id.chars().all(|c| c.is_ascii_digit()) {
+    format!("profiles/{}", id)
+  } else {
+    format!("id/{}", urlencoding::encode(id))
+  }
+}
+
+async fn get_store_json(client: &reqwest::Client, url: &str) -> Result<Value, String> {
+  ratelimit::acquire(url).await;
+  let res = client.get(url).send().await.map_err(|e| offline::note_error(&e))?;
+  offline::note_online();
+  if !res.status().is_success() {
+    return Err(format!("Steam HTTP {}", res.status()));
+  }
+  res.json::<Value>().await.map_err(|e| e.to_string())
+}
+
+fn priority_of(v: &Option<Value>) -> Option<u32> {
+  match v {
+    Some(Value::Number(n)) => n.as_u64().map(|n| n as u32),
+    Some(Value::String(s)) => s.trim().parse::<u32>().ok(),
+    _ => None,
+  }
+}
+
+async fn fetch_wishlist_pages(client: &reqwest::Client, steam_id: &str) -> Result<Vec<(u32, WishlistEntry)>, String> {
+  let base = steam_store_base();
+  let path = profile_path(steam_id);
+  let mut out = Vec::new();
+  for page in 0..WISHLIST_MAX_PAGES {
+    let url = format!("{}/wishlist/{}/wishlistdata/?p={}", base, path, page);
+    let body = breaker::observe("steam-store", get_store_json(client, &url).await)?;
+    let map = match body {
+      Value::Object(map) => map,
+      // An empty page comes back as `[]`.
+      _ => break,
+    };
+    if map.get("success").and_then(|v| v.as_i64()) == Some(2) {
+      return Err("Steam wishlist is private or the profile does not exist".into());
+    }
+    if map.is_empty() {
+      break;
+    }
+    for (key, value) in map {
+      let Ok(appid) = key.parse::<u32>() else { continue };
+      if let Ok(entry) = serde_json::from_value::<WishlistEntry>(value) {
+        out.push((appid, entry));
+      }
+    }
+  }
+  Ok(out)
+}
+
+pub(crate) async fn fetch_prices(client: &reqwest::Client, appids: &[u32], cc: &str) -> Result<HashMap<u32, SteamPrice>, String> {
+  let mut prices = HashMap::new();
+  for chunk in appids.chunks(PRICE_BATCH_SIZE) {
+    let body = breaker::observe("steam-store", steam_prices_request(client, chunk, cc).await)?;
+    let mut fetched = Vec::with_capacity(chunk.len());
+    for appid in chunk {
+      // A single delisted app shouldn't drop the whole batch.
+      let price = parse_steam_price(&body, *appid).ok().flatten();
+      fetched.push((*appid, price));
+    }
+    cache_steam_prices(cc, &fetched.iter().map(|(id, p)| (*id, p.as_ref())).collect::<Vec<_>>());
+    prices.extend(fetched.into_iter().filter_map(|(id, p)| p.map(|p| (id, p))));
+  }
+  Ok(prices)
+}
+
+/// Fetches a public Steam wishlist with each entry's current price in `region`.
+#[tauri::command]
+pub async fn get_steam_wishlist(
+  app: tauri::AppHandle,
+  steam_id: String,
+  region: Option<String>,
+) -> Result<Vec<WishlistItem>, String> {
+  if steam_id.trim().is_empty() {
+    return Err("Steam id is required".into());
+  }
+  if offline::is_offline() {
+    return Err("Offline: the Steam wishlist needs a network connection".into());
+  }
+  breaker::check("steam-store")?;
+  let client = http_client()?;
+  let cc = steam_region(region.as_deref());
+
+  let entries = fetch_wishlist_pages(&client, &steam_id).await?;
+  let appids: Vec<u32> = entries.iter().map(|(id, _)| *id).collect();
+  // Prices are a bonus on top of the list: if the store API is down or the
+  // breaker opens mid-way, still return the wishlist, just without prices.
+  let mut prices = match fetch_prices(&client, &appids, &cc).await {
+    Ok(prices) => {
+      price_history::check_alerts(&app);
+      prices
+    }
+    Err(e) => {
+      eprintln!("steam wishlist: prices unavailable for {}: {}", steam_id, e);
+      HashMap::new()
+    }
+  };
+
+  let mut items: Vec<WishlistItem> = entries
+    .into_iter()
+    .map(|(appid, entry)| WishlistItem {
+      appid,
+      name: entry.name.unwrap_or_default(),
+      added: entry.added.filter(|ts| *ts > 0),
+      priority: priority_of(&entry.priority),
+      price: prices.remove(&appid),
+    })
+    .collect();
+  // Ranked entries first in rank order, then unranked by most recently added.
+  items.sort_by(|a, b| {
+    let rank = |p: Option<u32>| match p {
+      Some(0) | None => u32::MAX,
+      Some(n) => n,
+    };
+    rank(a.priority)
+      .cmp(&rank(b.priority))
+      .then(b.added.cmp(&a.added))
+  });
+  Ok(items)
+}
+
+fn resolve_api_key(explicit: Option<String>) -> Result<String, String> {
+  explicit
+    .filter(|k| !k.trim().is_empty())
+    .or_else(|| std::env::var("STEAM_API_KEY").ok().filter(|k| !k.trim().is_empty()))
+    .map(|k| k.trim().to_string())
+    .ok_or_else(|| "Steam Web API key is not set (STEAM_API_KEY)".to_string())
+}
+
+async fn get_api_json(client: &reqwest::Client, url: &str) -> Result<Value, String> {
+  ratelimit::acquire(url).await;
+  let res = client.get(url).send().await.map_err(|e| offline::note_error(&e))?;
+  offline::note_online();
+  let status = res.status();
+  // GetPlayerAchievements answers 403 "Profile is not public" for private
+  // profiles; other 403s (e.g. a bad key) stay ordinary failures.
+  if status == reqwest::StatusCode::FORBIDDEN {
+    let body = res.text().await.unwrap_or_default();
+    if body.to_ascii_lowercase().contains("not public") {
+      return Err(PRIVATE_PROFILE_ERROR.into());
+    }
+    return Err(format!("Steam Web API HTTP {}", status));
+  }
+  if !status.is_success() && status != reqwest::StatusCode::BAD_REQUEST {
+    return Err(format!("Steam Web API HTTP {}", status));
+  }
+  let body = res.json::<Value>().await;
+  if status == reqwest::StatusCode::BAD_REQUEST {
+    // A 400 is only expected for games without stats; anything else (bad app
+    // id, malformed request) is a real failure.
+    return match body {
+      Ok(body) if playerstats_error(&body).is_some_and(|e| is_no_stats(&e)) => Ok(body),
+      Ok(body) => Err(format!(
+        "Steam Web API HTTP {}: {}",
+        status,
+        playerstats_error(&body).unwrap_or_default()
+      )),
+      Err(_) => Err(format!("Steam Web API HTTP {}", status)),
+    };
+  }
+  let body = body.map_err(|e| e.to_string())?;
+  match playerstats_error(&body) {
+    Some(e) if !is_no_stats(&e) => Err(format!("Steam Web API: {}", e)),
+    _ => Ok(body),
+  }
+}
+
+/// The error ISteamUserStats reports in `playerstats`, either as an `error`
+/// message or a bare `success: false`.
+fn playerstats_error(body: &Value) -> Option<String> {
+  let stats = body.get("playerstats")?;
+  let message = stats.get("error").and_then(|v| v.as_str());
+  let failed = stats.get("success").and_then(|v| v.as_bool()) == Some(false);
+  match message {
+    Some(m) => Some(m.to_string()),
+    None if failed => Some("request failed".into()),
+    None => None,
+  }
+}
+
+fn is_no_stats(error: &str) -> bool {
+  error.to_ascii_lowercase().contains("requested app has no stats")
+}
+
+/// Like `breaker::observe`, but a private profile is the user's setting, not
+/// an outage, so it doesn't count towards opening the `steam-api` breaker.
+fn observe_api<T>(res: Result<T, String>) -> Result<T, String> {
+  match res {
+    Err(e) if e == PRIVATE_PROFILE_ERROR => Err(e),
+    res => breaker::observe("steam-api", res),
+  }
+}
+
+pub(crate) async fn fetch_owned_games(
+  client: &reqwest::Client,
+  api_key: &str,
+  steamid64: &str,
+) -> Result<Vec<OwnedGame>, String> {
+  let url = format!(
+    "{}/IPlayerService/GetOwnedGames/v1/?key={}&steamid={}&include_appinfo=1&include_played_free_games=1&format=json",
+    STEAM_API_BASE,
+    urlencoding::encode(api_key),
+    urlencoding::encode(steamid64.trim())
+  );
+  let body = observe_api(get_api_json(client, &url).await)?;
+  let games = body
+    .pointer("/response/games")
+    .and_then(|v| v.as_array())
+    .cloned()
+    .unwrap_or_default();
+  Ok(
+    games
+      .iter()
+      .filter_map(|g| {
+        let appid = g.get("appid")?.as_u64()? as u32;
+        Some(OwnedGame {
+          store: "steam".into(),
+          external_id: appid.to_string(),
+          release_key: None,
+          appid: Some(appid),
+          name: g.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+          playtime_minutes: g.get("playtime_forever").and_then(|v| v.as_u64()).map(|v| v as u32),
+          last_played: g.get("rtime_last_played").and_then(|v| v.as_i64()).filter(|ts| *ts > 0),
+        })
+      })
+      .collect(),
+  )
+}
+
+/// Owned games for a public Steam profile via `IPlayerService/GetOwnedGames`.
+#[tauri::command]
+pub async fn get_owned_games(steam_api_key: Option<String>, steamid64: String) -> Result<Vec<OwnedGame>, String> {
+  if offline::is_offline() {
+    return Err("Offline: owned games need a network connection".into());
+  }
+  let api_key = resolve_api_key(steam_api_key)?;
+  breaker::check("steam-api")?;
+  let client = http_client()?;
+  fetch_owned_games(&client, &api_key, &steamid64).await
+}
+
+async fn fetch_schema_names(
+  client: &reqwest::Client,
+  api_key: &str,
+  appid: u32,
+  cache: &mut HashMap<String, SchemaCacheEntry>,
+) -> Result<HashMap<String, String>, String> {
+  let key = appid.to_string();
+  if let Some(entry) = cache.get(&key) {
+    if !is_expired(entry.ts, SCHEMA_CACHE_TTL_SECS) {
+      return Ok(entry.names.clone());
+    }
+  }
+  let url = format!(
+    "{}/ISteamUserStats/GetSchemaForGame/v2/?key={}&appid={}",
+    STEAM_API_BASE,
+    urlencoding::encode(api_key),
+    appid
+  );
+  let body = observe_api(get_api_json(client, &url).await)?;
+  let names: HashMap<String, String> = body
+    .pointer("/game/availableGameStats/achievements")
+    .and_then(|v| v.as_array())
+    .map(|list| {
+      list
+        .iter()
+        .filter_map(|a| {
+          let name = a.get("name")?.as_str()?.to_string();
+          let display = a.get("displayName").and_then(|v| v.as_str()).unwrap_or(&name).to_string();
+          Some((name, display))
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  cache.insert(key, SchemaCacheEntry { names: names.clone(), ts: now_unix() });
+  Ok(names)
+}
+
+async fn fetch_achievements(
+  client: &reqwest::Client,
+  api_key: &str,
+  steamid64: &str,
+  appid: u32,
+  schema_cache: &mut HashMap<String, SchemaCacheEntry>,
+) -> Result<AchievementProgress, String> {
+  let url = format!(
+    "{}/ISteamUserStats/GetPlayerAchievements/v1/?key={}&steamid={}&appid={}",
+    STEAM_API_BASE,
+    urlencoding::encode(api_key),
+    urlencoding::encode(steamid64.trim()),
+    appid
+  );
+  let body = observe_api(get_api_json(client, &url).await)?;
+  let stats = body.get("playerstats").cloned().unwrap_or(Value::Null);
+  let player: Vec<Value> = stats
+    .get("achievements")
+    .and_then(|v| v.as_array())
+    .cloned()
+    .unwrap_or_default();
+  if player.is_empty() {
+    // No stats for this app: report zero rather than fail the batch.
+    return Ok(AchievementProgress {
+      appid,
+      unlocked: 0,
+      total: 0,
+      percent: None,
+      last_unlock: None,
+      unlocks: Vec::new(),
+      stale: false,
+    });
+  }
+
+  let names = fetch_schema_names(client, api_key, appid, schema_cache).await.unwrap_or_default();
+  let mut unlocks: Vec<AchievementUnlock> = player
+    .iter()
+    .filter(|a| a.get("achieved").and_then(|v| v.as_u64()) == Some(1))
+    .filter_map(|a| {
+      let api_name = a.get("apiname")?.as_str()?.to_string();
+      Some(AchievementUnlock {
+        display_name: names.get(&api_name).cloned(),
+        unlocked_at: a.get("unlocktime").and_then(|v| v.as_i64()).filter(|ts| *ts > 0),
+        api_name,
+      })
+    })
+    .collect();
+  unlocks.sort_by_key(|u| std::cmp::Reverse(u.unlocked_at));
+
+  let total = names.len().max(player.len()) as u32;
+  let unlocked = unlocks.len() as u32;
+  Ok(AchievementProgress {
+    appid,
+    unlocked,
+    total,
+    percent: if total > 0 { Some(unlocked as f32 * 100.0 / total as f32) } else { None },
+    last_unlock: unlocks.iter().filter_map(|u| u.unlocked_at).max(),
+    unlocks,
+    stale: false,
+  })
+}
+
+/// Achievement completion per appid from the cache only, for library-wide
+/// scoring that must not call the Web API.
+pub(crate) fn cached_achievement_percents(steamid64: &str) -> HashMap<u32, f32> {
+  let prefix = format!("{}:", steamid64.trim());
+  read_cache_map::<AchievementCacheEntry>(ACHIEVEMENTS_CACHE_FILE)
+    .into_iter()
+    .filter(|(k, _)| k.starts_with(&prefix))
+    .filter_map(|(_, e)| Some((e.progress.appid, e.progress.percent?)))
+    .collect()
+}
+
+/// Unlocked/total achievements per appid; without `appids` it covers every
+/// owned game. Results are cached per account for a day.
+#[tauri::command]
+pub async fn get_steam_achievements(
+  steam_api_key: Option<String>,
+  steamid64: String,
+  appids: Option<Vec<u32>>,
+) -> Result<Vec<AchievementProgress>, String> {
+  let cache = read_cache_map::<AchievementCacheEntry>(ACHIEVEMENTS_CACHE_FILE);
+  let cache_key = |appid: u32| format!("{}:{}", steamid64.trim(), appid);
+
+  if offline::is_offline() {
+    let wanted: Option<Vec<u32>> = appids;
+    let prefix = format!("{}:", steamid64.trim());
+    return Ok(
+      cache
+        .iter()
+        .filter(|(k, e)| {
+          k.starts_with(&prefix) && wanted.as_ref().is_none_or(|ids| ids.contains(&e.progress.appid))
+        })
+        .map(|(_, e)| AchievementProgress {
+          stale: is_expired(e.ts, ACHIEVEMENTS_CACHE_TTL_SECS),
+          ..e.progress.clone()
+        })
+        .collect(),
+    );
+  }
+
+  let api_key = resolve_api_key(steam_api_key)?;
+  breaker::check("steam-api")?;
+  let client = http_client()?;
+  let appids = match appids {
+    Some(ids) => ids,
+    None => fetch_owned_games(&client, &api_key, &steamid64)
+      .await?
+      .into_iter()
+      .filter_map(|g| g.appid)
+      .collect(),
+  };
+
+  let mut schema_cache = read_cache_map::<SchemaCacheEntry>(SCHEMA_CACHE_FILE);
+  let mut fresh: HashMap<String, AchievementCacheEntry> = HashMap::new();
+  let persist = |fresh: HashMap<String, AchievementCacheEntry>, schema: HashMap<String, SchemaCacheEntry>| {
+    update_cache_map(ACHIEVEMENTS_CACHE_FILE, |c| c.extend(fresh));
+    update_cache_map(SCHEMA_CACHE_FILE, |c| c.extend(schema));
+  };
+  let mut out = Vec::with_capacity(appids.len());
+  for appid in appids {
+    if let Some(entry) = cache.get(&cache_key(appid)) {
+      if !is_expired(entry.ts, ACHIEVEMENTS_CACHE_TTL_SECS) {
+        out.push(entry.progress.clone());
+        continue;
+      }
+    }
+    match fetch_achievements(&client, &api_key, &steamid64, appid, &mut schema_cache).await {
+      Ok(progress) => {
+        fresh.insert(cache_key(appid), AchievementCacheEntry { progress: progress.clone(), ts: now_unix() });
+        out.push(progress);
+      }
+      Err(e) => {
+        // Persist what we have before giving up so a retry resumes where this stopped.
+        persist(fresh, schema_cache);
+        return Err(e);
+      }
+    }
+  }
+  persist(fresh, schema_cache);
+  Ok(out)
+}
+
+fn descriptions(v: Option<&Value>) -> Vec<String> {
+  v.and_then(|v| v.as_array())
+    .map(|list| {
+      list
+        .iter()
+        .filter_map(|d| d.get("description").and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn strings(v: Option<&Value>) -> Vec<String> {
+  v.and_then(|v| v.as_array())
+    .map(|list| list.iter().filter_map(|s| s.as_str()).map(str::to_string).collect())
+    .unwrap_or_default()
+}
+
+fn parse_app_metadata(body: &Value, appid: u32) -> Result<Option<SteamAppMetadata>, String> {
+  let entry = match body.get(appid.to_string()) {
+    Some(entry) => entry,
+    None => return Ok(None),
+  };
+  if entry.get("success").and_then(|v| v.as_bool()) != Some(true) {
+    return Ok(None);
+  }
+  let data = entry
+    .get("data")
+    .ok_or_else(|| format!("Steam appdetails for {} has no data", appid))?;
+  let platforms = data
+    .get("platforms")
+    .map(|p| SteamPlatforms {
+      windows: p.get("windows").and_then(|v| v.as_bool()).unwrap_or(false),
+      mac: p.get("mac").and_then(|v| v.as_bool()).unwrap_or(false),
+      linux: p.get("linux").and_then(|v| v.as_bool()).unwrap_or(false),
+    })
+    .unwrap_or_default();
+  let release = data.get("release_date");
+  Ok(Some(SteamAppMetadata {
+    appid,
+    name: data.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+    app_type: data.get("type").and_then(|v| v.as_str()).map(str::to_string),
+    genres: descriptions(data.get("genres")),
+    categories: descriptions(data.get("categories")),
+    release_date: release
+      .and_then(|r| r.get("date"))
+      .and_then(|v| v.as_str())
+      .filter(|s| !s.is_empty())
+      .map(str::to_string),
+    coming_soon: release
+      .and_then(|r| r.get("coming_soon"))
+      .and_then(|v| v.as_bool())
+      .unwrap_or(false),
+    developers: strings(data.get("developers")),
+    publishers: strings(data.get("publishers")),
+    platforms,
+    header_image: data.get("header_image").and_then(|v| v.as_str()).map(str::to_string),
+    controller_support: data.get("controller_support").and_then(|v| v.as_str()).map(str::to_string),
+    metacritic_score: data.pointer("/metacritic/score").and_then(|v| v.as_u64()).map(|v| v as u32),
+    deck_compatibility: None,
+    stale: false,
+  }))
+}
+
+/// Best-effort Steam Deck rating from the store's compatibility report.
+async fn fetch_deck_compatibility(client: &reqwest::Client, appid: u32) -> Option<String> {
+  let url = format!(
+    "{}/saleaction/ajaxgetdeckappcompatibilityreport?nAppID={}",
+    steam_store_base(),
+    appid
+  );
+  let body = get_store_json(client, &url).await.ok()?;
+  let category = body.pointer("/results/resolved_category")?.as_u64()?;
+  Some(
+    match category {
+      3 => "verified",
+      2 => "playable",
+      1 => "unsupported",
+      _ => "unknown",
+    }
+    .to_string(),
+  )
+}
+
+/// Store genres per appid from the metadata cache, whatever region they were
+/// fetched for.
+pub(crate) fn cached_store_genres() -> HashMap<u32, Vec<String>> {
+  read_cache_map::<MetadataCacheEntry>(METADATA_CACHE_FILE)
+    .into_values()
+    .filter(|e| !e.meta.genres.is_empty())
+    .map(|e| (e.meta.appid, e.meta.genres))
+    .collect()
+}
+
+/// Store facts for an app (genres, categories, release date, developers,
+/// platforms, header image, controller support, Metacritic, Deck rating).
+/// Cached for 90 days since these rarely change.
+#[tauri::command]
+pub async fn get_steam_app_metadata(appid: u32, region: Option<String>) -> Result<Option<SteamAppMetadata>, String> {
+  let cc = steam_region(region.as_deref());
+  let key = format!("{}:{}", appid, cc);
+  let cache = read_cache_map::<MetadataCacheEntry>(METADATA_CACHE_FILE);
+  if let Some(entry) = cache.get(&key) {
+    let stale = is_expired(entry.ts, METADATA_CACHE_TTL_SECS);
+    if !stale || offline::is_offline() {
+      return Ok(Some(SteamAppMetadata { stale, ..entry.meta.clone() }));
+    }
+  }
+  if offline::is_offline() {
+    return Err(format!("Offline: no cached Steam metadata for {}", appid));
+  }
+
+  breaker::check("steam-store")?;
+  let client = http_client()?;
+  let url = format!("{}/api/appdetails?appids={}&cc={}", steam_store_base(), appid, cc);
+  let body = breaker::observe("steam-store", get_store_json(&client, &url).await)?;
+  let Some(mut meta) = parse_app_metadata(&body, appid)? else {
+    return Ok(None);
+  };
+  meta.deck_compatibility = fetch_deck_compatibility(&client, appid).await;
+
+  update_cache_map(METADATA_CACHE_FILE, |cache| {
+    cache.insert(key, MetadataCacheEntry { meta: meta.clone(), ts: now_unix() });
+  });
+  Ok(Some(meta))
+}
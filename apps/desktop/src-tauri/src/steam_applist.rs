@@ -0,0 +1,365 @@
+use crate::commands::{data_root, jaccard_token_set, normalize_key, now_unix, NORMALIZE_VERSION, USER_AGENT};
+use crate::{breaker, offline, ratelimit};
+use rusqlite::{params, types::Value, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  path::PathBuf,
+};
+use strsim::jaro_winkler;
+
+const APPLIST_DB_FILE: &str = "steam_apps.db";
+const APPLIST_URL: &str = "https://api.steampowered.com/ISteamApps/GetAppList/v2/";
+const DEFAULT_CANDIDATES: usize = 5;
+const MAX_TOKEN_CANDIDATES: usize = 400;
+// At or above this an import can attach the appid without asking.
+const AUTO_ATTACH_CONFIDENCE: f64 = 0.92;
+
+#[derive(Deserialize)]
+struct AppListEntry {
+  appid: u32,
+  name: String,
+}
+
+#[derive(Deserialize)]
+struct AppListInner {
+  apps: Vec<AppListEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AppListDump {
+  Wrapped { applist: AppListInner },
+  Inner(AppListInner),
+  Bare(Vec<AppListEntry>),
+}
+
+impl AppListDump {
+  fn into_apps(self) -> Vec<AppListEntry> {
+    match self {
+      AppListDump::Wrapped { applist } => applist.apps,
+      AppListDump::Inner(inner) => inner.apps,
+      AppListDump::Bare(apps) => apps,
+    }
+  }
+}
+
+#[derive(Serialize)]
+pub struct AppListSummary {
+  pub count: usize,
+  pub source: String,
+  pub imported_at: i64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct AppidCandidate {
+  pub appid: u32,
+  pub name: String,
+  pub confidence: f64,
+  pub exact: bool,
+}
+
+#[derive(Serialize)]
+pub struct ResolvedTitle {
+  pub title: String,
+  pub candidates: Vec<AppidCandidate>,
+  /// Set when the top candidate is confident enough to attach automatically.
+  pub auto_appid: Option<u32>,
+}
+
+fn db_path() -> PathBuf {
+  let mut path = data_root();
+  path.push(APPLIST_DB_FILE);
+  path
+}
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS apps (
+     appid INTEGER PRIMARY KEY,
+     name TEXT NOT NULL,
+     norm TEXT NOT NULL
+   );
+   CREATE INDEX IF NOT EXISTS apps_norm ON apps(norm);
+   CREATE TABLE IF NOT EXISTS app_tokens (
+     token TEXT NOT NULL,
+     appid INTEGER NOT NULL
+   );
+   CREATE INDEX IF NOT EXISTS app_tokens_token ON app_tokens(token);
+   CREATE TABLE IF NOT EXISTS meta (
+     key TEXT PRIMARY KEY,
+     value TEXT NOT NULL
+   );";
+
+fn open_db() -> Result<Connection, String> {
+  let mut conn = Connection::open(db_path()).map_err(|e| e.to_string())?;
+  conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+  reindex_if_stale(&mut conn)?;
+  Ok(conn)
+}
+
+fn meta_value(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+  conn
+    .query_row("SELECT value FROM meta WHERE key = ?1", [key], |r| r.get(0))
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Rebuilds `norm` and `app_tokens` from the stored names when they were
+/// written by an older `normalize_key`, keeping the original import's meta.
+fn reindex_if_stale(conn: &mut Connection) -> Result<(), String> {
+  if meta_value(conn, "normalize_version")?.as_deref() == Some(NORMALIZE_VERSION.to_string().as_str()) {
+    return Ok(());
+  }
+  let apps: Vec<AppListEntry> = {
+    let mut stmt = conn.prepare("SELECT appid, name FROM apps").map_err(|e| e.to_string())?;
+    let rows = stmt
+      .query_map([], |r| Ok(AppListEntry { appid: r.get(0)?, name: r.get(1)? }))
+      .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+  };
+  if apps.is_empty() {
+    return Ok(());
+  }
+  let source = meta_value(conn, "source")?.unwrap_or_default();
+  let imported_at = meta_value(conn, "imported_at")?
+    .and_then(|v| v.parse().ok())
+    .unwrap_or_else(now_unix);
+  write_applist(conn, &apps, &source, imported_at)?;
+  Ok(())
+}
+
+fn tokens(norm: &str) -> HashSet<&str> {
+  norm
+    .split_whitespace()
+    .filter(|t| !matches!(*t, "the" | "of" | "a" | "an" | "and" | "&" | "-"))
+    .collect()
+}
+
+async fn fetch_applist() -> Result<Vec<AppListEntry>, String> {
+  if offline::is_offline() {
+    return Err("Offline: cannot download the Steam app list; pass a local dump instead".into());
+  }
+  breaker::check("steam-api")?;
+  let client = reqwest::Client::builder()
+    .user_agent(USER_AGENT)
+    .build()
+    .map_err(|e| e.to_string())?;
+  ratelimit::acquire(APPLIST_URL).await;
+  let res = client
+    .get(APPLIST_URL)
+    .send()
+    .await
+    .map_err(|e| offline::note_error(&e));
+  let res = breaker::observe("steam-api", res)?;
+  offline::note_online();
+  if !res.status().is_success() {
+    return Err(format!("Steam app list HTTP {}", res.status()));
+  }
+  let dump: AppListDump = res.json().await.map_err(|e| e.to_string())?;
+  Ok(dump.into_apps())
+}
+
+fn read_applist_file(path: &str) -> Result<Vec<AppListEntry>, String> {
+  let file = fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+  let dump: AppListDump =
+    serde_json::from_reader(std::io::BufReader::new(file)).map_err(|e| format!("{}: {}", path, e))?;
+  Ok(dump.into_apps())
+}
+
+fn store_applist(apps: &[AppListEntry], source: &str) -> Result<AppListSummary, String> {
+  let mut conn = open_db()?;
+  write_applist(&mut conn, apps, source, now_unix())
+}
+
+fn write_applist(
+  conn: &mut Connection,
+  apps: &[AppListEntry],
+  source: &str,
+  imported_at: i64,
+) -> Result<AppListSummary, String> {
+  let tx = conn.transaction().map_err(|e| e.to_string())?;
+  tx.execute_batch("DELETE FROM apps; DELETE FROM app_tokens;")
+    .map_err(|e| e.to_string())?;
+  let mut count = 0usize;
+  {
+    let mut insert_app = tx
+      .prepare("INSERT OR REPLACE INTO apps (appid, name, norm) VALUES (?1, ?2, ?3)")
+      .map_err(|e| e.to_string())?;
+    let mut insert_token = tx
+      .prepare("INSERT INTO app_tokens (token, appid) VALUES (?1, ?2)")
+      .map_err(|e| e.to_string())?;
+    for app in apps {
+      let name = app.name.trim();
+      if name.is_empty() {
+        continue;
+      }
+      let norm = normalize_key(name);
+      if norm.is_empty() {
+        continue;
+      }
+      insert_app
+        .execute(params![app.appid, name, norm])
+        .map_err(|e| e.to_string())?;
+      for token in tokens(&norm) {
+        insert_token
+          .execute(params![token, app.appid])
+          .map_err(|e| e.to_string())?;
+      }
+      count += 1;
+    }
+  }
+  tx.execute(
+    "INSERT OR REPLACE INTO meta (key, value) VALUES ('imported_at', ?1), ('source', ?2), ('normalize_version', ?3)",
+    params![imported_at.to_string(), source, NORMALIZE_VERSION.to_string()],
+  )
+  .map_err(|e| e.to_string())?;
+  tx.commit().map_err(|e| e.to_string())?;
+  Ok(AppListSummary { count, source: source.into(), imported_at })
+}
+
+fn score(query: &str, candidate: &str) -> f64 {
+  let jw = jaro_winkler(query, candidate);
+  let jac = jaccard_token_set(query, candidate);
+  jw.max(jac)
+}
+
+fn resolve_with(conn: &Connection, title: &str, limit: usize) -> Result<Vec<AppidCandidate>, String> {
+  let norm = normalize_key(title);
+  if norm.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  let mut found: HashMap<u32, AppidCandidate> = HashMap::new();
+  {
+    let mut exact = conn
+      .prepare_cached("SELECT appid, name FROM apps WHERE norm = ?1")
+      .map_err(|e| e.to_string())?;
+    let rows = exact
+      .query_map(params![norm], |r| Ok((r.get::<_, u32>(0)?, r.get::<_, String>(1)?)))
+      .map_err(|e| e.to_string())?;
+    for row in rows {
+      let (appid, name) = row.map_err(|e| e.to_string())?;
+      found.insert(appid, AppidCandidate { appid, name, confidence: 1.0, exact: true });
+    }
+  }
+
+  let query_tokens: Vec<&str> = tokens(&norm).into_iter().collect();
+  if !query_tokens.is_empty() {
+    let placeholders = vec!["?"; query_tokens.len()].join(", ");
+    // Candidates sharing the most tokens first; among equals (every hit for a
+    // one-word query) the ones closest in length to the query.
+    let sql = format!(
+      "SELECT a.appid, a.name, a.norm FROM app_tokens t JOIN apps a ON a.appid = t.appid
+       WHERE t.token IN ({}) GROUP BY a.appid
+       ORDER BY COUNT(*) DESC, ABS(LENGTH(a.norm) - ?) LIMIT {}",
+      placeholders, MAX_TOKEN_CANDIDATES
+    );
+    let args = query_tokens
+      .iter()
+      .map(|t| Value::Text(t.to_string()))
+      .chain(std::iter::once(Value::Integer(norm.chars().count() as i64)));
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+      .query_map(rusqlite::params_from_iter(args), |r| {
+        Ok((r.get::<_, u32>(0)?, r.get::<_, String>(1)?, r.get::<_, String>(2)?))
+      })
+      .map_err(|e| e.to_string())?;
+    for row in rows {
+      let (appid, name, cand_norm) = row.map_err(|e| e.to_string())?;
+      if found.contains_key(&appid) {
+        continue;
+      }
+      let confidence = score(&norm, &cand_norm);
+      found.insert(appid, AppidCandidate { appid, name, confidence, exact: false });
+    }
+  }
+
+  let mut ranked: Vec<AppidCandidate> = found.into_values().collect();
+  ranked.sort_by(|a, b| {
+    b.confidence
+      .partial_cmp(&a.confidence)
+      .unwrap_or(std::cmp::Ordering::Equal)
+      .then(a.appid.cmp(&b.appid))
+  });
+  ranked.truncate(limit.max(1));
+  Ok(ranked)
+}
+
+fn ensure_populated(conn: &Connection) -> Result<(), String> {
+  let populated: Option<i64> = conn
+    .query_row("SELECT 1 FROM apps LIMIT 1", [], |r| r.get(0))
+    .optional()
+    .map_err(|e| e.to_string())?;
+  if populated.is_none() {
+    return Err("Steam app list is empty; run steam_applist_import first".into());
+  }
+  Ok(())
+}
+
+/// Imports the Steam app list from a local `GetAppList` JSON dump, or
+/// downloads it when no path is given, replacing the current index.
+#[tauri::command]
+pub async fn steam_applist_import(path: Option<String>) -> Result<AppListSummary, String> {
+  let (apps, source) = match path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+    Some(p) => (read_applist_file(p)?, p.to_string()),
+    None => (fetch_applist().await?, APPLIST_URL.to_string()),
+  };
+  tauri::async_runtime::spawn_blocking(move || store_applist(&apps, &source))
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn steam_resolve_appid(title: String, limit: Option<usize>) -> Result<Vec<AppidCandidate>, String> {
+  let conn = open_db()?;
+  ensure_populated(&conn)?;
+  resolve_with(&conn, &title, limit.unwrap_or(DEFAULT_CANDIDATES))
+}
+
+/// Batch form for importers: ranks candidates for every title and marks the
+/// ones confident enough to attach without review.
+#[tauri::command]
+pub fn steam_resolve_appids(titles: Vec<String>, limit: Option<usize>) -> Result<Vec<ResolvedTitle>, String> {
+  let conn = open_db()?;
+  ensure_populated(&conn)?;
+  let limit = limit.unwrap_or(DEFAULT_CANDIDATES);
+  titles
+    .into_iter()
+    .map(|title| {
+      let candidates = resolve_with(&conn, &title, limit)?;
+      let auto_appid = match candidates.as_slice() {
+        [first, second, ..] if first.confidence >= AUTO_ATTACH_CONFIDENCE && first.confidence > second.confidence => {
+          Some(first.appid)
+        }
+        [only] if only.confidence >= AUTO_ATTACH_CONFIDENCE => Some(only.appid),
+        _ => None,
+      };
+      Ok(ResolvedTitle { title, candidates, auto_appid })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn one_word_queries_keep_close_length_candidates() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(SCHEMA).unwrap();
+    // More single-token hits than the candidate cap, all with long names...
+    let mut apps: Vec<AppListEntry> = (0..MAX_TOKEN_CANDIDATES as u32 + 100)
+      .map(|i| AppListEntry {
+        appid: i + 1,
+        name: format!("Hades Fan Soundtrack Collection Volume {}", i),
+      })
+      .collect();
+    // ...and the one that should be found, inserted last.
+    apps.push(AppListEntry { appid: 1145350, name: "Hades II".into() });
+    write_applist(&mut conn, &apps, "test", 0).unwrap();
+
+    let found = resolve_with(&conn, "Hades", 5).unwrap();
+    assert_eq!(found.first().map(|c| c.appid), Some(1145350));
+    assert!(!found[0].exact);
+  }
+}
@@ -0,0 +1,300 @@
+use crate::vdf::{self, Vdf};
+use serde::Serialize;
+use std::{
+  collections::HashSet,
+  fs,
+  path::{Path, PathBuf},
+};
+
+// StateFlags bit Steam sets once an app is fully installed.
+const STATE_FULLY_INSTALLED: u32 = 4;
+// SteamID64 = this base + 32-bit account id (the `userdata/<id>` folder name).
+const STEAMID64_BASE: u64 = 76_561_197_960_265_728;
+
+#[derive(Serialize)]
+pub struct InstalledGame {
+  pub appid: u32,
+  pub name: String,
+  pub install_dir: String,
+  pub library_path: String,
+  pub size_on_disk: u64,
+  pub last_updated: Option<i64>,
+  pub fully_installed: bool,
+}
+
+#[derive(Serialize)]
+pub struct SteamInstallScan {
+  pub steam_root: String,
+  pub libraries: Vec<String>,
+  pub games: Vec<InstalledGame>,
+}
+
+#[derive(Serialize)]
+pub struct LocalPlaytime {
+  pub appid: u32,
+  pub playtime_minutes: Option<u32>,
+  pub playtime_2wks_minutes: Option<u32>,
+  pub last_played: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct LocalAccount {
+  pub account_id: u32,
+  pub steamid64: String,
+  pub account_name: Option<String>,
+  pub persona_name: Option<String>,
+}
+
+fn candidate_roots() -> Vec<PathBuf> {
+  let mut roots = Vec::new();
+  if let Some(home) = dirs::home_dir() {
+    roots.push(home.join(".steam/steam"));
+    roots.push(home.join(".steam/root"));
+    roots.push(home.join(".local/share/Steam"));
+    roots.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+    roots.push(home.join("Library/Application Support/Steam"));
+  }
+  roots.push(PathBuf::from(r"C:\Program Files (x86)\Steam"));
+  roots.push(PathBuf::from(r"C:\Program Files\Steam"));
+  roots
+}
+
+/// Uses the given Steam directory, or finds the first standard install
+/// location that has a `steamapps` folder.
+fn resolve_steam_root(steam_root: Option<&str>) -> Result<PathBuf, String> {
+  if let Some(root) = steam_root.map(str::trim).filter(|r| !r.is_empty()) {
+    let path = PathBuf::from(root);
+    if !path.is_dir() {
+      return Err(format!("Steam directory not found: {}", root));
+    }
+    return Ok(path);
+  }
+  candidate_roots()
+    .into_iter()
+    .find(|p| p.join("steamapps").is_dir())
+    .ok_or_else(|| "Could not find a Steam installation; pass its directory explicitly".to_string())
+}
+
+fn read_vdf(path: &Path) -> Result<Vdf, String> {
+  let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+  vdf::parse(&String::from_utf8_lossy(&bytes)).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Library roots from `steamapps/libraryfolders.vdf`, handling both the
+/// current `{ "path" ... }` layout and the older bare-path entries.
+fn library_folders(steam_root: &Path) -> Vec<PathBuf> {
+  let mut libraries = vec![steam_root.to_path_buf()];
+  let doc = match read_vdf(&steam_root.join("steamapps/libraryfolders.vdf")) {
+    Ok(doc) => doc,
+    Err(_) => return libraries,
+  };
+  let root = doc
+    .get("libraryfolders")
+    .or_else(|| doc.get("LibraryFolders"))
+    .unwrap_or(&doc);
+  for (key, value) in root.entries() {
+    if !key.chars().all(|c| c.is_ascii_digit()) {
+      continue;
+    }
+    let path = match value {
+      Vdf::Str(p) => Some(p.as_str()),
+      Vdf::Obj(_) => value.get_str("path"),
+    };
+    if let Some(p) = path {
+      let p = PathBuf::from(p);
+      if !libraries.iter().any(|l| same_path(l, &p)) {
+        libraries.push(p);
+      }
+    }
+  }
+  libraries
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+  match (a.canonicalize(), b.canonicalize()) {
+    (Ok(a), Ok(b)) => a == b,
+    _ => a == b,
+  }
+}
+
+fn parse_manifest(path: &Path, library: &Path) -> Option<InstalledGame> {
+  let doc = read_vdf(path).ok()?;
+  let state = doc.get("AppState")?;
+  let appid = state.get_str("appid")?.trim().parse::<u32>().ok()?;
+  let name = state.get_str("name").unwrap_or_default().to_string();
+  let install_dir = state.get_str("installdir").unwrap_or_default();
+  let size_on_disk = state
+    .get_str("SizeOnDisk")
+    .and_then(|v| v.trim().parse::<u64>().ok())
+    .unwrap_or(0);
+  let last_updated = state
+    .get_str("LastUpdated")
+    .and_then(|v| v.trim().parse::<i64>().ok())
+    .filter(|ts| *ts > 0);
+  let flags = state
+    .get_str("StateFlags")
+    .and_then(|v| v.trim().parse::<u32>().ok())
+    .unwrap_or(0);
+  Some(InstalledGame {
+    appid,
+    name,
+    install_dir: library
+      .join("steamapps/common")
+      .join(install_dir)
+      .to_string_lossy()
+      .into_owned(),
+    library_path: library.to_string_lossy().into_owned(),
+    size_on_disk,
+    last_updated,
+    fully_installed: flags & STATE_FULLY_INSTALLED != 0,
+  })
+}
+
+fn scan_library(library: &Path) -> Vec<InstalledGame> {
+  let entries = match fs::read_dir(library.join("steamapps")) {
+    Ok(entries) => entries,
+    Err(_) => return Vec::new(),
+  };
+  entries
+    .filter_map(|e| e.ok())
+    .map(|e| e.path())
+    .filter(|p| {
+      p.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.starts_with("appmanifest_") && n.ends_with(".acf"))
+        .unwrap_or(false)
+    })
+    .filter_map(|p| parse_manifest(&p, library))
+    .collect()
+}
+
+/// Lists games installed in every Steam library folder on this machine.
+#[tauri::command]
+pub fn steam_scan_installed(steam_root: Option<String>) -> Result<SteamInstallScan, String> {
+  let root = resolve_steam_root(steam_root.as_deref())?;
+  let libraries = library_folders(&root);
+  // A game left behind in a second library folder is listed once, from the
+  // first folder Steam reports it in.
+  let mut seen = HashSet::new();
+  let mut games: Vec<InstalledGame> = libraries
+    .iter()
+    .flat_map(|l| scan_library(l))
+    .filter(|g| seen.insert(g.appid))
+    .collect();
+  games.sort_by_key(|g| g.name.to_lowercase());
+  Ok(SteamInstallScan {
+    steam_root: root.to_string_lossy().into_owned(),
+    libraries: libraries.iter().map(|l| l.to_string_lossy().into_owned()).collect(),
+    games,
+  })
+}
+
+fn userdata_ids(root: &Path) -> Vec<u32> {
+  let mut ids: Vec<u32> = fs::read_dir(root.join("userdata"))
+    .map(|entries| {
+      entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().to_str().and_then(|n| n.parse::<u32>().ok()))
+        .filter(|id| *id != 0)
+        .collect()
+    })
+    .unwrap_or_default();
+  ids.sort_unstable();
+  ids
+}
+
+/// Accepts a 32-bit account id or a SteamID64; with neither, falls back to
+/// the only local account when there is exactly one.
+fn resolve_account_id(root: &Path, account: Option<&str>) -> Result<u32, String> {
+  if let Some(text) = account.map(str::trim).filter(|a| !a.is_empty()) {
+    let raw = text
+      .parse::<u64>()
+      .map_err(|_| format!("Invalid Steam account id '{}'", text))?;
+    let id = if raw >= STEAMID64_BASE { raw - STEAMID64_BASE } else { raw };
+    return u32::try_from(id).map_err(|_| format!("Invalid Steam account id '{}'", text));
+  }
+  match userdata_ids(root).as_slice() {
+    [only] => Ok(*only),
+    [] => Err("No Steam accounts found under userdata".into()),
+    many => Err(format!(
+      "Several Steam accounts found ({}); pass one explicitly",
+      many.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ")
+    )),
+  }
+}
+
+fn parse_u32(node: &Vdf, key: &str) -> Option<u32> {
+  node.get_str(key).and_then(|v| v.trim().parse::<u32>().ok())
+}
+
+/// Accounts that have signed in on this machine, from `userdata/` and
+/// `config/loginusers.vdf`.
+#[tauri::command]
+pub fn steam_local_accounts(steam_root: Option<String>) -> Result<Vec<LocalAccount>, String> {
+  let root = resolve_steam_root(steam_root.as_deref())?;
+  let users = read_vdf(&root.join("config/loginusers.vdf")).ok();
+  let users = users.as_ref().and_then(|doc| doc.get("users"));
+  Ok(
+    userdata_ids(&root)
+      .into_iter()
+      .map(|account_id| {
+        let steamid64 = (STEAMID64_BASE + account_id as u64).to_string();
+        let user = users.and_then(|u| u.get(&steamid64));
+        LocalAccount {
+          account_id,
+          account_name: user.and_then(|u| u.get_str("AccountName")).map(str::to_string),
+          persona_name: user.and_then(|u| u.get_str("PersonaName")).map(str::to_string),
+          steamid64,
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Per-app playtime and last-played time recorded offline by the Steam
+/// client in `userdata/<account>/config/localconfig.vdf`.
+#[tauri::command]
+pub fn steam_local_playtime(
+  steam_root: Option<String>,
+  account: Option<String>,
+) -> Result<Vec<LocalPlaytime>, String> {
+  let root = resolve_steam_root(steam_root.as_deref())?;
+  let account_id = resolve_account_id(&root, account.as_deref())?;
+  let path = root
+    .join("userdata")
+    .join(account_id.to_string())
+    .join("config/localconfig.vdf");
+  let doc = read_vdf(&path)?;
+  let steam = doc
+    .path(&["UserLocalConfigStore", "Software", "Valve", "Steam"])
+    .ok_or_else(|| format!("{}: missing Software/Valve/Steam section", path.display()))?;
+  let apps = match steam.get("apps") {
+    Some(apps) => apps,
+    None => return Ok(Vec::new()),
+  };
+
+  let mut out: Vec<LocalPlaytime> = apps
+    .entries()
+    .iter()
+    .filter_map(|(key, node)| {
+      let appid = key.parse::<u32>().ok()?;
+      let playtime_minutes = parse_u32(node, "Playtime");
+      let playtime_2wks_minutes = parse_u32(node, "Playtime2wks");
+      let last_played = node
+        .get_str("LastPlayed")
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|ts| *ts > 0);
+      if playtime_minutes.is_none() && last_played.is_none() {
+        return None;
+      }
+      Some(LocalPlaytime {
+        appid,
+        playtime_minutes,
+        playtime_2wks_minutes,
+        last_played,
+      })
+    })
+    .collect();
+  out.sort_by_key(|p| std::cmp::Reverse(p.last_played));
+  Ok(out)
+}
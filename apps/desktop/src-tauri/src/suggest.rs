@@ -0,0 +1,503 @@
+use crate::commands::{now_unix, CachedMetadata, HltbTimes};
+use crate::currency::{self, DEFAULT_ITEM_CURRENCY};
+use crate::library::{self, Identity, LibraryItem};
+use crate::steam::{cached_achievement_percents, cached_store_genres};
+use crate::steam_local::steam_local_playtime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const MEMBER_PREFS_KEY: &str = "member_prefs";
+const DEFAULT_LIMIT: usize = 20;
+// Price-per-hour (in TRY) at which the value term is worth half its weight.
+const VALUE_HALF_POINT: f64 = 25.0;
+const RECENT_PLAY_DAYS: i64 = 14;
+const STALLED_DAYS: i64 = 180;
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct MemberPreferences {
+  /// "short" | "medium" | "long" | "epic"; empty means no preference.
+  pub preferred_length: Option<String>,
+  /// "main" | "extras" | "completionist"
+  pub hltb_style: Option<String>,
+  pub favorite_genres: Vec<String>,
+  pub avoid_genres: Vec<String>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SuggestWeights {
+  pub backlog_boost: f64,
+  pub score_weight: f64,
+  pub value_weight: f64,
+  pub length_weight: f64,
+  pub budget_weight: f64,
+  pub achievement_weight: f64,
+  pub recency_weight: f64,
+  pub genre_weight: f64,
+}
+
+impl Default for SuggestWeights {
+  fn default() -> Self {
+    SuggestWeights {
+      backlog_boost: 1.0,
+      score_weight: 1.0,
+      value_weight: 0.5,
+      length_weight: 0.5,
+      budget_weight: 1.0,
+      achievement_weight: 0.6,
+      recency_weight: 0.6,
+      genre_weight: 0.8,
+    }
+  }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct SuggestRequest {
+  pub member_id: Option<String>,
+  /// Hours available, e.g. 10 for "I have 10 hours this week".
+  pub budget_hours: Option<f64>,
+  /// Overrides the member's saved HLTB style.
+  pub hltb_style: Option<String>,
+  /// Enables achievement progress from the Steam cache.
+  pub steamid64: Option<String>,
+  /// Local Steam install/account for playtime and last-played.
+  pub steam_root: Option<String>,
+  pub steam_account: Option<String>,
+  pub weights: Option<SuggestWeights>,
+  pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct Suggestion {
+  pub item_id: String,
+  pub identity_id: String,
+  pub title: String,
+  /// "PlayNext" | "BuyClaim", as in `computeSuggestions`.
+  pub kind: String,
+  pub score: f64,
+  pub reasons: Vec<String>,
+  pub hours: Option<f64>,
+  pub remaining_hours: Option<f64>,
+  pub fits_budget: Option<bool>,
+}
+
+/// HLTB time for a play style: the extras or completionist median when HLTB
+/// reported one, else the main-story time.
+pub(crate) fn hltb_hours(main: f64, times: &HltbTimes, style: Option<&str>) -> f64 {
+  let category = match style.unwrap_or("main") {
+    "extras" | "main_extras" | "mainextras" => times.extras,
+    "completionist" | "100" => times.completionist,
+    _ => None,
+  };
+  category.map(f64::from).unwrap_or(main)
+}
+
+pub(crate) fn length_bucket(hours: f64) -> &'static str {
+  if hours < 5.0 {
+    "short"
+  } else if hours < 15.0 {
+    "medium"
+  } else if hours < 40.0 {
+    "long"
+  } else {
+    "epic"
+  }
+}
+
+pub(crate) fn load_member_preferences(conn: &rusqlite::Connection, member_id: &str) -> Result<MemberPreferences, String> {
+  let key = format!("{}:{}", MEMBER_PREFS_KEY, member_id);
+  Ok(
+    library::get_setting(conn, &key)?
+      .and_then(|v| serde_json::from_value(v).ok())
+      .unwrap_or_default(),
+  )
+}
+
+struct PlayRecord {
+  minutes: Option<u32>,
+  last_played: Option<i64>,
+}
+
+fn local_playtime(req: &SuggestRequest) -> HashMap<u32, PlayRecord> {
+  if req.steam_root.is_none() && req.steam_account.is_none() {
+    return HashMap::new();
+  }
+  steam_local_playtime(req.steam_root.clone(), req.steam_account.clone())
+    .map(|rows| {
+      rows
+        .into_iter()
+        .map(|r| {
+          (
+            r.appid,
+            PlayRecord {
+              minutes: r.playtime_minutes,
+              last_played: r.last_played,
+            },
+          )
+        })
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn genres_of(identity: &Identity, store_genres: &HashMap<u32, Vec<String>>) -> Vec<String> {
+  let mut out: Vec<String> = identity.mc_genres.clone().unwrap_or_default();
+  if let Some(g) = identity.appid.and_then(|a| store_genres.get(&a)) {
+    out.extend(g.iter().cloned());
+  }
+  let mut seen = std::collections::HashSet::new();
+  out.retain(|g| seen.insert(g.to_lowercase()));
+  out
+}
+
+/// Share of the member's beaten games carrying each genre (lowercased).
+fn genre_affinity(
+  items: &[&LibraryItem],
+  identities: &HashMap<String, Identity>,
+  store_genres: &HashMap<u32, Vec<String>>,
+) -> HashMap<String, f64> {
+  let beaten: Vec<&Identity> = items
+    .iter()
+    .filter(|i| i.status == "Beaten")
+    .filter_map(|i| identities.get(&i.identity_id))
+    .collect();
+  let mut counts: HashMap<String, f64> = HashMap::new();
+  for identity in &beaten {
+    for g in genres_of(identity, store_genres) {
+      *counts.entry(g.to_lowercase()).or_default() += 1.0;
+    }
+  }
+  let total = beaten.len().max(1) as f64;
+  counts.values_mut().for_each(|c| *c /= total);
+  counts
+}
+
+#[tauri::command]
+pub fn get_member_preferences(member_id: String) -> Result<MemberPreferences, String> {
+  load_member_preferences(&library::open_db()?, &member_id)
+}
+
+#[tauri::command]
+pub fn set_member_preferences(member_id: String, preferences: MemberPreferences) -> Result<MemberPreferences, String> {
+  let conn = library::open_db()?;
+  let value = serde_json::to_value(&preferences).map_err(|e| e.to_string())?;
+  library::set_setting(&conn, &format!("{}:{}", MEMBER_PREFS_KEY, member_id), &value)?;
+  Ok(preferences)
+}
+
+/// Everything besides the library rows that scoring reads, loaded from the
+/// local caches and the Steam install.
+#[derive(Default)]
+struct Signals {
+  cache: CachedMetadata,
+  store_genres: HashMap<u32, Vec<String>>,
+  achievements: HashMap<u32, f32>,
+  playtime: HashMap<u32, PlayRecord>,
+  /// item id -> price converted to TRY at its acquisition-date rate, for
+  /// items priced in another currency that have a rate.
+  try_prices: HashMap<String, f64>,
+  now: i64,
+}
+
+fn is_default_currency(item: &LibraryItem) -> bool {
+  item
+    .currency_code
+    .as_deref()
+    .map(str::trim)
+    .is_none_or(|c| c.is_empty() || c.eq_ignore_ascii_case(DEFAULT_ITEM_CURRENCY))
+}
+
+/// Ranks what to play next (and wishlist/subscription titles to buy or claim)
+/// for a member. Every score contribution comes with a human-readable reason.
+#[tauri::command]
+pub fn library_suggest(request: Option<SuggestRequest>) -> Result<Vec<Suggestion>, String> {
+  let req = request.unwrap_or_default();
+  let conn = library::open_db()?;
+  let identities: HashMap<String, Identity> = library::load_identities(&conn)?
+    .into_iter()
+    .map(|i| (i.id.clone(), i))
+    .collect();
+  let all_items = library::load_items(&conn)?;
+  let items: Vec<&LibraryItem> = all_items
+    .iter()
+    .filter(|i| req.member_id.is_none() || i.member_id == req.member_id)
+    .collect();
+  let prefs = match &req.member_id {
+    Some(m) => load_member_preferences(&conn, m)?,
+    None => MemberPreferences::default(),
+  };
+  let signals = Signals {
+    cache: CachedMetadata::load(),
+    store_genres: cached_store_genres(),
+    achievements: req
+      .steamid64
+      .as_deref()
+      .map(cached_achievement_percents)
+      .unwrap_or_default(),
+    playtime: local_playtime(&req),
+    try_prices: items
+      .iter()
+      .filter(|i| !is_default_currency(i))
+      .filter_map(|i| {
+        let c = currency::convert_item(&conn, i, DEFAULT_ITEM_CURRENCY).ok().flatten()?;
+        Some((i.id.clone(), c.converted))
+      })
+      .collect(),
+    now: now_unix(),
+  };
+  Ok(rank(&req, &prefs, items, &identities, &signals))
+}
+
+fn rank(
+  req: &SuggestRequest,
+  prefs: &MemberPreferences,
+  items: Vec<&LibraryItem>,
+  identities: &HashMap<String, Identity>,
+  signals: &Signals,
+) -> Vec<Suggestion> {
+  let w = req.weights.clone().unwrap_or_default();
+  let style = req.hltb_style.clone().or(prefs.hltb_style.clone());
+  let Signals { cache, store_genres, achievements, playtime, try_prices, now } = signals;
+  let affinity = genre_affinity(&items, identities, store_genres);
+
+  let mut out = Vec::new();
+  for item in items {
+    let Some(identity) = identities.get(&item.identity_id) else { continue };
+    let kind = match item.status.as_str() {
+      "Backlog" | "Playing" | "Owned" if item.services.as_ref().is_none_or(|s| s.is_empty()) => "PlayNext",
+      "Backlog" | "Playing" | "Owned" | "Wishlist" => "BuyClaim",
+      _ => continue,
+    };
+    let mut score = 0.0;
+    let mut reasons = Vec::new();
+
+    match item.status.as_str() {
+      "Backlog" => {
+        score += w.backlog_boost;
+        reasons.push("In the backlog".to_string());
+      }
+      "Playing" => {
+        score += w.backlog_boost * 0.8;
+        reasons.push("Already in progress".to_string());
+      }
+      "Owned" => score += w.backlog_boost * 0.5,
+      _ => {}
+    }
+
+    let critic = item
+      .oc_score
+      .or(identity.oc_score)
+      .or(identity.mc_score)
+      .or_else(|| cache.critic_score(&identity.title).map(f64::from));
+    if let Some(c) = critic {
+      score += w.score_weight * (c / 100.0);
+      reasons.push(format!("Critic score {:.0}", c));
+    }
+
+    let times = cache.hltb_times(&identity.title);
+    let main = item
+      .ttb_median_main_h
+      .or(identity.ttb_median_main_h)
+      .or(times.main.map(f64::from));
+    let hours = main.filter(|h| *h > 0.0).map(|h| hltb_hours(h, &times, style.as_deref()));
+    let play = identity.appid.and_then(|a| playtime.get(&a));
+    let played_hours = play.and_then(|p| p.minutes).map(|m| m as f64 / 60.0);
+    let remaining = hours.map(|h| (h - played_hours.unwrap_or(0.0)).max(0.5));
+
+    // Other currencies only count once converted; without a rate the term is skipped.
+    let price_try = if is_default_currency(item) {
+      item.price_try
+    } else {
+      try_prices.get(&item.id).copied()
+    };
+    if let (Some(price), Some(h)) = (price_try, hours) {
+      let pph = price / h;
+      score += w.value_weight * (1.0 / (1.0 + pph / VALUE_HALF_POINT));
+      reasons.push(format!("~{:.1} TRY per hour", pph));
+    }
+
+    if let Some(h) = hours {
+      let bucket = length_bucket(h);
+      if prefs.preferred_length.as_deref() == Some(bucket) {
+        score += w.length_weight;
+        reasons.push(format!("A {} game (~{:.0}h), as preferred", bucket, h));
+      }
+    }
+
+    let fits_budget = match (req.budget_hours, remaining) {
+      (Some(budget), Some(left)) if budget > 0.0 => {
+        if left <= budget {
+          score += w.budget_weight;
+          reasons.push(format!("Fits in {:.0}h (~{:.1}h left)", budget, left));
+        } else if left <= budget * 2.0 {
+          score += w.budget_weight * 0.3;
+          reasons.push(format!("About two sessions of {:.0}h (~{:.1}h left)", budget, left));
+        } else {
+          score -= w.budget_weight * 0.5;
+          reasons.push(format!("Too long for {:.0}h (~{:.0}h left)", budget, left));
+        }
+        Some(left <= budget)
+      }
+      _ => None,
+    };
+
+    if let Some(pct) = identity.appid.and_then(|a| achievements.get(&a)).map(|p| f64::from(*p)) {
+      if pct >= 100.0 {
+        score -= w.achievement_weight;
+        reasons.push("All achievements unlocked".to_string());
+      } else if pct > 0.0 {
+        score += w.achievement_weight * (pct / 100.0);
+        reasons.push(format!("{:.0}% of achievements unlocked", pct));
+      }
+    }
+
+    if let Some(last) = play.and_then(|p| p.last_played).filter(|t| *t > 0) {
+      let days = (now - last).max(0) / 86_400;
+      if days <= RECENT_PLAY_DAYS {
+        score += w.recency_weight;
+        reasons.push(format!("Played {} day(s) ago", days));
+      } else if days >= STALLED_DAYS && item.status == "Playing" {
+        score -= w.recency_weight * 0.3;
+        reasons.push(format!("Untouched for {} days", days));
+      }
+    }
+
+    let genres = genres_of(identity, store_genres);
+    let best = genres
+      .iter()
+      .filter_map(|g| affinity.get(&g.to_lowercase()).map(|a| (g, *a)))
+      .max_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((genre, a)) = best.filter(|(_, a)| *a > 0.0) {
+      score += w.genre_weight * a;
+      reasons.push(format!("You often finish {} games", genre));
+    }
+    let has = |list: &[String]| genres.iter().find(|g| list.iter().any(|l| l.eq_ignore_ascii_case(g)));
+    if let Some(g) = has(&prefs.favorite_genres) {
+      score += w.genre_weight * 0.5;
+      reasons.push(format!("Favorite genre: {}", g));
+    }
+    if let Some(g) = has(&prefs.avoid_genres) {
+      score -= w.genre_weight;
+      reasons.push(format!("Avoided genre: {}", g));
+    }
+
+    out.push(Suggestion {
+      item_id: item.id.clone(),
+      identity_id: identity.id.clone(),
+      title: identity.title.clone(),
+      kind: kind.to_string(),
+      score: (score * 1000.0).round() / 1000.0,
+      reasons,
+      hours: hours.map(|h| (h * 10.0).round() / 10.0),
+      remaining_hours: remaining.map(|h| (h * 10.0).round() / 10.0),
+      fits_budget,
+    });
+  }
+  out.sort_by(|a, b| b.score.total_cmp(&a.score));
+  out.truncate(req.limit.unwrap_or(DEFAULT_LIMIT));
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn identity(id: &str, genre: &str) -> Identity {
+    Identity {
+      id: id.into(),
+      title: format!("Suggest Test {}", id),
+      mc_genres: Some(vec![genre.into()]),
+      ..Default::default()
+    }
+  }
+
+  fn backlog(id: &str, hours: f64) -> LibraryItem {
+    LibraryItem {
+      id: format!("item-{}", id),
+      identity_id: id.into(),
+      member_id: Some("m".into()),
+      status: "Backlog".into(),
+      ttb_median_main_h: Some(hours),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn style_uses_reported_category_or_main() {
+    let times = HltbTimes {
+      main: Some(10.0),
+      extras: Some(18.5),
+      completionist: None,
+    };
+    assert_eq!(hltb_hours(10.0, &times, Some("extras")), 18.5);
+    assert_eq!(hltb_hours(10.0, &times, Some("completionist")), 10.0);
+    assert_eq!(hltb_hours(10.0, &times, None), 10.0);
+  }
+
+  #[test]
+  fn ranks_by_budget_and_avoided_genres() {
+    let identities: HashMap<String, Identity> = [
+      identity("short", "Puzzle"),
+      identity("scary", "Horror"),
+      identity("long", "Puzzle"),
+    ]
+    .into_iter()
+    .map(|i| (i.id.clone(), i))
+    .collect();
+    let items = [backlog("short", 4.0), backlog("scary", 6.0), backlog("long", 60.0)];
+    let req = SuggestRequest {
+      member_id: Some("m".into()),
+      budget_hours: Some(10.0),
+      ..Default::default()
+    };
+    let prefs = MemberPreferences {
+      avoid_genres: vec!["horror".into()],
+      ..Default::default()
+    };
+    let out = rank(&req, &prefs, items.iter().collect(), &identities, &Signals::default());
+
+    let order: Vec<&str> = out.iter().map(|s| s.identity_id.as_str()).collect();
+    assert_eq!(order, ["short", "scary", "long"]);
+    assert!(out.iter().all(|s| s.kind == "PlayNext"));
+    assert!(out[0].reasons.contains(&"Fits in 10h (~4.0h left)".to_string()));
+    assert_eq!(out[0].fits_budget, Some(true));
+    assert!(out[1].reasons.contains(&"Avoided genre: Horror".to_string()));
+    assert!(out[2].reasons.contains(&"Too long for 10h (~60h left)".to_string()));
+    assert_eq!(out[2].fits_budget, Some(false));
+  }
+
+  #[test]
+  fn value_term_needs_a_price_in_try() {
+    let identities: HashMap<String, Identity> = [identity("try", "Puzzle"), identity("usd", "Puzzle")]
+      .into_iter()
+      .map(|i| (i.id.clone(), i))
+      .collect();
+    let priced = |id: &str, price: f64, currency: Option<&str>| LibraryItem {
+      price_try: Some(price),
+      currency_code: currency.map(Into::into),
+      ..backlog(id, 10.0)
+    };
+    let items = [priced("try", 100.0, None), priced("usd", 20.0, Some("usd"))];
+    let value = |out: &[Suggestion], id: &str| {
+      out
+        .iter()
+        .find(|s| s.identity_id == id)
+        .and_then(|s| s.reasons.iter().find(|r| r.ends_with("TRY per hour")).cloned())
+    };
+    let req = SuggestRequest::default();
+    let prefs = MemberPreferences::default();
+
+    let out = rank(&req, &prefs, items.iter().collect(), &identities, &Signals::default());
+    assert_eq!(value(&out, "try").as_deref(), Some("~10.0 TRY per hour"));
+    assert_eq!(value(&out, "usd"), None);
+
+    let signals = Signals {
+      try_prices: [("item-usd".to_string(), 640.0)].into_iter().collect(),
+      ..Default::default()
+    };
+    let out = rank(&req, &prefs, items.iter().collect(), &identities, &signals);
+    assert_eq!(value(&out, "usd").as_deref(), Some("~64.0 TRY per hour"));
+  }
+}
@@ -0,0 +1,263 @@
+//! Minimal reader for Valve's text KeyValues format (`.vdf` / `.acf`).
+
+/// A KeyValues node: either a string leaf or an ordered list of children.
+/// Keys are kept as written; lookups are case-insensitive like Steam's own.
+#[derive(Debug, Clone)]
+pub enum Vdf {
+  Str(String),
+  Obj(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+  pub fn get(&self, key: &str) -> Option<&Vdf> {
+    match self {
+      Vdf::Obj(entries) => entries
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v),
+      Vdf::Str(_) => None,
+    }
+  }
+
+  /// Follows a path of keys, e.g. `["UserLocalConfigStore", "Software"]`.
+  pub fn path(&self, keys: &[&str]) -> Option<&Vdf> {
+    keys.iter().try_fold(self, |node, key| node.get(key))
+  }
+
+  pub fn get_str(&self, key: &str) -> Option<&str> {
+    self.get(key).and_then(Vdf::as_str)
+  }
+
+  pub fn as_str(&self) -> Option<&str> {
+    match self {
+      Vdf::Str(s) => Some(s),
+      Vdf::Obj(_) => None,
+    }
+  }
+
+  pub fn entries(&self) -> &[(String, Vdf)] {
+    match self {
+      Vdf::Obj(entries) => entries,
+      Vdf::Str(_) => &[],
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+  Str(String),
+  Open,
+  Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+  let mut tokens = Vec::new();
+  let mut chars = text.chars().peekable();
+  while let Some(&c) = chars.peek() {
+    match c {
+      c if c.is_whitespace() => {
+        chars.next();
+      }
+      '{' => {
+        chars.next();
+        tokens.push(Token::Open);
+      }
+      '}' => {
+        chars.next();
+        tokens.push(Token::Close);
+      }
+      '/' if chars.clone().nth(1) == Some('/') => {
+        for c in chars.by_ref() {
+          if c == '\n' {
+            break;
+          }
+        }
+      }
+      '[' => {
+        // Platform conditionals like [$WIN32]; they don't affect the data we read.
+        for c in chars.by_ref() {
+          if c == ']' {
+            break;
+          }
+        }
+      }
+      '"' => {
+        chars.next();
+        let mut s = String::new();
+        let mut closed = false;
+        while let Some(c) = chars.next() {
+          match c {
+            '"' => {
+              closed = true;
+              break;
+            }
+            '\\' => match chars.next() {
+              Some('n') => s.push('\n'),
+              Some('t') => s.push('\t'),
+              Some(other) => s.push(other),
+              None => break,
+            },
+            other => s.push(other),
+          }
+        }
+        if !closed {
+          return Err("VDF: unterminated string".into());
+        }
+        tokens.push(Token::Str(s));
+      }
+      _ => {
+        let mut s = String::new();
+        while let Some(&c) = chars.peek() {
+          if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+            break;
+          }
+          s.push(c);
+          chars.next();
+        }
+        tokens.push(Token::Str(s));
+      }
+    }
+  }
+  Ok(tokens)
+}
+
+fn parse_entries<I>(tokens: &mut std::iter::Peekable<I>, nested: bool) -> Result<Vec<(String, Vdf)>, String>
+where
+  I: Iterator<Item = Token>,
+{
+  let mut entries = Vec::new();
+  loop {
+    match tokens.next() {
+      None if nested => return Err("VDF: missing closing brace".into()),
+      None => return Ok(entries),
+      Some(Token::Close) if nested => return Ok(entries),
+      Some(Token::Close) => return Err("VDF: unexpected closing brace".into()),
+      Some(Token::Open) => return Err("VDF: unexpected opening brace".into()),
+      Some(Token::Str(key)) => match tokens.next() {
+        Some(Token::Str(value)) => entries.push((key, Vdf::Str(value))),
+        Some(Token::Open) => entries.push((key, Vdf::Obj(parse_entries(tokens, true)?))),
+        _ => return Err(format!("VDF: key '{}' has no value", key)),
+      },
+    }
+  }
+}
+
+/// Parses a whole document into a root object.
+pub fn parse(text: &str) -> Result<Vdf, String> {
+  let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+  let mut tokens = tokenize(text)?.into_iter().peekable();
+  Ok(Vdf::Obj(parse_entries(&mut tokens, false)?))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn library_folders_new_layout() {
+    let text = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+		"label"		""
+		"contentid"		"4872315553337051470"
+		"totalsize"		"0"
+		"apps"
+		{
+			"228980"		"390293145"
+			"620"		"12694080234"
+		}
+	}
+	"1"
+	{
+		"path"		"D:\\SteamLibrary"
+		"apps"
+		{
+			"1245620"		"52143879001"
+		}
+	}
+}
+"#;
+    let root = parse(text).unwrap();
+    let folders = root.get("libraryfolders").unwrap();
+    assert_eq!(folders.entries().len(), 2);
+    assert_eq!(folders.path(&["0", "path"]).and_then(Vdf::as_str), Some("C:\\Program Files (x86)\\Steam"));
+    assert_eq!(folders.path(&["1", "apps", "1245620"]).and_then(Vdf::as_str), Some("52143879001"));
+    assert_eq!(folders.path(&["0", "apps"]).unwrap().entries().len(), 2);
+  }
+
+  #[test]
+  fn library_folders_old_layout() {
+    let text = r#"
+"LibraryFolders"
+{
+	"TimeNextStatsReport"		"1561832478"
+	"ContentStatsID"		"-158337411110787451"
+	"1"		"D:\\SteamLibrary"
+	"2"		"E:\\Games\\Steam"
+}
+"#;
+    let root = parse(text).unwrap();
+    let folders = root.get("libraryfolders").unwrap();
+    assert_eq!(folders.get_str("1"), Some("D:\\SteamLibrary"));
+    assert_eq!(folders.get_str("2"), Some("E:\\Games\\Steam"));
+  }
+
+  #[test]
+  fn app_manifest() {
+    let text = "\u{feff}\"AppState\"\n{\n\t\"appid\"\t\t\"620\"\n\t\"Universe\"\t\t\"1\"\n\t\"name\"\t\t\"Portal 2\"\n\t\"StateFlags\"\t\t\"4\"\n\t\"installdir\"\t\t\"Portal 2\"\n\t\"LastUpdated\"\t\t\"1700000000\"\n\t\"SizeOnDisk\"\t\t\"12694080234\"\n\t\"InstalledDepots\"\n\t{\n\t\t\"621\"\n\t\t{\n\t\t\t\"manifest\"\t\t\"7386337838549713633\"\n\t\t\t\"size\"\t\t\"12694080234\"\n\t\t}\n\t}\n}\n";
+    let root = parse(text).unwrap();
+    let state = root.get("AppState").unwrap();
+    assert_eq!(state.get_str("appid"), Some("620"));
+    assert_eq!(state.get_str("name"), Some("Portal 2"));
+    assert_eq!(state.path(&["InstalledDepots", "621", "manifest"]).and_then(Vdf::as_str), Some("7386337838549713633"));
+  }
+
+  #[test]
+  fn escaped_quotes() {
+    let root = parse(r#""root" { "name" "The \"Best\" Game\\" "next" "ok" }"#).unwrap();
+    assert_eq!(root.path(&["root", "name"]).and_then(Vdf::as_str), Some("The \"Best\" Game\\"));
+    assert_eq!(root.path(&["root", "next"]).and_then(Vdf::as_str), Some("ok"));
+  }
+
+  #[test]
+  fn comments_and_conditionals() {
+    let text = r#"
+// leading comment
+"root"
+{
+	"a"	"1" // trailing comment
+	"b"	"2"	[$WIN32]
+	"c"	"3"	[!$OSX]
+}
+"#;
+    let root = parse(text).unwrap();
+    let node = root.get("root").unwrap();
+    assert_eq!(node.entries().len(), 3);
+    assert_eq!(node.get_str("a"), Some("1"));
+    assert_eq!(node.get_str("c"), Some("3"));
+  }
+
+  #[test]
+  fn unquoted_token_with_slash() {
+    let root = parse("root { path /home/user/.steam next value }").unwrap();
+    let node = root.get("root").unwrap();
+    assert_eq!(node.get_str("path"), Some("/home/user/.steam"));
+    assert_eq!(node.get_str("next"), Some("value"));
+  }
+
+  #[test]
+  fn keys_are_case_insensitive() {
+    let root = parse(r#""Root" { "Key" "v" }"#).unwrap();
+    assert_eq!(root.path(&["root", "key"]).and_then(Vdf::as_str), Some("v"));
+  }
+
+  #[test]
+  fn malformed_input() {
+    assert!(parse(r#""root" { "a" "1""#).is_err());
+    assert!(parse(r#""a" "unterminated"#).is_err());
+    assert!(parse(r#""a" }"#).is_err());
+  }
+}
@@ -5,37 +5,43 @@ export const isTauri =
 
 export type HLTBResult = {
   mainMedianHours: number | null;
-  source: "hltb" | "hltb-cache" | "html";
+  source: "hltb" | "hltb-cache" | "html" | "hltb-local";
+  stale: boolean;
 };
 
-export type SteamPriceResult = { price: number; currency: string } | null;
+export type SteamPriceResult = { price: number; currency: string; stale: boolean } | null;
 
 export async function fetchHLTB(title: string): Promise<HLTBResult> {
   if (!isTauri) throw new Error("Desktop-only: run Tauri to use HLTB.");
-  const res = await invoke<{ main_median_hours?: number | null; source?: string }>("hltb_search", { title });
+  const res = await invoke<{ main_median_hours?: number | null; source?: string; stale?: boolean }>("hltb_search", {
+    title,
+  });
   const hours = res?.main_median_hours ?? null;
   const source = (() => {
     if (res?.source === "hltb-cache") return "hltb-cache" as const;
     if (res?.source === "html") return "html" as const;
+    if (res?.source === "hltb-local") return "hltb-local" as const;
     return "hltb" as const;
   })();
-  return { mainMedianHours: hours, source };
+  return { mainMedianHours: hours, source, stale: Boolean(res?.stale) };
 }
 
 export async function fetchSteamPrice(appid: number, region?: string): Promise<SteamPriceResult> {
   if (!isTauri) throw new Error("Desktop-only: run Tauri to fetch Steam price.");
-  const res = await invoke<{ price?: number | null; currency?: string | null }>("get_steam_price_try", {
+  const res = await invoke<{ price?: number | null; currency?: string | null; stale?: boolean }>("get_steam_price_try", {
     appid,
     region,
   });
   if (res == null || res.price == null || !res.currency) {
     return null;
   }
-  return { price: res.price, currency: res.currency.toUpperCase() };
+  return { price: res.price, currency: res.currency.toUpperCase(), stale: Boolean(res.stale) };
 }
 
 export async function fetchOpenCriticScore(title: string): Promise<number | null> {
   if (!isTauri) throw new Error("Desktop-only: run Tauri to fetch OpenCritic scores.");
-  const res = await invoke<number | null>("get_opencritic_score", { title });
-  return res ?? null;
+  const res = await invoke<{ score?: number | null; source?: string; stale?: boolean }>("get_opencritic_score", {
+    title,
+  });
+  return res?.score ?? null;
 }